@@ -0,0 +1,54 @@
+#![feature(core, io, test)]
+
+extern crate mio;
+extern crate test;
+
+use mio::EventLoop;
+use test::Bencher;
+
+mod harness;
+
+// Keep clear of the ports used by the tests
+const ECHO_ADDR: &'static str = "127.0.0.1:19080";
+
+// Number of messages sent through the notify channel per iteration
+const NOTIFY_BATCH: usize = 512;
+
+#[bench]
+fn bench_echo_round_trip(b: &mut Bencher) {
+    let msg = [0u8; 64];
+
+    let mut event_loop = EventLoop::new().unwrap();
+    let mut echo = Some(harness::Echo::new(&mut event_loop, ECHO_ADDR));
+
+    // Reported as MB/s of echoed data
+    b.bytes = msg.len() as u64;
+
+    b.iter(|| {
+        let e = echo.take().unwrap();
+        echo = Some(e.round_trip(&mut event_loop, &msg));
+    });
+}
+
+#[bench]
+fn bench_notify_throughput(b: &mut Bencher) {
+    let mut event_loop = EventLoop::new().unwrap();
+    let sender = event_loop.channel();
+
+    let mut counter = Some(harness::Counter { received: 0, expected: NOTIFY_BATCH });
+
+    // Each byte stands for one message, so the reported MB/s reads as
+    // millions of messages per second
+    b.bytes = NOTIFY_BATCH as u64;
+
+    b.iter(|| {
+        let mut c = counter.take().unwrap();
+        c.received = 0;
+
+        for i in range(0, NOTIFY_BATCH) {
+            sender.send(i).unwrap();
+        }
+
+        counter = Some(event_loop.run(c).ok().expect("failed to execute event loop"));
+    });
+}
@@ -0,0 +1,129 @@
+//! A small echo server / client pair driven by a single event loop, used to
+//! measure round trip throughput.
+
+use mio::*;
+use mio::buf::{ByteBuf, MutByteBuf, SliceBuf};
+use mio::net::*;
+use mio::net::tcp::*;
+
+pub type EchoEventLoop = EventLoop<(), ()>;
+
+const SERVER: Token = Token(0);
+const CLIENT: Token = Token(1);
+const CONN: Token = Token(2);
+
+pub struct Echo {
+    srv: TcpAcceptor,
+    cli: TcpSocket,
+    conn: Option<TcpSocket>,
+    // Bytes still expected back for the current round trip
+    pending: usize,
+    buf: Option<MutByteBuf>,
+}
+
+impl Echo {
+    /// Sets up a server listening on `addr` and a client connected to it.
+    /// The connection is established by the time this returns.
+    pub fn new(event_loop: &mut EchoEventLoop, addr: &str) -> Echo {
+        let addr = SockAddr::parse(addr).unwrap();
+
+        let srv = TcpSocket::v4().unwrap();
+        srv.set_reuseaddr(true).unwrap();
+
+        let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+        event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::level()).unwrap();
+
+        let cli = TcpSocket::v4().unwrap();
+        cli.connect(&addr).unwrap();
+        event_loop.register_opt(&cli, CLIENT, Interest::readable(), PollOpt::level()).unwrap();
+
+        let echo = Echo {
+            srv: srv,
+            cli: cli,
+            conn: None,
+            pending: 0,
+            buf: Some(ByteBuf::mut_with_capacity(2048)),
+        };
+
+        // Run until the connection has been accepted
+        event_loop.run(echo).ok().expect("failed to accept the connection")
+    }
+
+    /// Sends `msg` and runs the event loop until it has been echoed back.
+    pub fn round_trip(self, event_loop: &mut EchoEventLoop, msg: &[u8]) -> Echo {
+        let mut echo = self;
+
+        echo.pending = msg.len();
+        echo.cli.write(&mut SliceBuf::wrap(msg)).unwrap();
+
+        event_loop.run(echo).ok().expect("failed to echo the message")
+    }
+
+    fn echo(&mut self) {
+        let mut buf = self.buf.take().unwrap();
+        let conn = self.conn.as_ref().unwrap();
+
+        if conn.read(&mut buf).unwrap().would_block() {
+            self.buf = Some(buf);
+            return;
+        }
+
+        // Messages are small, so writing everything back never blocks
+        let mut buf = buf.flip();
+        conn.write(&mut buf).unwrap();
+
+        self.buf = Some(buf.flip());
+    }
+
+    fn receive(&mut self, event_loop: &mut EchoEventLoop) {
+        let mut buf = self.buf.take().unwrap();
+
+        if let NonBlock::Ready(n) = self.cli.read(&mut buf).unwrap() {
+            self.pending -= n;
+        }
+
+        self.buf = Some(buf.flip().flip());
+
+        if self.pending == 0 {
+            event_loop.shutdown();
+        }
+    }
+}
+
+impl Handler for Echo {
+    type Timeout = ();
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut EchoEventLoop, token: Token, _: ReadHint) {
+        match token {
+            SERVER => {
+                let conn = self.srv.accept().unwrap().unwrap();
+                event_loop.register_opt(&conn, CONN, Interest::readable(), PollOpt::level()).unwrap();
+                self.conn = Some(conn);
+                event_loop.shutdown();
+            }
+            CONN => self.echo(),
+            CLIENT => self.receive(event_loop),
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+/// Counts notifications, stopping the event loop once `expected` arrived.
+pub struct Counter {
+    pub received: usize,
+    pub expected: usize,
+}
+
+impl Handler for Counter {
+    type Timeout = ();
+    type Message = usize;
+
+    fn notify(&mut self, event_loop: &mut EventLoop<(), usize>, _: usize) {
+        self.received += 1;
+
+        if self.received == self.expected {
+            event_loop.shutdown();
+        }
+    }
+}
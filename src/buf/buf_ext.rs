@@ -0,0 +1,246 @@
+use std::cmp;
+use std::slice::bytes;
+use bytes::{Buf, MutBuf};
+use buf::Endianness;
+use buf::Endianness::{BigEndian, LittleEndian};
+
+/// Parsing helpers for readable buffers.
+///
+/// `find` and `read_until` only look at the bytes returned by `bytes()`. For
+/// buffers that are contiguous, such as `ByteBuf` and `SliceBuf`, that is
+/// all of the remaining data; for a `RingBuf` that wrapped around it is only
+/// the part up to the end of its storage.
+///
+/// The integer reads return `None`, and consume nothing, if fewer bytes
+/// remain than the integer is wide, meaning more data has to arrive first.
+/// They read across the end of a `RingBuf`'s storage.
+pub trait BufExt : Buf {
+    /// Returns the offset of the first occurrence of `needle`, relative to
+    /// the current position.
+    fn find(&self, needle: u8) -> Option<usize> {
+        self.bytes().iter().position(|&b| b == needle)
+    }
+
+    /// Copies bytes into `dst` up to and including the first `delim`, and
+    /// returns whether `delim` was reached. Returns false, having copied as
+    /// much as possible, if this buffer runs out of data or `dst` runs out
+    /// of space first; call it again with the next buffer to complete a
+    /// line that spans two of them.
+    fn read_until<M: MutBuf>(&mut self, delim: u8, dst: &mut M) -> bool {
+        while self.has_remaining() && dst.has_remaining() {
+            let (cnt, found) = {
+                let src = self.bytes();
+                let (len, found) = match src.iter().position(|&b| b == delim) {
+                    Some(idx) => (idx + 1, true),
+                    None => (src.len(), false)
+                };
+
+                let dst = dst.mut_bytes();
+                let cnt = cmp::min(len, dst.len());
+
+                bytes::copy_memory(dst, &src[..cnt]);
+                (cnt, found && cnt == len)
+            };
+
+            self.advance(cnt);
+            dst.advance(cnt);
+
+            if found {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn read_u16_be(&mut self) -> Option<u16> {
+        read_uint(self, 2, BigEndian).map(|v| v as u16)
+    }
+
+    fn read_u32_be(&mut self) -> Option<u32> {
+        read_uint(self, 4, BigEndian).map(|v| v as u32)
+    }
+
+    fn read_u64_be(&mut self) -> Option<u64> {
+        read_uint(self, 8, BigEndian)
+    }
+
+    fn read_u16_le(&mut self) -> Option<u16> {
+        read_uint(self, 2, LittleEndian).map(|v| v as u16)
+    }
+
+    fn read_u32_le(&mut self) -> Option<u32> {
+        read_uint(self, 4, LittleEndian).map(|v| v as u32)
+    }
+
+    fn read_u64_le(&mut self) -> Option<u64> {
+        read_uint(self, 8, LittleEndian)
+    }
+}
+
+impl<B: Buf> BufExt for B {
+}
+
+/// Writes fixed-width integers to writable buffers, the counterpart of the
+/// reads in `BufExt`.
+///
+/// Each method returns false, and writes nothing, if the buffer has less
+/// room left than the integer is wide.
+pub trait MutBufExt : MutBuf {
+    fn write_u16_be(&mut self, val: u16) -> bool {
+        write_uint(self, val as u64, 2, BigEndian)
+    }
+
+    fn write_u32_be(&mut self, val: u32) -> bool {
+        write_uint(self, val as u64, 4, BigEndian)
+    }
+
+    fn write_u64_be(&mut self, val: u64) -> bool {
+        write_uint(self, val, 8, BigEndian)
+    }
+
+    fn write_u16_le(&mut self, val: u16) -> bool {
+        write_uint(self, val as u64, 2, LittleEndian)
+    }
+
+    fn write_u32_le(&mut self, val: u32) -> bool {
+        write_uint(self, val as u64, 4, LittleEndian)
+    }
+
+    fn write_u64_le(&mut self, val: u64) -> bool {
+        write_uint(self, val, 8, LittleEndian)
+    }
+}
+
+impl<B: MutBuf> MutBufExt for B {
+}
+
+// Reads the integer a byte at a time, as it may span the end of the
+// buffer's storage
+fn read_uint<B: Buf + ?Sized>(buf: &mut B, len: usize, endianness: Endianness) -> Option<u64> {
+    if buf.remaining() < len {
+        return None;
+    }
+
+    let mut val = 0u64;
+
+    for i in range(0, len) {
+        let b = buf.bytes()[0] as u64;
+        buf.advance(1);
+
+        val = match endianness {
+            BigEndian => (val << 8) | b,
+            LittleEndian => val | (b << (8 * i))
+        };
+    }
+
+    Some(val)
+}
+
+fn write_uint<B: MutBuf + ?Sized>(buf: &mut B, val: u64, len: usize, endianness: Endianness) -> bool {
+    if buf.remaining() < len {
+        return false;
+    }
+
+    for i in range(0, len) {
+        let shift = match endianness {
+            BigEndian => 8 * (len - 1 - i),
+            LittleEndian => 8 * i
+        };
+
+        buf.mut_bytes()[0] = (val >> shift) as u8;
+        buf.advance(1);
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Buf, ByteBuf, RingBuf, SliceBuf};
+    use super::{BufExt, MutBufExt};
+
+    #[test]
+    fn test_find() {
+        let buf = SliceBuf::wrap(b"GET / HTTP/1.1\r\n");
+
+        assert_eq!(Some(3), buf.find(b' '));
+        assert_eq!(Some(15), buf.find(b'\n'));
+        assert_eq!(None, buf.find(b'x'));
+    }
+
+    #[test]
+    fn test_read_until_across_buffers() {
+        let mut first = SliceBuf::wrap(b"hel");
+        let mut second = SliceBuf::wrap(b"lo\nworld");
+        let mut line = ByteBuf::mut_with_capacity(64);
+
+        // The line continues in the next buffer
+        assert!(!first.read_until(b'\n', &mut line));
+        assert!(!first.has_remaining());
+
+        assert!(second.read_until(b'\n', &mut line));
+        assert_eq!(b"world", second.bytes());
+
+        assert_eq!(b"hello\n", line.flip().bytes());
+    }
+
+    #[test]
+    fn test_read_uint() {
+        let mut buf = SliceBuf::wrap(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07]);
+
+        assert_eq!(Some(0x0102), buf.read_u16_be());
+        assert_eq!(Some(0x06050403), buf.read_u32_le());
+
+        // Only a part of the integer has arrived: nothing is consumed
+        assert_eq!(None, buf.read_u16_be());
+        assert_eq!(None, buf.read_u64_le());
+        assert_eq!(1, buf.remaining());
+        assert_eq!(b"\x07", buf.bytes());
+    }
+
+    #[test]
+    fn test_write_uint() {
+        let mut buf = ByteBuf::mut_with_capacity(16);
+
+        assert!(buf.write_u16_be(0x0102));
+        assert!(buf.write_u16_le(0x0102));
+        assert!(buf.write_u32_be(0x03040506));
+        assert!(buf.write_u64_be(0x0708090a0b0c0d0e));
+
+        let mut buf = buf.flip();
+
+        assert_eq!(Some(0x0102), buf.read_u16_be());
+        assert_eq!(Some(0x0201), buf.read_u16_be());
+        assert_eq!(Some(0x03040506), buf.read_u32_be());
+        assert_eq!(Some(0x0e0d0c0b0a090807), buf.read_u64_le());
+        assert!(!buf.has_remaining());
+
+        // No room left for the integer: nothing is written
+        let mut buf = ByteBuf::mut_with_capacity(4);
+        assert!(buf.write_u32_be(1));
+        assert!(!buf.write_u16_be(2));
+        assert_eq!(b"\x00\x00\x00\x01", buf.flip().bytes());
+    }
+
+    #[test]
+    fn test_uint_across_ring_end() {
+        let mut ring = RingBuf::new(8);
+
+        // Fill the ring, then consume all but the last two bytes
+        assert!(ring.writer().write_u32_be(0));
+        assert!(ring.writer().write_u32_be(0x0a0b));
+        assert!(!ring.writer().write_u16_be(1));
+
+        ring.reader().advance(6);
+
+        // The write wraps around to the start of the storage
+        assert!(ring.writer().write_u32_be(0x01020304));
+        assert!(!ring.writer().write_u16_be(1));
+        assert_eq!(6, ring.reader().remaining());
+
+        assert_eq!(Some(0x0a0b), ring.reader().read_u16_be());
+        assert_eq!(Some(0x01020304), ring.reader().read_u32_be());
+        assert!(!ring.reader().has_remaining());
+    }
+}
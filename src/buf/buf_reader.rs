@@ -0,0 +1,88 @@
+use std::{cmp, io};
+use std::slice::bytes;
+use bytes::Buf;
+
+/// Adapts a buffer of already received bytes to `std::io::Read`, so that
+/// parsers written against `Read` can consume it.
+///
+/// Reading never blocks. Once the buffer is exhausted, `read` fails with an
+/// error of kind `ResourceUnavailable`, the `Read` equivalent of
+/// `WouldBlock`, rather than returning `Ok(0)`, which `Read` users take to
+/// mean end of stream.
+///
+/// Callers drive a parser with this as follows: read from the socket into
+/// the buffer, run the parser on a `BufReader`, and if the parser stops on
+/// `ResourceUnavailable`, wait for the socket to become readable and resume
+/// the parser on a buffer holding the new data. Parsers that cannot resume
+/// part way need to be rerun from the start of the message, so keep the
+/// bytes of an incomplete message around until it has been parsed.
+pub struct BufReader<B: Buf> {
+    buf: B,
+}
+
+impl<B: Buf> BufReader<B> {
+    pub fn new(buf: B) -> BufReader<B> {
+        BufReader { buf: buf }
+    }
+
+    /// Number of bytes left to read.
+    pub fn remaining(&self) -> usize {
+        self.buf.remaining()
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.buf
+    }
+
+    pub fn into_inner(self) -> B {
+        self.buf
+    }
+}
+
+impl<B: Buf> io::Read for BufReader<B> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        if dst.len() == 0 {
+            return Ok(0);
+        }
+
+        if self.buf.remaining() == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::ResourceUnavailable,
+                "no more buffered data",
+                None));
+        }
+
+        let cnt = {
+            let src = self.buf.bytes();
+            let cnt = cmp::min(src.len(), dst.len());
+
+            bytes::copy_memory(dst, &src[..cnt]);
+            cnt
+        };
+
+        self.buf.advance(cnt);
+        Ok(cnt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind, Read};
+    use bytes::SliceBuf;
+    use super::BufReader;
+
+    #[test]
+    fn test_read_until_exhausted() {
+        let mut rd = BufReader::new(SliceBuf::wrap(b"hello world"));
+        let mut dst = [0u8; 8];
+
+        assert_eq!(8, rd.read(&mut dst).unwrap());
+        assert_eq!(b"hello wo", &dst[..]);
+
+        assert_eq!(3, rd.read(&mut dst).unwrap());
+        assert_eq!(b"rld", &dst[..3]);
+
+        let err = rd.read(&mut dst).unwrap_err();
+        assert_eq!(ErrorKind::ResourceUnavailable, err.kind());
+    }
+}
@@ -0,0 +1,159 @@
+use std::cmp;
+use error::MioResult;
+use io::IoWriter;
+use io::NonBlock::{self, Ready, WouldBlock};
+
+/// Coalesces small writes into a buffer that is written to the underlying
+/// handle in one go.
+///
+/// Writes only go to the handle when the buffer fills up or when `flush` is
+/// called. If the handle cannot take all of the data, the remainder stays
+/// buffered and `flush` returns `WouldBlock`. In that case `wants_writable`
+/// returns true: register (or, with oneshot, reregister) the handle for
+/// writable interest and call `flush` again once it is writable.
+pub struct BufferedWriter<'a, W: IoWriter + 'a> {
+    io: &'a W,
+    buf: Vec<u8>,
+    // Offset of the first byte that has not been written to the handle yet
+    pos: usize,
+    cap: usize,
+}
+
+impl<'a, W: IoWriter + 'a> BufferedWriter<'a, W> {
+    /// Creates a writer buffering up to `capacity` bytes.
+    pub fn new(io: &'a W, capacity: usize) -> BufferedWriter<'a, W> {
+        BufferedWriter {
+            io: io,
+            buf: Vec::with_capacity(capacity),
+            pos: 0,
+            cap: capacity,
+        }
+    }
+
+    /// Buffers as much of `data` as fits, flushing first if the buffer is
+    /// full. Returns the number of bytes buffered, or `WouldBlock` if the
+    /// buffer is full and could not be flushed.
+    pub fn write_slice(&mut self, data: &[u8]) -> MioResult<NonBlock<usize>> {
+        if self.remaining() < data.len() {
+            try!(self.flush());
+        }
+
+        let cnt = cmp::min(self.remaining(), data.len());
+
+        if cnt == 0 && data.len() > 0 {
+            return Ok(WouldBlock);
+        }
+
+        self.buf.push_all(&data[..cnt]);
+        Ok(Ready(cnt))
+    }
+
+    /// Buffers as much of `s` as fits, see `write_slice`.
+    pub fn write_str(&mut self, s: &str) -> MioResult<NonBlock<usize>> {
+        self.write_slice(s.as_bytes())
+    }
+
+    /// Writes the buffered data to the handle. Returns `WouldBlock`, keeping
+    /// the data that was not written, if the handle is not able to take all
+    /// of it.
+    pub fn flush(&mut self) -> MioResult<NonBlock<()>> {
+        while self.pos < self.buf.len() {
+            let res = try!(self.io.write_slice(&self.buf[self.pos..]));
+
+            match res {
+                Ready(cnt) => self.pos += cnt,
+                WouldBlock => {
+                    self.compact();
+                    return Ok(WouldBlock);
+                }
+            }
+        }
+
+        self.buf.clear();
+        self.pos = 0;
+
+        Ok(Ready(()))
+    }
+
+    /// Returns true when buffered data is waiting for the handle to become
+    /// writable.
+    pub fn wants_writable(&self) -> bool {
+        self.buffered() > 0
+    }
+
+    /// Number of bytes buffered but not yet written to the handle.
+    pub fn buffered(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    /// Number of bytes that can be buffered before a flush is needed.
+    pub fn remaining(&self) -> usize {
+        self.cap - self.buffered()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.io
+    }
+
+    // Moves the unwritten bytes to the front of the buffer
+    fn compact(&mut self) {
+        if self.pos > 0 {
+            let rest = self.buf[self.pos..].to_vec();
+            self.buf.clear();
+            self.buf.push_all(&rest);
+            self.pos = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferedWriter;
+    use io::{self, IoReader};
+    use io::NonBlock::Ready;
+
+    #[test]
+    fn test_buffered_writes_are_coalesced() {
+        let (reader, writer) = io::pipe().unwrap();
+        let mut buf = [0u8; 64];
+
+        {
+            let mut w = BufferedWriter::new(&writer, 16);
+
+            w.write_str("hello ").unwrap();
+            w.write_str("world").unwrap();
+
+            // Nothing reached the pipe yet
+            assert!(reader.read_slice(&mut buf).unwrap().would_block());
+            assert!(w.wants_writable());
+            assert_eq!(11, w.buffered());
+
+            assert!(w.flush().unwrap().unwrap() == ());
+            assert!(!w.wants_writable());
+        }
+
+        match reader.read_slice(&mut buf).unwrap() {
+            Ready(n) => assert_eq!(b"hello world", &buf[..n]),
+            _ => panic!("expected the flushed data")
+        }
+    }
+
+    #[test]
+    fn test_write_flushes_when_full() {
+        let (reader, writer) = io::pipe().unwrap();
+        let mut buf = [0u8; 64];
+
+        let mut w = BufferedWriter::new(&writer, 4);
+
+        assert_eq!(4, w.write_str("abcd").unwrap().unwrap());
+        assert_eq!(2, w.write_str("ef").unwrap().unwrap());
+
+        // The first four bytes were flushed to make room
+        match reader.read_slice(&mut buf).unwrap() {
+            Ready(n) => assert_eq!(b"abcd", &buf[..n]),
+            _ => panic!("expected the flushed data")
+        }
+
+        assert_eq!(2, w.buffered());
+    }
+}
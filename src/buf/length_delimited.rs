@@ -0,0 +1,286 @@
+use std::fmt;
+use std::slice::bytes;
+use bytes::{Buf, MutBuf, ByteBuf};
+
+use self::Endianness::{BigEndian, LittleEndian};
+
+/// Largest frame a codec accepts unless configured otherwise, 8MB.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// Byte order of the length field.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Endianness {
+    BigEndian,
+    LittleEndian,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CodecError {
+    /// The length field announced a frame larger than the configured
+    /// maximum. The frame has not been consumed and the stream cannot be
+    /// resynchronized, so the connection should be closed.
+    FrameTooLarge(u64),
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CodecError::FrameTooLarge(len) => write!(fmt, "frame of {} bytes exceeds the maximum frame length", len)
+        }
+    }
+}
+
+/// Splits a byte stream into frames, each preceded by a length field that
+/// holds the number of bytes in the frame, not counting the field itself.
+///
+/// The length field is 1 to 8 bytes long, big endian unless configured
+/// otherwise. Data arrives from the socket in arbitrary pieces, so a frame,
+/// and even its length field, may span several reads; `decode` leaves the
+/// buffer untouched until a whole frame is available.
+///
+/// The length field comes from the peer, so it is checked against a
+/// maximum frame length as soon as it arrives, `DEFAULT_MAX_FRAME_LEN`
+/// unless set with `max_frame_len`. A peer announcing a larger frame gets a
+/// `FrameTooLarge` error instead of making the application buffer data for
+/// it. Use `frame_len` to size the read buffer for the frame being
+/// received.
+///
+/// ```
+/// use mio::buf::LengthDelimitedCodec;
+///
+/// let codec = LengthDelimitedCodec::new(2).max_frame_len(16 * 1024);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct LengthDelimitedCodec {
+    field_len: usize,
+    endianness: Endianness,
+    max_frame_len: usize,
+}
+
+impl LengthDelimitedCodec {
+    /// Creates a codec with a length field of `field_len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `field_len` is not between 1 and 8.
+    pub fn new(field_len: usize) -> LengthDelimitedCodec {
+        assert!(field_len >= 1 && field_len <= 8, "length field must be 1 to 8 bytes; field_len={}", field_len);
+
+        LengthDelimitedCodec {
+            field_len: field_len,
+            endianness: BigEndian,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+        }
+    }
+
+    /// Sets the byte order of the length field.
+    pub fn endianness(mut self, endianness: Endianness) -> LengthDelimitedCodec {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Sets the largest frame, in bytes, that `decode` accepts. Larger
+    /// frames are rejected as soon as their length field is read, before
+    /// anything is allocated for them. Only raise it as far as the protocol
+    /// requires.
+    pub fn max_frame_len(mut self, max: usize) -> LengthDelimitedCodec {
+        self.max_frame_len = max;
+        self
+    }
+
+    /// Returns the length of the frame at the start of `src`, not counting
+    /// the length field, once the length field is complete, and `Ok(None)`
+    /// before that. Fails with `FrameTooLarge` if the frame exceeds the
+    /// maximum, so that nothing is allocated for it.
+    pub fn frame_len(&self, src: &[u8]) -> Result<Option<usize>, CodecError> {
+        if src.len() < self.field_len {
+            return Ok(None);
+        }
+
+        let len = self.read_len(&src[..self.field_len]);
+
+        if len > self.max_frame_len as u64 {
+            return Err(CodecError::FrameTooLarge(len));
+        }
+
+        Ok(Some(len as usize))
+    }
+
+    /// Takes the next frame out of `buf`. Returns `Ok(None)`, consuming
+    /// nothing, if `buf` does not hold a complete frame yet.
+    pub fn decode(&self, buf: &mut ByteBuf) -> Result<Option<ByteBuf>, CodecError> {
+        let (len, frame) = {
+            let src = buf.bytes();
+
+            let len = match try!(self.frame_len(src)) {
+                Some(len) => len,
+                None => return Ok(None)
+            };
+
+            if src.len() - self.field_len < len {
+                return Ok(None);
+            }
+
+            let start = self.field_len;
+            (len, to_byte_buf(&src[start..start + len]))
+        };
+
+        buf.advance(self.field_len + len);
+        Ok(Some(frame))
+    }
+
+    /// Prepends the length field to `frame`.
+    pub fn encode(&self, frame: &[u8]) -> Result<ByteBuf, CodecError> {
+        let len = frame.len() as u64;
+
+        if frame.len() > self.max_frame_len || (self.field_len < 8 && len >> (self.field_len * 8) != 0) {
+            return Err(CodecError::FrameTooLarge(len));
+        }
+
+        let mut dst = Vec::with_capacity(self.field_len + frame.len());
+
+        for i in range(0, self.field_len) {
+            let shift = match self.endianness {
+                BigEndian => (self.field_len - 1 - i) * 8,
+                LittleEndian => i * 8,
+            };
+
+            dst.push((len >> shift) as u8);
+        }
+
+        dst.push_all(frame);
+        Ok(to_byte_buf(dst.as_slice()))
+    }
+
+    fn read_len(&self, field: &[u8]) -> u64 {
+        let mut len = 0u64;
+
+        for i in range(0, field.len()) {
+            let byte = match self.endianness {
+                BigEndian => field[i],
+                LittleEndian => field[field.len() - 1 - i],
+            };
+
+            len = (len << 8) | byte as u64;
+        }
+
+        len
+    }
+}
+
+fn to_byte_buf(src: &[u8]) -> ByteBuf {
+    let mut buf = ByteBuf::mut_with_capacity(src.len());
+
+    bytes::copy_memory(buf.mut_bytes(), src);
+    buf.advance(src.len());
+
+    buf.flip()
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Buf;
+    use super::{LengthDelimitedCodec, CodecError, DEFAULT_MAX_FRAME_LEN, to_byte_buf};
+    use super::Endianness::{BigEndian, LittleEndian};
+
+    // Cheap deterministic generator (xorshift), so failures can be
+    // reproduced
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self, max: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % max as u64) as usize
+        }
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        let codec = LengthDelimitedCodec::new(2);
+        let mut buf = to_byte_buf(b"\x00\x03foo\x00\x00\x00\x05hello\x00");
+
+        assert_eq!(b"foo", codec.decode(&mut buf).unwrap().unwrap().bytes());
+        assert_eq!(b"", codec.decode(&mut buf).unwrap().unwrap().bytes());
+        assert_eq!(b"hello", codec.decode(&mut buf).unwrap().unwrap().bytes());
+
+        // Half of a length field
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert_eq!(1, buf.remaining());
+    }
+
+    #[test]
+    fn test_little_endian() {
+        let codec = LengthDelimitedCodec::new(4).endianness(LittleEndian);
+        let mut buf = to_byte_buf(b"\x03\x00\x00\x00foo");
+
+        assert_eq!(b"foo", codec.decode(&mut buf).unwrap().unwrap().bytes());
+    }
+
+    #[test]
+    fn test_frame_too_large() {
+        let codec = LengthDelimitedCodec::new(8).max_frame_len(4);
+
+        // Rejected from the length field alone, the body never arrived
+        let mut buf = to_byte_buf(b"\xff\xff\xff\xff\xff\xff\xff\xff");
+        assert_eq!(Some(CodecError::FrameTooLarge(0xffff_ffff_ffff_ffff)), codec.decode(&mut buf).err());
+
+        assert_eq!(Some(CodecError::FrameTooLarge(5)), codec.encode(b"hello").err());
+        assert_eq!(Some(CodecError::FrameTooLarge(256)), LengthDelimitedCodec::new(1).encode(&[0; 256]).err());
+    }
+
+    #[test]
+    fn test_default_max_frame_len() {
+        let codec = LengthDelimitedCodec::new(4);
+
+        // A 4GB frame is refused from its length field, nothing is buffered
+        let mut buf = to_byte_buf(b"\xff\xff\xff\xff");
+        assert_eq!(Some(CodecError::FrameTooLarge(0xffff_ffff)), codec.decode(&mut buf).err());
+        assert_eq!(4, buf.remaining());
+
+        let max = DEFAULT_MAX_FRAME_LEN as u32;
+        let field = [(max >> 24) as u8, (max >> 16) as u8, (max >> 8) as u8, max as u8];
+        assert_eq!(Ok(Some(DEFAULT_MAX_FRAME_LEN)), codec.frame_len(&field));
+
+        let field = [(max >> 24) as u8, (max >> 16) as u8, (max >> 8) as u8, max as u8 + 1];
+        assert_eq!(Err(CodecError::FrameTooLarge(DEFAULT_MAX_FRAME_LEN as u64 + 1)), codec.frame_len(&field));
+
+        assert_eq!(Ok(None), codec.frame_len(b"\x00\x00"));
+    }
+
+    #[test]
+    fn test_byte_at_a_time() {
+        let mut rng = Rng(42);
+
+        for _ in range(0, 200us) {
+            let field_len = rng.next(8) + 1;
+            let endianness = if rng.next(2) == 0 { BigEndian } else { LittleEndian };
+            let codec = LengthDelimitedCodec::new(field_len).endianness(endianness);
+
+            let max = if field_len == 1 { 256 } else { 600 };
+            let frame: Vec<u8> = range(0, rng.next(max)).map(|_| rng.next(256) as u8).collect();
+
+            let encoded = codec.encode(frame.as_slice()).unwrap();
+            let encoded = encoded.bytes();
+
+            // Deliver the stream one more byte at a time; nothing is decoded
+            // until the last byte has arrived
+            for n in range(0, encoded.len() + 1) {
+                let mut buf = to_byte_buf(&encoded[..n]);
+
+                match codec.decode(&mut buf).unwrap() {
+                    Some(decoded) => {
+                        assert_eq!(encoded.len(), n);
+                        assert_eq!(frame.as_slice(), decoded.bytes());
+                        assert!(!buf.has_remaining());
+                    }
+                    None => {
+                        assert!(n < encoded.len());
+                        assert_eq!(n, buf.remaining());
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::{cmp, usize};
+use std::slice::bytes;
+use bytes::{ByteBuf, MutBuf};
+
+/// A `MutBuf` over an owned `Vec<u8>` that grows as it is written to, for
+/// building messages whose size is not known up front.
+///
+/// Only the bytes written so far are part of the buffer; `flip` returns
+/// them as a readable `ByteBuf`.
+pub struct VecBuf {
+    vec: Vec<u8>,
+    // Number of bytes written
+    len: usize,
+}
+
+impl VecBuf {
+    pub fn new() -> VecBuf {
+        VecBuf::with_capacity(0)
+    }
+
+    /// Creates a buffer that can hold `capacity` bytes before growing.
+    pub fn with_capacity(capacity: usize) -> VecBuf {
+        VecBuf {
+            vec: Vec::with_capacity(capacity),
+            len: 0,
+        }
+    }
+
+    /// Wraps `vec`, reusing its allocation. Its contents are kept as the
+    /// bytes written so far, so writes are appended to them; clear the
+    /// `Vec` first to start out empty.
+    pub fn from_vec(vec: Vec<u8>) -> VecBuf {
+        let len = vec.len();
+
+        VecBuf {
+            vec: vec,
+            len: len,
+        }
+    }
+
+    /// Appends all of `src`, growing the buffer as needed.
+    pub fn write_slice(&mut self, src: &[u8]) {
+        self.vec.truncate(self.len);
+        self.vec.push_all(src);
+        self.len += src.len();
+    }
+
+    /// The bytes written so far.
+    pub fn bytes(&self) -> &[u8] {
+        &self.vec[..self.len]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    /// Returns a readable buffer containing exactly the bytes written.
+    pub fn flip(self) -> ByteBuf {
+        let mut buf = ByteBuf::mut_with_capacity(self.len);
+
+        bytes::copy_memory(buf.mut_bytes(), self.bytes());
+        buf.advance(self.len);
+
+        buf.flip()
+    }
+
+    pub fn into_vec(mut self) -> Vec<u8> {
+        self.vec.truncate(self.len);
+        self.vec
+    }
+}
+
+impl MutBuf for VecBuf {
+    // The buffer grows on demand, so there is always room left
+    fn remaining(&self) -> usize {
+        usize::MAX - self.len
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        assert!(self.len + cnt <= self.vec.len(), "advanced past the written bytes");
+        self.len += cnt;
+    }
+
+    fn mut_bytes<'a>(&'a mut self) -> &'a mut [u8] {
+        if self.len == self.vec.len() {
+            // Double the space, zero filling it so it can be handed out
+            let grow = cmp::max(self.vec.len(), 64);
+            self.vec.extend(::std::iter::repeat(0u8).take(grow));
+        }
+
+        &mut self.vec[self.len..]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Buf, MutBuf};
+    use super::VecBuf;
+
+    #[test]
+    fn test_grows_past_capacity() {
+        let mut buf = VecBuf::with_capacity(4);
+
+        buf.write_slice(b"hello");
+        buf.write_slice(b" ");
+
+        // Writing through MutBuf grows the buffer as well
+        let world = b"world";
+        {
+            let dst = buf.mut_bytes();
+            assert!(dst.len() >= world.len());
+
+            for (d, s) in dst.iter_mut().zip(world.iter()) {
+                *d = *s;
+            }
+        }
+        buf.advance(world.len());
+
+        assert!(buf.capacity() >= 11);
+        assert_eq!(b"hello world", buf.bytes());
+
+        let buf = buf.flip();
+        assert_eq!(11, buf.remaining());
+        assert_eq!(b"hello world", buf.bytes());
+    }
+
+    #[test]
+    fn test_from_vec() {
+        let mut vec = Vec::with_capacity(3);
+        vec.push_all(b"abc");
+
+        let mut buf = VecBuf::from_vec(vec);
+        assert_eq!(b"abc", buf.bytes());
+
+        // Full already, so writing grows the vec
+        buf.write_slice(b"def");
+        assert!(buf.capacity() >= 6);
+        assert_eq!(b"abcdef", buf.bytes());
+
+        let buf = buf.flip();
+        assert_eq!(6, buf.remaining());
+        assert_eq!(b"abcdef", buf.bytes());
+    }
+}
@@ -0,0 +1,142 @@
+//! Name resolution off the event loop thread
+//!
+//! `getaddrinfo` blocks, so it must not run on the thread of an event loop.
+//! A `Resolver` hands lookups to a small pool of worker threads and sends
+//! each result back to the event loop as a message, which the handler
+//! receives in `Handler::notify` along with the token the lookup was
+//! started with.
+
+use std::old_io::IoResult;
+use std::old_io::net::get_host_addresses;
+use std::old_io::timer::sleep;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Sender, Receiver};
+use std::thread::Thread;
+use std::time::Duration;
+use error::{MioResult, MioError};
+use event_loop::{EventLoop, EventLoopSender};
+use net::{SockAddr, Port};
+use notify::NotifyError;
+use os::token::Token;
+
+/// The outcome of a lookup started with `Resolver::resolve`.
+#[derive(Debug)]
+pub struct Resolved {
+    /// The token passed to `resolve`.
+    pub token: Token,
+    /// The addresses the host name resolved to, each with the requested
+    /// port, or the error `getaddrinfo` failed with.
+    pub result: IoResult<Vec<SockAddr>>,
+}
+
+struct Job {
+    host: String,
+    port: Port,
+    token: Token,
+}
+
+/// Resolves host names on a pool of worker threads and delivers the results
+/// to an event loop.
+///
+/// The results are sent through the event loop's channel, so the message
+/// type of the loop has to be able to carry them: `to_msg` wraps each
+/// `Resolved` into a message. A loop that only deals with lookups can use
+/// `Resolved` itself as its message type.
+///
+/// ```
+/// use mio::{EventLoop, Handler, Token};
+/// use mio::dns::{Resolver, Resolved};
+///
+/// struct MyHandler;
+///
+/// impl Handler for MyHandler {
+///     type Timeout = ();
+///     type Message = Resolved;
+///
+///     fn notify(&mut self, event_loop: &mut EventLoop<(), Resolved>, msg: Resolved) {
+///         assert_eq!(Token(1), msg.token);
+///         event_loop.shutdown();
+///     }
+/// }
+///
+/// fn identity(msg: Resolved) -> Resolved { msg }
+///
+/// let mut event_loop = EventLoop::new().unwrap();
+/// let resolver = Resolver::new(&event_loop, 2, identity).unwrap();
+///
+/// resolver.resolve("localhost", 80, Token(1));
+/// let _ = event_loop.run(MyHandler);
+/// ```
+///
+/// Lookups are processed in the order they were started, by up to
+/// `threads` workers at a time. Dropping the resolver lets the workers
+/// finish the lookups already started, then stop.
+pub struct Resolver {
+    jobs: Sender<Job>,
+}
+
+impl Resolver {
+    /// Starts `threads` worker threads delivering results to `event_loop`.
+    /// Fails with an `InvalidConfig` error if `threads` is zero.
+    pub fn new<T, M: Send>(event_loop: &EventLoop<T, M>, threads: usize, to_msg: fn(Resolved) -> M) -> MioResult<Resolver> {
+        if threads == 0 {
+            return Err(MioError::invalid_config("resolver threads must be greater than zero"));
+        }
+
+        let (tx, rx) = channel();
+        let rx = Arc::new(Mutex::new(rx));
+
+        for _ in range(0, threads) {
+            let rx = rx.clone();
+            let sender = event_loop.channel();
+
+            Thread::spawn(move || work(rx, sender, to_msg));
+        }
+
+        Ok(Resolver { jobs: tx })
+    }
+
+    /// Starts resolving `host`. The result is delivered to the event loop
+    /// as a `Resolved` carrying `token`, with `port` set on every address.
+    pub fn resolve(&self, host: &str, port: Port, token: Token) {
+        let job = Job {
+            host: host.to_string(),
+            port: port,
+            token: token,
+        };
+
+        // The workers only stop once the resolver is dropped
+        let _ = self.jobs.send(job);
+    }
+}
+
+fn work<M: Send>(jobs: Arc<Mutex<Receiver<Job>>>, sender: EventLoopSender<M>, to_msg: fn(Resolved) -> M) {
+    loop {
+        // Only held while waiting for a job, not while resolving it
+        let job = match jobs.lock().unwrap().recv() {
+            Ok(job) => job,
+            Err(_) => return
+        };
+
+        debug!("resolving; host={}; token={:?}", job.host, job.token);
+
+        let result = get_host_addresses(job.host.as_slice()).map(|ips| {
+            ips.into_iter().map(|ip| SockAddr::inet(ip, job.port)).collect()
+        });
+
+        let mut msg = to_msg(Resolved { token: job.token, result: result });
+
+        // Wait for room in the notification queue rather than drop the
+        // result
+        loop {
+            match sender.send(msg) {
+                Ok(()) => break,
+                Err(NotifyError::Full(m)) => {
+                    msg = m;
+                    sleep(Duration::milliseconds(1));
+                }
+                Err(NotifyError::Closed(_)) => return
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use std::old_io;
 use nix::NixError;
-use nix::errno::{EAGAIN, EADDRINUSE};
+use nix::errno::{EAGAIN, EADDRINUSE, ECONNABORTED, EINTR, EMFILE, ENFILE};
 
 use self::MioErrorKind::{
     Eof,
@@ -8,7 +8,15 @@ use self::MioErrorKind::{
     BufOverflow,
     WouldBlock,
     AddrInUse,
+    ConnectionAborted,
+    Interrupted,
+    ResourceLimit,
+    NotRegistered,
+    AlreadyRegistered,
     EventLoopTerminated,
+    InvalidConfig,
+    TimerOverflow,
+    Unsupported,
     OtherError
 };
 
@@ -17,7 +25,8 @@ pub type MioResult<T> = Result<T, MioError>;
 #[derive(Copy, Debug, PartialEq, Clone)]
 pub struct MioError {
     pub kind: MioErrorKind,
-    sys: Option<NixError>
+    sys: Option<NixError>,
+    desc: Option<&'static str>
 }
 
 #[derive(Copy, Debug, PartialEq, Clone)]
@@ -25,9 +34,17 @@ pub enum MioErrorKind {
     Eof,                    // End of file or socket closed
     WouldBlock,             // The operation would have blocked
     AddrInUse,              // Inet socket address or domain socket path already in use
+    ConnectionAborted,      // A connection failed before it could be accepted
+    Interrupted,            // A signal interrupted the system call (EINTR)
+    ResourceLimit,          // The process or system ran out of file descriptors
+    NotRegistered,          // The handle is not registered, or was closed while registered
+    AlreadyRegistered,      // The handle is already registered
     BufUnderflow,           // Buf does not contain enough data to perform read op
     BufOverflow,            // Buf does not contain enough capacity to perform write op
     EventLoopTerminated,    // The event loop is not running anymore
+    InvalidConfig,          // The supplied configuration is not valid
+    TimerOverflow,          // The event loop's timer has no room for another timeout
+    Unsupported,            // The operation is not supported on this platform
     OtherError,             // System error not covered by other kinds
 }
 
@@ -35,21 +52,68 @@ impl MioError {
     pub fn eof() -> MioError {
         MioError {
             kind: Eof,
-            sys: None
+            sys: None,
+            desc: None
         }
     }
 
     pub fn buf_underflow() -> MioError {
         MioError {
             kind: BufUnderflow,
-            sys: None
+            sys: None,
+            desc: None
         }
     }
 
     pub fn buf_overflow() -> MioError {
         MioError {
             kind: BufOverflow,
-            sys: None
+            sys: None,
+            desc: None
+        }
+    }
+
+    pub fn resource_limit() -> MioError {
+        MioError {
+            kind: ResourceLimit,
+            sys: None,
+            desc: None
+        }
+    }
+
+    pub fn timer_overflow() -> MioError {
+        MioError {
+            kind: TimerOverflow,
+            sys: None,
+            desc: None
+        }
+    }
+
+    pub fn unsupported() -> MioError {
+        MioError {
+            kind: Unsupported,
+            sys: None,
+            desc: None
+        }
+    }
+
+    /// The handle is not registered with the event loop. `sys` is the error
+    /// reported by the OS selector, if any.
+    pub fn not_registered(sys: Option<NixError>) -> MioError {
+        MioError {
+            kind: NotRegistered,
+            sys: sys,
+            desc: None
+        }
+    }
+
+    /// The handle is already registered with the event loop. `sys` is the
+    /// error reported by the OS selector, if any.
+    pub fn already_registered(sys: Option<NixError>) -> MioError {
+        MioError {
+            kind: AlreadyRegistered,
+            sys: sys,
+            desc: None
         }
     }
 
@@ -57,15 +121,38 @@ impl MioError {
         let kind = match err {
             NixError::Sys(EAGAIN) => WouldBlock,
             NixError::Sys(EADDRINUSE) => AddrInUse,
+            NixError::Sys(ECONNABORTED) => ConnectionAborted,
+            NixError::Sys(EINTR) => Interrupted,
+            NixError::Sys(EMFILE) | NixError::Sys(ENFILE) => ResourceLimit,
             _ => OtherError,
         };
 
         MioError {
             kind: kind,
-            sys: Some(err)
+            sys: Some(err),
+            desc: None
+        }
+    }
+
+    pub fn invalid_config(desc: &'static str) -> MioError {
+        MioError {
+            kind: InvalidConfig,
+            sys: None,
+            desc: Some(desc)
         }
     }
 
+    /// A description of what went wrong, when one is more specific than the
+    /// error kind.
+    pub fn desc(&self) -> Option<&'static str> {
+        self.desc
+    }
+
+    /// The OS error this error was created from, if any.
+    pub fn sys(&self) -> Option<&NixError> {
+        self.sys.as_ref()
+    }
+
     pub fn is_eof(&self) -> bool {
         match self.kind {
             Eof => true,
@@ -80,6 +167,50 @@ impl MioError {
         }
     }
 
+    pub fn is_addr_in_use(&self) -> bool {
+        match self.kind {
+            AddrInUse => true,
+            _ => false
+        }
+    }
+
+    /// Returns true if a pending connection failed before it could be
+    /// accepted. Only that connection is affected, not the listener.
+    pub fn is_connection_aborted(&self) -> bool {
+        match self.kind {
+            ConnectionAborted => true,
+            _ => false
+        }
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        match self.kind {
+            Interrupted => true,
+            _ => false
+        }
+    }
+
+    pub fn is_resource_limit(&self) -> bool {
+        match self.kind {
+            ResourceLimit => true,
+            _ => false
+        }
+    }
+
+    pub fn is_not_registered(&self) -> bool {
+        match self.kind {
+            NotRegistered => true,
+            _ => false
+        }
+    }
+
+    pub fn is_already_registered(&self) -> bool {
+        match self.kind {
+            AlreadyRegistered => true,
+            _ => false
+        }
+    }
+
     pub fn is_buf_underflow(&self) -> bool {
         match self.kind {
             BufUnderflow => true,
@@ -94,6 +225,27 @@ impl MioError {
         }
     }
 
+    pub fn is_invalid_config(&self) -> bool {
+        match self.kind {
+            InvalidConfig => true,
+            _ => false
+        }
+    }
+
+    pub fn is_timer_overflow(&self) -> bool {
+        match self.kind {
+            TimerOverflow => true,
+            _ => false
+        }
+    }
+
+    pub fn is_unsupported(&self) -> bool {
+        match self.kind {
+            Unsupported => true,
+            _ => false
+        }
+    }
+
     pub fn as_io_error(&self) -> old_io::IoError {
         use std::old_io::OtherIoError;
 
@@ -101,10 +253,25 @@ impl MioError {
             Eof | BufUnderflow | BufOverflow => old_io::standard_error(old_io::EndOfFile),
             WouldBlock => old_io::standard_error(old_io::ResourceUnavailable),
             AddrInUse => old_io::standard_error(old_io::PathAlreadyExists),
-            OtherError => match self.sys {
+            ConnectionAborted | Interrupted | ResourceLimit | NotRegistered | AlreadyRegistered | OtherError => match self.sys {
                 Some(NixError::Sys(err)) => old_io::IoError::from_errno(err as usize, false),
                 _ => old_io::standard_error(old_io::OtherIoError)
             },
+            InvalidConfig => old_io::IoError {
+                kind: old_io::InvalidInput,
+                desc: "invalid configuration",
+                detail: self.desc.map(|d| d.to_string())
+            },
+            Unsupported => old_io::IoError {
+                kind: OtherIoError,
+                desc: "operation not supported on this platform",
+                detail: None
+            },
+            TimerOverflow => old_io::IoError {
+                kind: OtherIoError,
+                desc: "too many timer entries",
+                detail: None
+            },
             EventLoopTerminated => old_io::standard_error(OtherIoError)
         }
     }
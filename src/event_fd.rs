@@ -0,0 +1,47 @@
+use error::MioResult;
+use io::IoHandle;
+use os;
+
+/// A counter that other threads can bump to wake up the event loop.
+///
+/// Register the `EventFd` with the event loop for readable interest. Any
+/// thread may then call [#wake](#method.wake) to add to the counter, which
+/// makes the handle readable. The readable handler calls
+/// [#reset](#method.reset) to take the accumulated count and zero the counter.
+///
+/// This is lighter than `EventLoop::channel` when all that needs to be
+/// communicated is "N units of work are ready": nothing is allocated or
+/// queued, and wakeups are coalesced into a single count.
+///
+/// On Linux this is an eventfd in counter mode. Other platforms fall back to
+/// a pipe, where every `wake` writes 8 bytes; a `wake` may fail with
+/// `WouldBlock` if the pipe fills up before the loop resets it.
+pub struct EventFd {
+    inner: os::EventFd
+}
+
+impl EventFd {
+    pub fn new() -> MioResult<EventFd> {
+        Ok(EventFd { inner: try!(os::EventFd::new()) })
+    }
+
+    /// Adds `count` to the counter, waking up the event loop.
+    pub fn wake(&self, count: u64) -> MioResult<()> {
+        self.inner.wake(count)
+    }
+
+    /// Returns the count accumulated since the last reset and sets the
+    /// counter back to zero. Returns 0 if nothing was pending.
+    pub fn reset(&self) -> MioResult<u64> {
+        self.inner.reset()
+    }
+}
+
+impl IoHandle for EventFd {
+    fn desc(&self) -> &os::IoDesc {
+        self.inner.desc()
+    }
+}
+
+unsafe impl Send for EventFd { }
+unsafe impl Sync for EventFd { }
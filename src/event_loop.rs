@@ -1,28 +1,147 @@
 use std::default::Default;
 use std::time::duration::Duration;
+use std::num::UnsignedInt;
 use std::{fmt, usize};
+use std::cmp::{max, min};
+use std::collections::HashMap;
+use std::mem;
 use error::{MioResult, MioError};
 use handler::Handler;
 use io::IoHandle;
-use notify::Notify;
+use notify::{Notify, NotifyError};
+use observer::LoopObserver;
+use os;
 use os::event::{IoEvent, Interest, PollOpt};
 use poll::{Poll};
-use timer::{Timer, Timeout, TimerResult};
+use registration::Registration;
+use timer::{self, Timer, Timeout, TimerError, TimerResult, TimerStats};
 use os::token::Token;
 
 /// Configure EventLoop runtime details
+///
+/// The configuration is checked by
+/// [EventLoop::configured](struct.EventLoop.html#method.configured), which
+/// returns an `InvalidConfig` error if any field is out of range.
+///
+/// The default values match the ones used by
+/// [EventLoop::new](struct.EventLoop.html#method.new), so it is possible to
+/// only override a few fields:
+///
+/// ```
+/// use std::default::Default;
+/// use mio::{EventLoop, EventLoopConfig};
+///
+/// let config = EventLoopConfig {
+///     messages_per_tick: 128,
+///     .. Default::default()
+/// };
+///
+/// let event_loop = EventLoop::<(), ()>::configured(config).unwrap();
+/// ```
 #[derive(Copy, Clone, Debug)]
 pub struct EventLoopConfig {
+    /// Maximum number of milliseconds to block waiting for IO events.
     pub io_poll_timeout_ms: usize,
 
     // == Notifications ==
+
+    /// Number of messages the notification queue can hold. Must be greater
+    /// than zero.
     pub notify_capacity: usize,
+
+    /// Maximum number of messages processed per tick. Must be greater than
+    /// zero.
     pub messages_per_tick: usize,
 
     // == Timer ==
+
+    /// Timer resolution in milliseconds. Must be greater than zero.
     pub timer_tick_ms: u64,
+
+    /// Number of slots in the timer wheel. Must be a power of two, or zero
+    /// to disable timers altogether. Without timers, no wheel is allocated
+    /// or advanced, and scheduling a timeout fails.
     pub timer_wheel_size: usize,
+
+    /// Maximum number of pending timeouts. Must be greater than zero.
     pub timer_capacity: usize,
+
+    // == Dispatch ==
+
+    /// Rotate the position at which IO events start being dispatched on each
+    /// tick. By default, events are dispatched in the order the OS returns
+    /// them, which under load can consistently favor the same handles.
+    /// Enabling this spreads that advantage evenly over time.
+    pub fair_dispatch: bool,
+
+    /// Dispatch the IO events of each tick in ascending token order,
+    /// instead of the order the OS returns them in, which varies from run
+    /// to run. Meant for tests that need reproducible dispatch order; it
+    /// costs a sort per tick. Takes precedence over `fair_dispatch`, while
+    /// token priorities still go first.
+    pub sort_events: bool,
+
+    /// Deliver IO readiness through `Handler::ready`, once per token and
+    /// tick, instead of through separate `readable` and `writable`
+    /// callbacks. Events the selector reports separately for the same token
+    /// are merged first.
+    pub unified_readiness: bool,
+
+    /// Keep the readiness reported for each token, for
+    /// [EventLoop::readiness](struct.EventLoop.html#method.readiness). Off
+    /// by default, as it costs a map update per event.
+    pub track_readiness: bool,
+
+    /// Options used by `register`, and by registrations that pass
+    /// `PollOpt::configured()`, so that the triggering mode can be picked
+    /// once for the whole loop. Registrations with explicit options are not
+    /// affected.
+    pub default_poll_opt: PollOpt,
+
+    // == Limits ==
+
+    /// Maximum number of IO handles that can be registered at once. Further
+    /// registrations fail with a `ResourceLimit` error, which allows a
+    /// server to cap its connections before running out of descriptors.
+    ///
+    /// Handles count until they are deregistered. One that is closed while
+    /// still registered keeps its place, as the event loop cannot tell,
+    /// until its descriptor number is registered again. Deregister handles
+    /// before closing them, or register them with
+    /// [EventLoop::register_owned](struct.EventLoop.html#method.register_owned),
+    /// which does it on drop.
+    pub max_io_sources: usize,
+}
+
+impl EventLoopConfig {
+    /// Checks that every field is within its allowed range.
+    pub fn validate(&self) -> MioResult<()> {
+        if self.notify_capacity == 0 {
+            return Err(MioError::invalid_config("notify_capacity must be greater than zero"));
+        }
+
+        if self.messages_per_tick == 0 {
+            return Err(MioError::invalid_config("messages_per_tick must be greater than zero"));
+        }
+
+        if self.timer_tick_ms == 0 {
+            return Err(MioError::invalid_config("timer_tick_ms must be greater than zero"));
+        }
+
+        if self.timer_wheel_size != 0 && !self.timer_wheel_size.is_power_of_two() {
+            return Err(MioError::invalid_config("timer_wheel_size must be a power of two"));
+        }
+
+        if self.timer_capacity == 0 {
+            return Err(MioError::invalid_config("timer_capacity must be greater than zero"));
+        }
+
+        if self.default_poll_opt.is_configured() {
+            return Err(MioError::invalid_config("default_poll_opt cannot refer to itself"));
+        }
+
+        Ok(())
+    }
 }
 
 impl Default for EventLoopConfig {
@@ -34,18 +153,55 @@ impl Default for EventLoopConfig {
             timer_tick_ms: 100,
             timer_wheel_size: 1_024,
             timer_capacity: 65_536,
+            fair_dispatch: false,
+            sort_events: false,
+            unified_readiness: false,
+            track_readiness: false,
+            default_poll_opt: PollOpt::level(),
+            max_io_sources: usize::MAX,
         }
     }
 }
 
 /// Single threaded IO event loop.
-#[derive(Debug)]
+///
+/// Dropping the event loop deregisters every handle still registered with
+/// it before the selector is closed. Handles owned elsewhere are
+/// left open and can be registered with another event loop. A
+/// `Registration` guard that outlives the loop still closes its handle when
+/// dropped; deregistering it at that point is a no-op.
 pub struct EventLoop<T, M: Send> {
     run: bool,
     poll: Poll,
-    timer: Timer<T>,
+    // `None` when timers are disabled
+    timer: Option<Timer<T>>,
+    // Deadlines set with `set_deadline`, on a wheel of their own that is
+    // only allocated once a deadline is set
+    deadlines: Option<Timer<Token>>,
+    deadline_timeouts: HashMap<Token, Timeout>,
+    // Dispatch priority of the tokens that have one, sorted by token
+    priorities: Vec<(usize, u8)>,
     notify: Notify<M>,
     config: EventLoopConfig,
+    // Rotating offset into the ready events, used for fair dispatch
+    dispatch_offset: usize,
+    // Time sampled at the start of the current tick
+    now_ms: u64,
+    // Events of handles that were already ready when they were registered
+    // with `PollOpt::synthesize_initial()`, delivered on the next tick
+    synthetic: Vec<IoEvent>,
+    // Events of the current tick in dispatch order, along with the priority
+    // of their token. Kept between ticks to reuse the allocation.
+    dispatch: Vec<(u8, IoEvent)>,
+    // Used to merge the events of each token with unified readiness, kept
+    // between ticks like `dispatch`
+    merge_order: Vec<(usize, usize, bool)>,
+    observer: Option<Box<LoopObserver + Send>>,
+    // Readiness reported for each token since it was registered, less what
+    // the handler cleared. Only kept with `track_readiness`.
+    readiness: HashMap<Token, os::event::Ready>,
+    // Applied to every message before it reaches the handler
+    notify_filter: Option<Box<FnMut(M) -> Option<M> + Send>>,
 }
 
 // Token used to represent notifications
@@ -59,15 +215,23 @@ impl<T, M: Send> EventLoop<T, M> {
         EventLoop::configured(Default::default())
     }
 
+    /// Initializes a new event loop using the supplied configuration. Returns
+    /// an error if the configuration is invalid.
     pub fn configured(config: EventLoopConfig) -> MioResult<EventLoop<T, M>> {
+        try!(config.validate());
+
         // Create the IO poller
         let mut poll = try!(Poll::new());
 
-        // Create the timer
-        let mut timer = Timer::new(
-            config.timer_tick_ms,
-            config.timer_wheel_size,
-            config.timer_capacity);
+        // Create the timer, unless timers are disabled
+        let mut timer = if config.timer_wheel_size == 0 {
+            None
+        } else {
+            Some(Timer::new(
+                config.timer_tick_ms,
+                config.timer_wheel_size,
+                config.timer_capacity))
+        };
 
         // Create cross thread notification queue
         let notify = try!(Notify::with_capacity(config.notify_capacity));
@@ -76,14 +240,27 @@ impl<T, M: Send> EventLoop<T, M> {
         try!(poll.register(&notify, NOTIFY, Interest::readable() | Interest::writable() , PollOpt::edge()));
 
         // Set the timer's starting time reference point
-        timer.setup();
+        if let Some(ref mut timer) = timer {
+            timer.setup();
+        }
 
         Ok(EventLoop {
             run: true,
             poll: poll,
             timer: timer,
+            deadlines: None,
+            deadline_timeouts: HashMap::new(),
+            priorities: Vec::new(),
             notify: notify,
             config: config,
+            dispatch_offset: 0,
+            now_ms: timer::now_ms(),
+            synthetic: Vec::new(),
+            dispatch: Vec::new(),
+            merge_order: Vec::new(),
+            observer: None,
+            readiness: HashMap::new(),
+            notify_filter: None,
         })
     }
 
@@ -99,7 +276,10 @@ impl<T, M: Send> EventLoop<T, M> {
     ///
     /// struct MyHandler;
     ///
-    /// impl Handler<(), u32> for MyHandler {
+    /// impl Handler for MyHandler {
+    ///     type Timeout = ();
+    ///     type Message = u32;
+    ///
     ///     fn notify(&mut self, event_loop: &mut EventLoop<(), u32>, msg: u32) {
     ///         assert_eq!(msg, 123);
     ///         event_loop.shutdown();
@@ -155,7 +335,10 @@ impl<T, M: Send> EventLoop<T, M> {
     ///
     /// struct MyHandler;
     ///
-    /// impl Handler<u32, ()> for MyHandler {
+    /// impl Handler for MyHandler {
+    ///     type Timeout = u32;
+    ///     type Message = ();
+    ///
     ///     fn timeout(&mut self, event_loop: &mut EventLoop<u32, ()>, timeout: u32) {
     ///         assert_eq!(timeout, 123);
     ///         event_loop.shutdown();
@@ -167,14 +350,108 @@ impl<T, M: Send> EventLoop<T, M> {
     /// let timeout = event_loop.timeout(123, Duration::milliseconds(300)).unwrap();
     /// let _ = event_loop.run(MyHandler);
     /// ```
+    ///
+    /// Fails with an error for which `is_disabled()` returns true if the
+    /// event loop was configured with a `timer_wheel_size` of zero.
     pub fn timeout(&mut self, token: T, delay: Duration) -> TimerResult<Timeout> {
-        self.timer.timeout(token, delay)
+        match self.timer {
+            Some(ref mut timer) => timer.timeout(token, delay),
+            None => Err(TimerError::disabled())
+        }
     }
 
     /// If the supplied timeout has not been triggered, cancel it such that it
     /// will not be triggered in the future.
     pub fn clear_timeout(&mut self, timeout: Timeout) -> bool {
-        self.timer.clear(timeout)
+        match self.timer {
+            Some(ref mut timer) => timer.clear(timeout),
+            None => false
+        }
+    }
+
+    /// Returns how full the timer is, which helps tuning `timer_capacity`
+    /// and `timer_wheel_size`. Computing the stats walks all the pending
+    /// timeouts. With timers disabled, all the counts are zero.
+    pub fn timer_stats(&self) -> TimerStats {
+        match self.timer {
+            Some(ref timer) => timer.stats(),
+            None => Default::default()
+        }
+    }
+
+    /// Sets a deadline for `token`, at `deadline` milliseconds on the clock
+    /// returned by [#now](#method.now). Unless it is set again or cleared
+    /// before then, [Handler::deadline_expired](trait.Handler.html#method.deadline_expired)
+    /// is invoked with `token` once the deadline passes.
+    ///
+    /// Each token has at most one deadline: setting a new one replaces the
+    /// previous one. To close connections that make no progress, call
+    /// `set_deadline(token, event_loop.now() + idle_ms)` after every
+    /// successful read or write. Deadlines are not cleared when a handle is
+    /// deregistered; use [#clear_deadline](#method.clear_deadline).
+    ///
+    /// Deadlines share the timer configuration, so they have the same
+    /// resolution as timeouts and fail the same way when timers are disabled
+    /// or too many are pending.
+    pub fn set_deadline(&mut self, token: Token, deadline: u64) -> TimerResult<()> {
+        if self.timer.is_none() {
+            return Err(TimerError::disabled());
+        }
+
+        self.clear_deadline(token);
+
+        if self.deadlines.is_none() {
+            let mut deadlines = Timer::new(
+                self.config.timer_tick_ms,
+                self.config.timer_wheel_size,
+                self.config.timer_capacity);
+
+            deadlines.setup();
+            self.deadlines = Some(deadlines);
+        }
+
+        // A deadline that already passed expires on the next tick
+        let at = max(deadline, timer::now_ms());
+        let timeout = try!(self.deadlines.as_mut().unwrap().timeout_at_ms(token, at));
+
+        self.deadline_timeouts.insert(token, timeout);
+        Ok(())
+    }
+
+    /// Clears the deadline of `token`. Returns false if it had none.
+    pub fn clear_deadline(&mut self, token: Token) -> bool {
+        match (self.deadline_timeouts.remove(&token), self.deadlines.as_mut()) {
+            (Some(timeout), Some(deadlines)) => deadlines.clear(timeout),
+            _ => false
+        }
+    }
+
+    /// Sets the dispatch priority of `token`. Within a tick, the events of
+    /// tokens with a higher priority are dispatched first, which lets
+    /// latency sensitive connections, such as ones carrying control
+    /// traffic, go ahead of bulk transfers.
+    ///
+    /// Tokens have a priority of 0 by default, and setting it back to 0
+    /// removes it. As long as no token has a priority, events are dispatched
+    /// in the usual order without any sorting. Priorities belong to tokens,
+    /// not handles: they are kept when a handle is deregistered and apply to
+    /// any handle registered with the same token later.
+    pub fn set_priority(&mut self, token: Token, priority: u8) {
+        match self.priorities.binary_search_by(|p| p.0.cmp(&token.as_usize())) {
+            Ok(i) if priority == 0 => { self.priorities.remove(i); }
+            Ok(i) => self.priorities[i].1 = priority,
+            Err(i) if priority != 0 => self.priorities.insert(i, (token.as_usize(), priority)),
+            Err(_) => {}
+        }
+    }
+
+    /// Returns the dispatch priority of `token`, see
+    /// [#set_priority](#method.set_priority).
+    pub fn priority(&self, token: Token) -> u8 {
+        match self.priorities.binary_search_by(|p| p.0.cmp(&token.as_usize())) {
+            Ok(i) => self.priorities[i].1,
+            Err(_) => 0
+        }
     }
 
     /// Tells the event loop to exit after it is done handling all events in the
@@ -183,29 +460,200 @@ impl<T, M: Send> EventLoop<T, M> {
         self.run = false;
     }
 
+    /// Stops accepting messages and returns the ones that were sent but not
+    /// yet processed, in the order they were sent. Meant to be called after
+    /// the event loop has shut down, so that in-flight work can be accounted
+    /// for.
+    ///
+    /// From then on, sending on any of the loop's channels fails with
+    /// `NotifyError::Closed`, letting producers notice the loop is gone
+    /// instead of filling up the queue. Dropping the event loop closes the
+    /// channel as well.
+    pub fn drain_notifications(&mut self) -> Vec<M> {
+        self.notify.close()
+    }
+
     /// Registers an IO handle with the event loop.
+    ///
+    /// Registering, reregistering and deregistering handles is allowed from
+    /// within any handler callback. Changes apply immediately to the events
+    /// of the current tick that have not been dispatched yet:
+    ///
+    /// * A deregistered handle receives no further events, including any
+    ///   that were already received for its token in this tick. This holds
+    ///   even if the token is registered again in the same tick.
+    /// * A reregistered handle only receives the readiness it is still
+    ///   interested in.
+    /// * A newly registered handle receives its first events after the next
+    ///   poll.
+    ///
+    /// The handle is registered for readable events, with the loop's
+    /// `default_poll_opt`.
     pub fn register<H: IoHandle>(&mut self, io: &H, token: Token) -> MioResult<()> {
-        self.poll.register(io, token, Interest::readable(), PollOpt::level())
+        self.register_opt(io, token, Interest::readable(), PollOpt::configured())
     }
 
     /// Registers an IO handle with the event loop.
+    ///
+    /// Fails with a `ResourceLimit` error if `max_io_sources` handles are
+    /// already registered. Registering a handle that is registered already
+    /// fails with an error for which `is_already_registered()` returns true
+    /// on Linux; use [#reregister](#method.reregister) to change an existing
+    /// registration. kqueue has no such check and updates the registration
+    /// instead.
     pub fn register_opt<H: IoHandle>(&mut self, io: &H, token: Token, interest: Interest, opt: PollOpt) -> MioResult<()> {
-        self.poll.register(io, token, interest, opt)
+        let opt = self.resolve_poll_opt(opt);
+        try!(self.check_io_sources());
+        try!(self.poll.register(io, token, interest, opt));
+        self.readiness.remove(&token);
+
+        if let Some(ref mut observer) = self.observer {
+            observer.on_register(token, interest, opt);
+        }
+
+        self.synthesize_initial(io, token, interest, opt)
+    }
+
+    /// Registers an IO handle with the event loop, handing ownership of the
+    /// handle to the returned guard. The handle stays registered until the
+    /// guard is dropped, at which point it is deregistered and then closed.
+    ///
+    /// Calling `mem::forget` on the guard leaks both the handle and its
+    /// registration; use [#register_opt](#method.register_opt) to manage
+    /// registrations by hand instead.
+    pub fn register_owned<H: IoHandle>(&mut self, io: H, token: Token, interest: Interest, opt: PollOpt) -> MioResult<Registration<H>> {
+        let opt = self.resolve_poll_opt(opt);
+        try!(self.check_io_sources());
+        try!(self.poll.register(&io, token, interest, opt));
+        self.readiness.remove(&token);
+
+        if let Some(ref mut observer) = self.observer {
+            observer.on_register(token, interest, opt);
+        }
+
+        try!(self.synthesize_initial(&io, token, interest, opt));
+        Ok(Registration::new(io, token, self.poll.registry()))
     }
 
     /// Re-Registers an IO handle with the event loop.
+    ///
+    /// If the handle is no longer known to the OS, for example because it
+    /// was closed in the meantime, an error for which `is_not_registered()`
+    /// returns true is returned and the handle's registration is dropped.
     pub fn reregister<H: IoHandle>(&mut self, io: &H, token: Token, interest: Interest, opt: PollOpt) -> MioResult<()> {
-        self.poll.reregister(io, token, interest, opt)
+        let opt = self.resolve_poll_opt(opt);
+        try!(self.poll.reregister(io, token, interest, opt));
+
+        if let Some(ref mut observer) = self.observer {
+            observer.on_reregister(token, interest, opt);
+        }
+
+        Ok(())
+    }
+
+    /// Changes the interest and options of many registrations at once, each
+    /// identified by its token, for example to stop reading from every
+    /// connection while a server is paused.
+    ///
+    /// Each change is applied like [#reregister](#method.reregister), in
+    /// order. Unlike `reregister`, no handle is needed: the event loop knows
+    /// which handle each token is registered for. There is one system call
+    /// per change. Stops at the first change that fails, leaving the ones
+    /// before it applied. A token that is not registered fails with an
+    /// error for which `is_not_registered()` returns true, and so does one
+    /// whose handle was closed without being deregistered, even if another
+    /// file has the same descriptor number by now: that file is left
+    /// alone.
+    pub fn batch_reregister(&mut self, changes: &[(Token, Interest, PollOpt)]) -> MioResult<()> {
+        let changes: Vec<(Token, Interest, PollOpt)> = changes.iter()
+            .map(|&(token, interest, opt)| (token, interest, self.resolve_poll_opt(opt)))
+            .collect();
+
+        try!(self.poll.reregister_tokens(changes.as_slice()));
+
+        if let Some(ref mut observer) = self.observer {
+            for &(token, interest, opt) in changes.iter() {
+                observer.on_reregister(token, interest, opt);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Installs a filter that every message received through the channel
+    /// goes through before reaching
+    /// [Handler::notify](trait.Handler.html#method.notify), replacing the
+    /// previous one. The filter may change the message, or drop it by
+    /// returning `None`, e.g. to expire stale messages or rate limit a
+    /// sender.
+    ///
+    /// It runs on the event loop thread, as the messages are drained, and
+    /// has to be `Send` so that the event loop still is. Dropped messages
+    /// still count towards `messages_per_tick`.
+    pub fn set_notify_filter<F: FnMut(M) -> Option<M> + Send + 'static>(&mut self, filter: F) {
+        self.notify_filter = Some(Box::new(filter));
+    }
+
+    /// Removes the notification filter, messages reach the handler as sent.
+    pub fn clear_notify_filter(&mut self) {
+        self.notify_filter = None;
+    }
+
+    /// Installs an observer that is notified of registrations and of the
+    /// events the loop receives and dispatches, replacing the previous one.
+    /// The `debug!` logging is unaffected.
+    pub fn set_observer(&mut self, observer: Box<LoopObserver + Send>) {
+        self.observer = Some(observer);
+    }
+
+    /// Removes the observer and returns it.
+    pub fn take_observer(&mut self) -> Option<Box<LoopObserver + Send>> {
+        self.observer.take()
     }
 
     /// Keep spinning the event loop indefinitely, and notify the handler whenever
     /// any of the registered handles are ready.
-    pub fn run<H: Handler<T, M>>(&mut self, mut handler: H) -> EventLoopResult<H> {
+    ///
+    /// If a signal interrupts the poll (`EINTR`), the loop invokes
+    /// [Handler::interrupted](trait.Handler.html#method.interrupted) and keeps
+    /// running unless the handler calls [#shutdown](#method.shutdown).
+    pub fn run<H: Handler<Timeout=T, Message=M>>(&mut self, mut handler: H) -> EventLoopResult<H> {
         self.run = true;
 
         while self.run {
+            let timeout_ms = self.next_poll_timeout_ms();
+
             // Execute ticks as long as the event loop is running
-            match self.tick(&mut handler) {
+            match self.tick(&mut handler, Some(timeout_ms)) {
+                Err(e) => return Err(EventLoopError::new(handler, e)),
+                _ => {}
+            }
+        }
+
+        Ok(handler)
+    }
+
+    /// Runs the event loop like [#run](#method.run), but returns once
+    /// `duration` has elapsed if it was not shut down before.
+    ///
+    /// Poll timeouts are cut short at the deadline, so the loop returns on
+    /// time even when no events arrive. A handler callback that is running
+    /// at the deadline is not interrupted.
+    pub fn run_for<H: Handler<Timeout=T, Message=M>>(&mut self, mut handler: H, duration: Duration) -> EventLoopResult<H> {
+        let deadline = timer::now_ms() + max(0, duration.num_milliseconds()) as u64;
+
+        self.run = true;
+
+        while self.run {
+            let now = timer::now_ms();
+
+            if now >= deadline {
+                break;
+            }
+
+            let timeout_ms = min(self.next_poll_timeout_ms(), (deadline - now) as usize);
+
+            match self.tick(&mut handler, Some(timeout_ms)) {
                 Err(e) => return Err(EventLoopError::new(handler, e)),
                 _ => {}
             }
@@ -214,17 +662,107 @@ impl<T, M: Send> EventLoop<T, M> {
         Ok(handler)
     }
 
+    /// Returns the time, in milliseconds, at which the current tick started,
+    /// read from the monotonic clock that timeouts are measured against.
+    ///
+    /// The value is sampled once per tick, not read live, so every callback
+    /// of a tick sees the same time and no clock read is needed. The origin
+    /// of the clock is unspecified; only differences between values are
+    /// meaningful.
+    pub fn now(&self) -> u64 {
+        self.now_ms
+    }
+
+    /// Returns true if a handle is currently registered with the given token.
+    ///
+    /// This reflects the registrations made through the event loop: a handle
+    /// that is closed without being deregistered first is still reported as
+    /// registered.
+    pub fn is_registered(&self, token: Token) -> bool {
+        self.poll.is_registered(token)
+    }
+
+    /// Returns the interest and options `token` was last registered or
+    /// reregistered with, or `None` if it is not registered. Options passed
+    /// as `PollOpt::configured()` are reported as the configured default they
+    /// stood for.
+    ///
+    /// Like [#is_registered](#method.is_registered), this is what the event
+    /// loop was told, not read back from the kernel: a oneshot registration
+    /// is still reported after its event fired, although the kernel has
+    /// disabled it until it is reregistered.
+    pub fn registration(&self, token: Token) -> Option<(Interest, PollOpt)> {
+        self.poll.registration(token)
+    }
+
+    /// Returns the readiness known for `token`: everything its events
+    /// reported since it was registered, less what was cleared with
+    /// [#clear_readiness](#method.clear_readiness). Returns no readiness if
+    /// the token is not registered, or if the loop was not configured with
+    /// `track_readiness`.
+    ///
+    /// This is a cache, no system call is made. With oneshot or edge
+    /// triggered registrations, a handler that reads or writes until
+    /// `WouldBlock` and clears the readiness at that point can tell whether
+    /// a handle is still known to be ready, and skip reregistering for an
+    /// interest it would be notified of right away.
+    pub fn readiness(&self, token: Token) -> os::event::Ready {
+        if !self.poll.is_registered(token) {
+            return os::event::Ready::none();
+        }
+
+        match self.readiness.get(&token) {
+            Some(&ready) => ready,
+            None => os::event::Ready::none()
+        }
+    }
+
+    /// Removes `ready` from the readiness known for `token`, typically once
+    /// a read or write returned `WouldBlock`. It is added back by the next
+    /// event reporting it.
+    pub fn clear_readiness(&mut self, token: Token, ready: os::event::Ready) {
+        if let Some(known) = self.readiness.get_mut(&token) {
+            known.remove(ready);
+        }
+    }
+
     /// Deregisters an IO handle with the event loop.
+    ///
+    /// The handle is left untouched, so it can be registered with another
+    /// event loop, for example to move a connection off an overloaded
+    /// thread. No readiness is lost or reported twice in the process: the
+    /// events of the current tick that were not dispatched yet are dropped
+    /// by this loop, and registering with the other loop reports the
+    /// readiness the handle has at that point, even when edge triggered.
+    /// Data the application has buffered for the connection, and any
+    /// deadline or priority set for its token here, are not carried over.
     pub fn deregister<H: IoHandle>(&mut self, io: &H) -> MioResult<()> {
-        self.poll.deregister(io)
+        let token = self.poll.token(io);
+        try!(self.poll.deregister(io));
+
+        if let Some(token) = token {
+            self.readiness.remove(&token);
+
+            if let Some(ref mut observer) = self.observer {
+                observer.on_deregister(token);
+            }
+        }
+
+        Ok(())
     }
 
-    /// Spin the event loop once, with a timeout of one second, and notify the
-    /// handler if any of the registered handles become ready during that
-    /// time.
-    pub fn run_once<H: Handler<T, M>>(&mut self, mut handler: H) -> EventLoopResult<H> {
+    /// Spin the event loop once, and notify the handler if any of the
+    /// registered handles become ready.
+    ///
+    /// Waits for IO events for at most `timeout`, or until an event is
+    /// received if `timeout` is `None`. Unlike [#run](#method.run), the wait
+    /// does not account for pending timers, so a caller mixing the two should
+    /// pass a timeout that ends when the next timer is due.
+    pub fn run_once<H: Handler<Timeout=T, Message=M>>(&mut self, mut handler: H, timeout: Option<Duration>) -> EventLoopResult<H> {
+        let timeout_ms = timeout.map(|d| max(0, d.num_milliseconds()) as usize);
+
         // Execute a single tick
-        match self.tick(&mut handler) {
+        match self.tick(&mut handler, timeout_ms) {
             Err(e) => return Err(EventLoopError::new(handler, e)),
             _ => {}
         }
@@ -232,23 +770,43 @@ impl<T, M: Send> EventLoop<T, M> {
         Ok(handler)
     }
 
-    // Executes a single run of the event loop loop
-    fn tick<H: Handler<T, M>>(&mut self, handler: &mut H) -> MioResult<()> {
+    // Executes a single run of the event loop loop, waiting at most
+    // `timeout_ms` for IO events
+    fn tick<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H, timeout_ms: Option<usize>) -> MioResult<()> {
         let mut messages;
         let mut pending;
 
         debug!("event loop tick");
 
+        self.now_ms = timer::now_ms();
+
         // Check the notify channel for any pending messages. If there are any,
         // avoid blocking when polling for IO events. Messages will be
         // processed after IO events.
         messages = self.notify.check(self.config.messages_per_tick, true);
-        pending = messages > 0;
+        pending = messages > 0 || !self.synthetic.is_empty();
+
+        // Last call before blocking, the handler may override the wait
+        let timeout_ms = match handler.before_poll(self) {
+            Some(d) => Some(max(0, d.num_milliseconds()) as usize),
+            None => timeout_ms
+        };
 
         // Check the registered IO handles for any new events. Each poll
         // is for one second, so a shutdown request can last as long as
         // one second before it takes effect.
-        let events = try!(self.io_poll(pending));
+        //
+        // A signal delivered while blocked in the poll interrupts it. This is
+        // not an error: the handler is told about it and the tick carries on
+        // as if no IO events were received.
+        let events = match self.io_poll(pending, timeout_ms) {
+            Ok(events) => events,
+            Err(ref e) if e.is_interrupted() => {
+                handler.interrupted(self);
+                0
+            }
+            Err(e) => return Err(e)
+        };
 
         if !pending {
             // Indicate that the sleep period is over, also grab any additional
@@ -265,30 +823,154 @@ impl<T, M: Send> EventLoop<T, M> {
     }
 
     #[inline]
-    fn io_poll(&mut self, immediate: bool) -> MioResult<usize> {
+    fn io_poll(&mut self, immediate: bool, timeout_ms: Option<usize>) -> MioResult<usize> {
         if immediate {
-            self.poll.poll(0)
+            self.poll.poll(Some(0))
+        } else {
+            self.poll.poll(timeout_ms)
+        }
+    }
+
+    // Queues an event for a newly registered handle that is already ready,
+    // if it asked for one
+    fn synthesize_initial<H: IoHandle>(&mut self, io: &H, token: Token, interest: Interest, opt: PollOpt) -> MioResult<()> {
+        if !opt.is_synthesize_initial() {
+            return Ok(());
+        }
+
+        let ready = try!(os::readiness(io.desc(), interest));
+
+        if ready != Interest::none() {
+            self.synthetic.push(IoEvent::new(ready | Interest::hinted(), token.as_usize()));
+        }
+
+        Ok(())
+    }
+
+    // Replaces `PollOpt::configured()` with the configured default options
+    fn resolve_poll_opt(&self, opt: PollOpt) -> PollOpt {
+        if opt.is_configured() {
+            (opt - PollOpt::configured()) | self.config.default_poll_opt
         } else {
-            let mut sleep = self.timer.next_tick_in_ms() as usize;
+            opt
+        }
+    }
 
-            if sleep > self.config.io_poll_timeout_ms {
-                sleep = self.config.io_poll_timeout_ms;
-            }
+    // Fails if registering one more handle would exceed `max_io_sources`
+    fn check_io_sources(&self) -> MioResult<()> {
+        // The notification queue is registered as well, but does not count
+        if self.poll.len() - 1 >= self.config.max_io_sources {
+            return Err(MioError::resource_limit());
+        }
+
+        Ok(())
+    }
+
+    // How long `run` waits for IO events: until the next timeout is due, but
+    // no longer than the configured maximum. Idle ticks of the timer wheel
+    // are skipped rather than waking up for each one.
+    fn next_poll_timeout_ms(&self) -> usize {
+        let next = self.timer.as_ref().and_then(|timer| timer.next_timeout_in_ms());
+
+        let next = match self.deadlines.as_ref().and_then(|deadlines| deadlines.next_timeout_in_ms()) {
+            Some(ms) => Some(next.map_or(ms, |next| min(next, ms))),
+            None => next
+        };
+
+        let mut sleep = match next {
+            Some(ms) => ms as usize,
+            None => self.config.io_poll_timeout_ms
+        };
 
-            self.poll.poll(sleep)
+        if sleep > self.config.io_poll_timeout_ms {
+            sleep = self.config.io_poll_timeout_ms;
         }
+
+        sleep
     }
 
-    // Process IO events that have been previously polled
-    fn io_process<H: Handler<T, M>>(&mut self, handler: &mut H, cnt: usize) {
-        let mut i = 0us;
+    // Process IO events that have been previously polled, along with the
+    // synthetic events queued since the last tick
+    fn io_process<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H, cnt: usize) {
+        // The buffer is kept from tick to tick, to avoid allocating
+        let mut events = mem::replace(&mut self.dispatch, Vec::new());
+        events.clear();
+
+        // With fair dispatch, start at a different event every tick
+        let start = if self.config.fair_dispatch && cnt > 0 {
+            let start = self.dispatch_offset % cnt;
+            self.dispatch_offset += 1;
+            start
+        } else {
+            0
+        };
+
+        for i in range(0, cnt) {
+            let evt = self.poll.event((start + i) % cnt);
+            events.push((self.priority(evt.token()), evt));
+        }
+
+        // Synthetic events of handles that were already ready when they were
+        // registered, unless the poll reported them as well
+        if !self.synthetic.is_empty() {
+            for evt in mem::replace(&mut self.synthetic, Vec::new()).into_iter() {
+                let token = evt.token();
+
+                if !self.poll.is_registered(token) {
+                    continue;
+                }
+
+                if range(0, cnt).any(|i| self.poll.event(i).token() == token) {
+                    continue;
+                }
+
+                events.push((self.priority(token), evt));
+            }
+        }
+
+        // With sorted events, tokens are dispatched in ascending order, and
+        // with priorities set, events of higher priority tokens go first.
+        // The sorts are stable, so the order is otherwise unchanged.
+        if self.config.sort_events {
+            events.sort_by(|a, b| (!a.0, a.1.token().as_usize()).cmp(&(!b.0, b.1.token().as_usize())));
+        } else if !self.priorities.is_empty() {
+            events.sort_by(|a, b| b.0.cmp(&a.0));
+        }
+
+        if let Some(ref mut observer) = self.observer {
+            for &(_, evt) in events.iter() {
+                if evt.token() != NOTIFY {
+                    observer.on_event(evt.token(), evt.readiness());
+                }
+            }
+        }
+
+        // With unified readiness, the handler hears about each token once
+        if self.config.unified_readiness {
+            self.io_process_merged(handler, events.as_mut_slice());
+        } else {
+            self.io_dispatch(handler, events.as_slice());
+        }
+
+        self.dispatch = events;
+    }
 
-        // Iterate over the notifications. Each event provides the token
-        // it was registered with (which usually represents, at least, the
-        // handle that the event is about) as well as information about
-        // what kind of event occurred (readable, writable, signal, etc.)
-        while i < cnt {
-            let evt = self.poll.event(i);
+    // Iterate over the notifications. Each event provides the token it was
+    // registered with (which usually represents, at least, the handle that
+    // the event is about) as well as information about what kind of event
+    // occurred (readable, writable, signal, etc.)
+    //
+    // Handlers may register, reregister and deregister handles while events
+    // are being dispatched. Events of a token deregistered since the poll
+    // are dropped, even if the token has been registered again in the
+    // meantime, and events of a reregistered token only report the readiness
+    // it is still interested in.
+    fn io_dispatch<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H, events: &[(u8, IoEvent)]) {
+        for &(_, evt) in events.iter() {
+            let evt = match self.poll.refresh(evt) {
+                Some(evt) => evt,
+                None => continue
+            };
 
             debug!("event={:?}", evt);
 
@@ -296,20 +978,86 @@ impl<T, M: Send> EventLoop<T, M> {
                 NOTIFY => self.notify.cleanup(),
                 _      => self.io_event(handler, evt)
             }
+        }
+    }
+
+    // Merges the events of every token into its first one, then dispatches
+    // the merged events in order. Events are grouped by sorting their
+    // positions by token, in a buffer kept from tick to tick.
+    fn io_process_merged<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H, events: &mut [(u8, IoEvent)]) {
+        // Token, position and whether it is the token's first event
+        let mut order = mem::replace(&mut self.merge_order, Vec::new());
+        order.clear();
+        order.extend(events.iter().enumerate().map(|(pos, &(_, evt))| (evt.token().as_usize(), pos, false)));
+
+        // The sort is stable, so a token's first event comes first
+        order.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut first = 0;
+
+        for i in range(0, order.len()) {
+            if i == 0 || order[i].0 != order[i - 1].0 {
+                first = order[i].1;
+                order[i].2 = true;
+            } else {
+                events[first].1 = events[first].1.merge(events[order[i].1].1);
+            }
+        }
+
+        order.sort_by(|a, b| a.1.cmp(&b.1));
+
+        for &(_, pos, is_first) in order.iter() {
+            if !is_first {
+                continue;
+            }
 
-            i += 1;
+            // Callbacks for earlier tokens may have changed the registration
+            let evt = match self.poll.refresh(events[pos].1) {
+                Some(evt) => evt,
+                None => continue
+            };
+
+            debug!("event={:?}", evt);
+
+            match evt.token() {
+                NOTIFY => self.notify.cleanup(),
+                _      => self.io_event(handler, evt)
+            }
         }
+
+        self.merge_order = order;
     }
 
-    fn io_event<H: Handler<T, M>>(&mut self, handler: &mut H, evt: IoEvent) {
+    fn io_event<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H, evt: IoEvent) {
         let tok = evt.token();
 
+        if self.config.track_readiness {
+            let mut ready = self.readiness(tok);
+            ready.insert(evt.readiness());
+            self.readiness.insert(tok, ready);
+        }
+
+        if let Some(ref mut observer) = self.observer {
+            observer.on_dispatch(tok, evt.readiness());
+        }
+
+        if self.config.unified_readiness {
+            handler.ready(self, tok, evt.readiness());
+            return;
+        }
+
         if evt.is_readable() {
             handler.readable(self, tok, evt.read_hint());
         }
 
+        // The readable callback may have changed the registration
+        let evt = match self.poll.refresh(evt) {
+            Some(evt) => evt,
+            None => return
+        };
+
         if evt.is_writable() {
-            handler.writable(self, tok);
+            handler.writable_hint(self, tok, evt.write_hint());
         }
 
         if evt.is_error() {
@@ -317,29 +1065,80 @@ impl<T, M: Send> EventLoop<T, M> {
         }
     }
 
-    fn notify<H: Handler<T, M>>(&mut self, handler: &mut H, mut cnt: usize) {
+    fn notify<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H, mut cnt: usize) {
         while cnt > 0 {
             let msg = self.notify.poll()
                 .expect("[BUG] at this point there should always be a message");
 
-            handler.notify(self, msg);
             cnt -= 1;
+
+            let msg = match self.notify_filter {
+                Some(ref mut filter) => (**filter)(msg),
+                None => Some(msg)
+            };
+
+            if let Some(msg) = msg {
+                handler.notify(self, msg);
+            }
+        }
+    }
+
+    fn timer_process<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H) {
+        let now = match self.timer {
+            Some(ref timer) => timer.now(),
+            None => return
+        };
+
+        loop {
+            let next = match self.timer {
+                Some(ref mut timer) => timer.tick_to_at(now),
+                None => return
+            };
+
+            match next {
+                Some((t, at)) => handler.timeout_at(self, t, at),
+                _ => break
+            }
         }
+
+        self.deadline_process(handler);
     }
 
-    fn timer_process<H: Handler<T, M>>(&mut self, handler: &mut H) {
-        let now = self.timer.now();
+    fn deadline_process<H: Handler<Timeout=T, Message=M>>(&mut self, handler: &mut H) {
+        let now = match self.deadlines {
+            Some(ref deadlines) => deadlines.now(),
+            None => return
+        };
 
         loop {
-            match self.timer.tick_to(now) {
-                Some(t) => handler.timeout(self, t),
+            let next = match self.deadlines {
+                Some(ref mut deadlines) => deadlines.tick_to(now),
+                None => return
+            };
+
+            match next {
+                Some(token) => {
+                    self.deadline_timeouts.remove(&token);
+
+                    handler.deadline_expired(self, token);
+                }
                 _ => return
             }
         }
     }
 }
 
-unsafe impl<T, M: Send> Sync for EventLoop<T, M> { }
+#[unsafe_destructor]
+impl<T, M: Send> Drop for EventLoop<T, M> {
+    fn drop(&mut self) {
+        // Let the senders know that nobody is listening anymore
+        let _ = self.notify.close();
+
+        // Handles owned elsewhere must not stay registered with a selector
+        // that nobody polls anymore
+        self.poll.deregister_all();
+    }
+}
 
 /// Sends messages to the EventLoop from other threads.
 pub struct EventLoopSender<M: Send> {
@@ -352,6 +1151,12 @@ impl<M: Send> Clone for EventLoopSender<M> {
     }
 }
 
+impl<T, M: Send> fmt::Debug for EventLoop<T, M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "EventLoop {{ run: {}, registered: {} }}", self.run, self.poll.len())
+    }
+}
+
 impl<M: Send> fmt::Debug for EventLoopSender<M> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         write!(fmt, "EventLoopSender<?> {{ ... }}")
@@ -365,7 +1170,10 @@ impl<M: Send> EventLoopSender<M> {
         EventLoopSender { notify: notify }
     }
 
-    pub fn send(&self, msg: M) -> Result<(), M> {
+    /// Sends a message to the event loop. Fails with `Full` if the
+    /// notification queue is at capacity, or `Closed` once the event loop
+    /// stopped accepting messages. The message is handed back on failure.
+    pub fn send(&self, msg: M) -> Result<(), NotifyError<M>> {
         self.notify.notify(msg)
     }
 }
@@ -390,12 +1198,18 @@ impl<H> EventLoopError<H> {
 #[cfg(test)]
 mod tests {
     use std::str;
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use std::sync::atomic::AtomicIsize;
     use std::sync::atomic::Ordering::SeqCst;
-    use super::EventLoop;
-    use io::{IoWriter, IoReader};
-    use {io, buf, Buf, Handler, Token};
+    use std::default::Default;
+    use std::mem;
+    use std::old_io::timer::sleep;
+    use std::thread::Thread;
+    use std::time::Duration;
+    use nix::unistd::{dup, dup2};
+    use super::{EventLoop, EventLoopConfig};
+    use io::{IoHandle, IoWriter, IoReader};
+    use {io, buf, os, timer, Buf, Handler, LoopObserver, Token};
     use os::event;
 
     type TestEventLoop = EventLoop<usize, ()>;
@@ -414,7 +1228,10 @@ mod tests {
         }
     }
 
-    impl Handler<usize, ()> for Funtimes {
+    impl Handler for Funtimes {
+        type Timeout = usize;
+        type Message = ();
+
         fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _hint: event::ReadHint) {
             (*self.rcount).fetch_add(1, SeqCst);
             assert_eq!(token, Token(10));
@@ -434,7 +1251,7 @@ mod tests {
         writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
         event_loop.register(&reader, Token(10)).unwrap();
 
-        let _ = event_loop.run_once(handler);
+        let _ = event_loop.run_once(handler, Some(Duration::seconds(1)));
         let mut b = buf::ByteBuf::mut_with_capacity(16);
 
         assert_eq!((*rcount).load(SeqCst), 1);
@@ -443,4 +1260,951 @@ mod tests {
 
         assert_eq!(str::from_utf8(b.flip().bytes()).unwrap(), "hello");
     }
+
+    struct FirstReadable {
+        first: Option<Token>
+    }
+
+    impl Handler for FirstReadable {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _hint: event::ReadHint) {
+            if self.first.is_none() {
+                self.first = Some(token);
+            }
+        }
+    }
+
+    struct ReadyEvents {
+        events: Vec<(Token, event::Ready)>
+    }
+
+    impl Handler for ReadyEvents {
+        type Timeout = usize;
+        type Message = ();
+
+        fn ready(&mut self, _event_loop: &mut TestEventLoop, token: Token, readiness: event::Ready) {
+            self.events.push((token, readiness));
+        }
+    }
+
+    struct ReadHints {
+        hints: Vec<(Token, event::ReadHint)>
+    }
+
+    impl Handler for ReadHints {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, hint: event::ReadHint) {
+            self.hints.push((token, hint));
+        }
+    }
+
+    #[test]
+    fn test_unified_readiness_merges_events() {
+        let config = EventLoopConfig {
+            unified_readiness: true,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+
+        // Readable and writable reported separately, as kqueue does
+        let hinted = event::Interest::hinted();
+        let urgent = hinted | event::Interest::readable() | event::Interest::priority() | event::Interest::rdhup();
+
+        let mut events = [
+            (0, event::IoEvent::new(hinted | event::Interest::readable(), 1)),
+            (0, event::IoEvent::new(hinted | event::Interest::writable(), 2)),
+            (0, event::IoEvent::new(hinted | event::Interest::writable(), 1)),
+            (0, event::IoEvent::new(urgent, 3))];
+
+        let mut handler = ReadyEvents { events: vec![] };
+        event_loop.io_process_merged(&mut handler, &mut events);
+
+        // Each token is dispatched once, at the position of its first event
+        assert_eq!(vec![
+            (Token(1), event::Ready::readable() | event::Ready::writable()),
+            (Token(2), event::Ready::writable()),
+            (Token(3), event::Ready::readable() | event::Ready::oob() | event::Ready::rdhup())],
+            handler.events);
+
+        // The default `ready` forwards to `readable`, hints included
+        let mut handler = ReadHints { hints: vec![] };
+        event_loop.io_process_merged(&mut handler, &mut [(0, event::IoEvent::new(urgent, 3))]);
+
+        assert_eq!(vec![(Token(3), event::ReadHint::data() | event::ReadHint::oob() | event::ReadHint::rdhup())],
+                   handler.hints);
+    }
+
+    struct ReadTokens {
+        tokens: Vec<Token>
+    }
+
+    impl Handler for ReadTokens {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _hint: event::ReadHint) {
+            self.tokens.push(token);
+        }
+    }
+
+    #[test]
+    fn test_registration() {
+        let config = EventLoopConfig {
+            default_poll_opt: event::PollOpt::edge(),
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+        let (reader, _writer) = io::pipe().unwrap();
+
+        assert_eq!(None, event_loop.registration(Token(1)));
+
+        let interest = event::Interest::readable() | event::Interest::hup();
+        let opt = event::PollOpt::edge() | event::PollOpt::oneshot();
+
+        event_loop.register_opt(&reader, Token(1), interest, opt).unwrap();
+        assert_eq!(Some((interest, opt)), event_loop.registration(Token(1)));
+
+        // The default options are resolved
+        event_loop.reregister(&reader, Token(1), event::Interest::readable(), event::PollOpt::configured()).unwrap();
+        assert_eq!(Some((event::Interest::readable(), event::PollOpt::edge())), event_loop.registration(Token(1)));
+
+        event_loop.deregister(&reader).unwrap();
+        assert_eq!(None, event_loop.registration(Token(1)));
+    }
+
+    #[test]
+    fn test_batch_reregister() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let mut pipes = vec![];
+
+        for i in range(0, 3us) {
+            let (reader, writer) = io::pipe().unwrap();
+            writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+            event_loop.register(&reader, Token(i)).unwrap();
+            pipes.push((reader, writer));
+        }
+
+        // Pause every pipe but the last one
+        let pause: Vec<_> = range(0, 2us)
+            .map(|i| (Token(i), event::Interest::none(), event::PollOpt::level()))
+            .collect();
+
+        event_loop.batch_reregister(pause.as_slice()).unwrap();
+
+        let mut handler = ReadTokens { tokens: vec![] };
+        handler = event_loop.run_once(handler, Some(Duration::milliseconds(100))).ok().expect("failed to run event loop");
+        assert_eq!(vec![Token(2)], handler.tokens);
+
+        // Resume them
+        let resume: Vec<_> = range(0, 2us)
+            .map(|i| (Token(i), event::Interest::readable(), event::PollOpt::level()))
+            .collect();
+
+        event_loop.batch_reregister(resume.as_slice()).unwrap();
+
+        handler.tokens.clear();
+        handler = event_loop.run_once(handler, Some(Duration::milliseconds(100))).ok().expect("failed to run event loop");
+        handler.tokens.sort_by(|a, b| a.as_usize().cmp(&b.as_usize()));
+        assert_eq!(vec![Token(0), Token(1), Token(2)], handler.tokens);
+
+        let err = event_loop.batch_reregister(&[(Token(7), event::Interest::readable(), event::PollOpt::level())])
+            .err().expect("expected an unknown token to fail");
+        assert!(err.is_not_registered());
+
+        // Closed without being deregistered, and the number most likely
+        // reused by the next pipe
+        drop(pipes.remove(0));
+        let (other, _other_writer) = io::pipe().unwrap();
+
+        let err = event_loop.batch_reregister(&[(Token(0), event::Interest::readable(), event::PollOpt::level())])
+            .err().expect("expected a closed handle to fail");
+        assert!(err.is_not_registered());
+        assert!(!event_loop.is_registered(Token(0)));
+
+        // The new pipe was not registered in its place
+        event_loop.register(&other, Token(8)).unwrap();
+    }
+
+    #[test]
+    fn test_fair_dispatch() {
+        let config = EventLoopConfig {
+            fair_dispatch: true,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+        let mut pipes = vec![];
+
+        // Every pipe stays readable, so each tick sees all of them
+        for i in range(0, 4us) {
+            let (reader, writer) = io::pipe().unwrap();
+            writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+            event_loop.register(&reader, Token(i)).unwrap();
+            pipes.push((reader, writer));
+        }
+
+        let mut handler = FirstReadable { first: None };
+        let mut served_first = vec![];
+
+        for _ in range(0, 4us) {
+            handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+            served_first.push(handler.first.take().unwrap().as_usize());
+        }
+
+        // Each handle got to go first once
+        served_first.sort();
+        assert_eq!(served_first, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sort_events() {
+        let config = EventLoopConfig {
+            sort_events: true,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+        let mut pipes = vec![];
+
+        for &i in [5us, 2, 8].iter() {
+            let (reader, writer) = io::pipe().unwrap();
+            writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+            event_loop.register(&reader, Token(i)).unwrap();
+            pipes.push((reader, writer));
+        }
+
+        let mut handler = ReadTokens { tokens: vec![] };
+
+        for _ in range(0, 3us) {
+            handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+            assert_eq!(vec![Token(2), Token(5), Token(8)], handler.tokens);
+            handler.tokens.clear();
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut config: EventLoopConfig = Default::default();
+        config.timer_wheel_size = 1_000;
+
+        let err = EventLoop::<usize, ()>::configured(config).err()
+            .expect("expected the config to be rejected");
+
+        assert!(err.is_invalid_config());
+        assert_eq!(err.desc(), Some("timer_wheel_size must be a power of two"));
+    }
+
+    #[test]
+    fn test_drain_notifications() {
+        let mut event_loop = EventLoop::<usize, usize>::new().unwrap();
+        let sender = event_loop.channel();
+
+        for i in range(0, 3us) {
+            sender.send(i).unwrap();
+        }
+
+        assert_eq!(vec![0, 1, 2], event_loop.drain_notifications());
+
+        match sender.send(3) {
+            Err(e) => {
+                assert!(e.is_closed());
+                assert_eq!(3, e.into_inner());
+            }
+            Ok(..) => panic!("expected the channel to be closed")
+        }
+    }
+
+    #[test]
+    fn test_drain_notifications_racing_senders() {
+        let mut event_loop = EventLoop::<usize, usize>::configured(EventLoopConfig {
+            notify_capacity: 1 << 16,
+            .. Default::default()
+        }).unwrap();
+
+        let senders: Vec<_> = range(0, 4us).map(|_| {
+            let sender = event_loop.channel();
+
+            Thread::scoped(move || {
+                let mut sent = 0us;
+
+                loop {
+                    match sender.send(sent) {
+                        Ok(()) => sent += 1,
+                        Err(ref e) if e.is_full() => continue,
+                        Err(_) => return sent
+                    }
+                }
+            })
+        }).collect();
+
+        sleep(Duration::milliseconds(10));
+        let drained = event_loop.drain_notifications();
+
+        // Every send that succeeded left its message in the queue
+        let sent = senders.into_iter()
+            .map(|s| s.join().ok().unwrap())
+            .fold(0, |a, b| a + b);
+
+        assert_eq!(sent, drained.len());
+    }
+
+    struct Messages {
+        received: Vec<usize>
+    }
+
+    impl Handler for Messages {
+        type Timeout = ();
+        type Message = usize;
+
+        fn notify(&mut self, _event_loop: &mut EventLoop<(), usize>, msg: usize) {
+            self.received.push(msg);
+        }
+    }
+
+    #[test]
+    fn test_notify_filter() {
+        let mut event_loop = EventLoop::<(), usize>::new().unwrap();
+        let sender = event_loop.channel();
+
+        // Drop every other message and tag the rest
+        let mut seen = 0us;
+
+        event_loop.set_notify_filter(move |msg| {
+            seen += 1;
+
+            if seen % 2 == 0 {
+                None
+            } else {
+                Some(msg * 10)
+            }
+        });
+
+        for i in range(0, 6us) {
+            sender.send(i).unwrap();
+        }
+
+        let handler = event_loop.run_once(Messages { received: vec![] }, Some(Duration::milliseconds(100)))
+            .ok().expect("failed to run event loop");
+
+        assert_eq!(vec![0, 20, 40], handler.received);
+
+        event_loop.clear_notify_filter();
+        sender.send(7).unwrap();
+
+        let handler = event_loop.run_once(handler, Some(Duration::milliseconds(100)))
+            .ok().expect("failed to run event loop");
+
+        assert_eq!(vec![0, 20, 40, 7], handler.received);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_reregister_closed() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let (reader, _writer) = io::pipe().unwrap();
+
+        event_loop.register(&reader, Token(1)).unwrap();
+
+        // Close the descriptor behind the event loop's back
+        let desc = os::IoDesc { fd: reader.desc().fd };
+        drop(reader);
+
+        let err = event_loop.reregister(&desc, Token(1), event::Interest::readable(), event::PollOpt::edge())
+            .err().expect("expected reregistering a closed handle to fail");
+
+        assert!(err.is_not_registered());
+        assert!(!event_loop.is_registered(Token(1)));
+
+        // The descriptor is already closed
+        mem::forget(desc);
+    }
+
+    #[test]
+    fn test_register_reused_fd() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let (reader, _writer) = io::pipe().unwrap();
+        let (other, _other_writer) = io::pipe().unwrap();
+
+        event_loop.register(&reader, Token(1)).unwrap();
+
+        // Close the reader behind the event loop's back, reusing its number
+        // for the other pipe
+        dup2(other.desc().fd, reader.desc().fd).unwrap();
+
+        // Registering the number again drops the stale registration
+        event_loop.register(&reader, Token(2)).unwrap();
+
+        assert!(!event_loop.is_registered(Token(1)));
+        assert!(event_loop.is_registered(Token(2)));
+
+        event_loop.deregister(&reader).unwrap();
+        assert!(!event_loop.is_registered(Token(2)));
+    }
+
+    #[test]
+    fn test_timers_disabled() {
+        let config = EventLoopConfig {
+            timer_wheel_size: 0,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+
+        let err = event_loop.timeout(1, Duration::milliseconds(10)).err()
+            .expect("expected scheduling a timeout to fail");
+
+        assert!(err.is_disabled());
+    }
+
+    struct PipeEvents {
+        hints: Vec<event::ReadHint>
+    }
+
+    impl Handler for PipeEvents {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, _event_loop: &mut TestEventLoop, _token: Token, hint: event::ReadHint) {
+            self.hints.push(hint);
+        }
+    }
+
+    #[test]
+    fn test_pipe_from_raw_fd() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let (orig, writer) = io::pipe().unwrap();
+
+        // The reader takes the descriptor over
+        let fd = orig.desc().fd;
+        mem::forget(orig);
+
+        let reader = io::PipeReader::from_raw_fd(fd).unwrap();
+
+        let mut buf = [0u8; 16];
+        assert!(reader.read_slice(&mut buf).unwrap().would_block());
+
+        event_loop.register_opt(&reader, Token(1), event::Interest::readable(), event::PollOpt::edge()).unwrap();
+        writer.write_slice(b"cmd\n").unwrap();
+
+        let mut handler = PipeEvents { hints: vec![] };
+        handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+
+        assert_eq!(1, handler.hints.len());
+        assert!(handler.hints[0].is_data());
+        assert_eq!(4, reader.read_slice(&mut buf).unwrap().unwrap());
+        assert!(reader.read_slice(&mut buf).unwrap().would_block());
+
+        // Closing the write end hangs the reader up
+        drop(writer);
+
+        handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+
+        assert_eq!(2, handler.hints.len());
+        assert!(handler.hints[1].is_hup());
+        assert!(reader.read_slice(&mut buf).unwrap_err().is_eof());
+    }
+
+    struct SpuriousReads {
+        readers: Vec<io::PipeReader>,
+        reads: Vec<Option<usize>>
+    }
+
+    impl Handler for SpuriousReads {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _hint: event::ReadHint) {
+            let mut buf = [0u8; 16];
+            let res = self.readers[token.as_usize()].read_slice(&mut buf).unwrap();
+
+            self.reads.push(res.ready());
+        }
+    }
+
+    #[test]
+    fn test_spurious_readable() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let (reader, writer) = io::pipe().unwrap();
+
+        // Both handles are notified, only one of them gets the data
+        let copy = io::PipeReader::from_raw_fd(dup(reader.desc().fd).unwrap()).unwrap();
+
+        event_loop.register_opt(&reader, Token(0), event::Interest::readable(), event::PollOpt::edge()).unwrap();
+        event_loop.register_opt(&copy, Token(1), event::Interest::readable(), event::PollOpt::edge()).unwrap();
+        writer.write_slice(b"ping").unwrap();
+
+        let mut handler = SpuriousReads { readers: vec![reader, copy], reads: vec![] };
+        handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+
+        assert_eq!(2, handler.reads.len());
+        assert!(handler.reads.contains(&Some(4)));
+        assert!(handler.reads.contains(&None));
+    }
+
+    struct CachedReadiness {
+        reader: io::PipeReader,
+        reads: usize
+    }
+
+    impl Handler for CachedReadiness {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _hint: event::ReadHint) {
+            let mut buf = [0u8; 4];
+
+            loop {
+                assert!(event_loop.readiness(token).is_readable());
+
+                match self.reader.read_slice(&mut buf).unwrap() {
+                    io::NonBlock::Ready(_) => self.reads += 1,
+                    io::NonBlock::WouldBlock => {
+                        event_loop.clear_readiness(token, event::Ready::readable());
+                        break;
+                    }
+                }
+            }
+
+            assert!(!event_loop.readiness(token).is_readable());
+        }
+    }
+
+    #[test]
+    fn test_readiness_cache() {
+        let config = EventLoopConfig {
+            track_readiness: true,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+        let (reader, writer) = io::pipe().unwrap();
+
+        event_loop.register_opt(&reader, Token(1), event::Interest::readable(), event::PollOpt::edge()).unwrap();
+        assert!(event_loop.readiness(Token(1)) == event::Ready::none());
+
+        writer.write_slice(b"pingpong").unwrap();
+
+        let mut handler = CachedReadiness { reader: reader, reads: 0 };
+        handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+
+        assert_eq!(2, handler.reads);
+        assert!(!event_loop.readiness(Token(1)).is_readable());
+
+        // Forgotten once deregistered
+        event_loop.deregister(&handler.reader).unwrap();
+        assert!(event_loop.readiness(Token(1)) == event::Ready::none());
+        assert!(event_loop.readiness.is_empty());
+    }
+
+    #[test]
+    fn test_readiness_not_tracked_by_default() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let (reader, writer) = io::pipe().unwrap();
+
+        event_loop.register(&reader, Token(10)).unwrap();
+        writer.write_slice(b"ping").unwrap();
+
+        let handler = Funtimes::new(Arc::new(AtomicIsize::new(0)), Arc::new(AtomicIsize::new(0)));
+        event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+
+        assert!(event_loop.readiness(Token(10)) == event::Ready::none());
+    }
+
+    #[test]
+    fn test_drop_with_live_registrations() {
+        let (reader, writer) = io::pipe().unwrap();
+        let (owned, owned_writer) = io::pipe().unwrap();
+
+        let registration = {
+            let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+
+            event_loop.register_opt(&reader, Token(1), event::Interest::readable(), event::PollOpt::edge()).unwrap();
+            event_loop.register_owned(owned, Token(2), event::Interest::readable(), event::PollOpt::edge()).unwrap()
+        };
+
+        // Neither descriptor was closed with the loop: fcntl fails with
+        // EBADF on a closed one
+        assert!(os::is_nonblock(reader.desc()).is_ok());
+        assert!(os::is_nonblock(registration.desc()).is_ok());
+
+        // The handle owned elsewhere is still open and usable
+        let mut buf = [0u8; 16];
+        writer.write_slice(b"ping").unwrap();
+        assert_eq!(4, reader.read_slice(&mut buf).unwrap().unwrap());
+
+        // and can move to another event loop
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        event_loop.register_opt(&reader, Token(1), event::Interest::readable(), event::PollOpt::edge()).unwrap();
+        writer.write_slice(b"pong").unwrap();
+
+        let mut handler = PipeEvents { hints: vec![] };
+        handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+        assert_eq!(1, handler.hints.len());
+
+        // The guard outlived its loop, dropping it still closes the pipe
+        owned_writer.write_slice(b"ping").unwrap();
+        assert_eq!(4, registration.read_slice(&mut buf).unwrap().unwrap());
+        drop(registration);
+    }
+
+    struct Timeouts {
+        fired: Vec<(usize, u64)>
+    }
+
+    impl Handler for Timeouts {
+        type Timeout = usize;
+        type Message = ();
+
+        fn timeout_at(&mut self, event_loop: &mut TestEventLoop, timeout: usize, scheduled: u64) {
+            self.fired.push((timeout, scheduled));
+
+            if self.fired.len() == 3 {
+                event_loop.shutdown();
+            }
+        }
+    }
+
+    #[test]
+    fn test_timeouts_in_scheduled_order() {
+        let config = EventLoopConfig {
+            timer_tick_ms: 100,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+        let start = timer::now_ms();
+
+        // Likely all due within the same tick
+        event_loop.timeout(3, Duration::milliseconds(30)).unwrap();
+        event_loop.timeout(1, Duration::milliseconds(10)).unwrap();
+        event_loop.timeout(2, Duration::milliseconds(20)).unwrap();
+
+        let handler = event_loop.run(Timeouts { fired: vec![] })
+            .ok().expect("failed to run event loop");
+
+        let order: Vec<usize> = handler.fired.iter().map(|&(t, _)| t).collect();
+        assert_eq!(vec![1, 2, 3], order);
+
+        for &(t, scheduled) in handler.fired.iter() {
+            let delay = scheduled - start;
+            assert!(delay >= 10 * t as u64 && delay < 10 * t as u64 + 10, "t={}; delay={}", t, delay);
+        }
+    }
+
+    #[test]
+    fn test_timer_stats() {
+        let config = EventLoopConfig {
+            timer_capacity: 4,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+
+        for i in range(0, 4us) {
+            event_loop.timeout(i, Duration::milliseconds(100)).unwrap();
+        }
+
+        // Over capacity: the error is reported and counted
+        assert!(event_loop.timeout(4, Duration::milliseconds(100)).is_err());
+
+        let stats = event_loop.timer_stats();
+        assert_eq!(4, stats.scheduled);
+        assert_eq!(4, stats.peak);
+        assert_eq!(4, stats.capacity);
+        assert_eq!(1, stats.overflows);
+    }
+
+    #[test]
+    fn test_max_io_sources() {
+        let config = EventLoopConfig {
+            max_io_sources: 2,
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+        let mut pipes = vec![];
+
+        for i in range(0, 3us) {
+            pipes.push(io::pipe().unwrap());
+
+            let res = event_loop.register(&pipes[i].0, Token(i));
+
+            if i < 2 {
+                res.unwrap();
+            } else {
+                assert!(res.err().expect("expected the limit to be enforced").is_resource_limit());
+            }
+        }
+
+        // Deregistering frees up room for another handle
+        event_loop.deregister(&pipes[0].0).unwrap();
+        event_loop.register(&pipes[2].0, Token(2)).unwrap();
+
+        // Closing a handle without deregistering it does not
+        pipes.remove(1);
+
+        let (reader, _writer) = io::pipe().unwrap();
+        let res = event_loop.register(&reader, Token(3));
+        assert!(res.err().expect("expected the closed handle to still count").is_resource_limit());
+    }
+
+    #[test]
+    fn test_synthesize_initial() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let rcount = Arc::new(AtomicIsize::new(0));
+        let wcount = Arc::new(AtomicIsize::new(0));
+        let handler = Funtimes::new(rcount.clone(), wcount.clone());
+
+        // The data is written before registering, so no new edge occurs
+        let (reader, writer) = io::pipe().unwrap();
+        writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+
+        let opt = event::PollOpt::edge() | event::PollOpt::synthesize_initial();
+        event_loop.register_opt(&reader, Token(10), event::Interest::readable(), opt).unwrap();
+
+        // Dispatched exactly once, whether or not the poll reports it too
+        let handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().unwrap();
+        assert_eq!(1, (*rcount).load(SeqCst));
+
+        let _ = event_loop.run_once(handler, Some(Duration::milliseconds(50)));
+        assert_eq!(1, (*rcount).load(SeqCst));
+    }
+
+    #[test]
+    fn test_default_poll_opt() {
+        let config = EventLoopConfig {
+            default_poll_opt: event::PollOpt::edge(),
+            .. Default::default()
+        };
+
+        let mut event_loop: TestEventLoop = EventLoop::configured(config).unwrap();
+
+        let opt = event_loop.resolve_poll_opt(event::PollOpt::configured() | event::PollOpt::oneshot());
+        assert_eq!(event::PollOpt::edge() | event::PollOpt::oneshot(), opt);
+
+        // Explicit options are left alone
+        let opt = event_loop.resolve_poll_opt(event::PollOpt::level());
+        assert_eq!(event::PollOpt::level(), opt);
+
+        // `register` uses the configured options as well
+        let (reader, _writer) = io::pipe().unwrap();
+        event_loop.register(&reader, Token(1)).unwrap();
+        assert_eq!(Some((event::Interest::readable(), event::PollOpt::edge())), event_loop.registration(Token(1)));
+
+        assert!(!event::PollOpt::all().is_configured());
+    }
+
+    struct BeforePoll {
+        calls: Vec<&'static str>
+    }
+
+    impl Handler for BeforePoll {
+        type Timeout = usize;
+        type Message = ();
+
+        fn readable(&mut self, _event_loop: &mut TestEventLoop, _token: Token, _hint: event::ReadHint) {
+            self.calls.push("readable");
+        }
+
+        fn before_poll(&mut self, _event_loop: &mut TestEventLoop) -> Option<Duration> {
+            self.calls.push("before_poll");
+            Some(Duration::milliseconds(0))
+        }
+    }
+
+    #[test]
+    fn test_before_poll() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+
+        let (reader, writer) = io::pipe().unwrap();
+        writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+        event_loop.register(&reader, Token(10)).unwrap();
+
+        // The override turns an otherwise unbounded wait into a non-blocking
+        // poll, so the second tick returns right away
+        let mut handler = BeforePoll { calls: vec![] };
+        handler = event_loop.run_once(handler, None).ok().expect("failed to run event loop");
+        reader.read(&mut buf::ByteBuf::mut_with_capacity(16)).unwrap();
+        handler = event_loop.run_once(handler, None).ok().expect("failed to run event loop");
+
+        assert_eq!(vec!["before_poll", "readable", "before_poll"], handler.calls);
+    }
+
+    #[test]
+    fn test_now() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+
+        let start = event_loop.now();
+        assert_eq!(start, event_loop.now());
+
+        let handler = Funtimes::new(Arc::new(AtomicIsize::new(0)), Arc::new(AtomicIsize::new(0)));
+        let _ = event_loop.run_once(handler, Some(Duration::milliseconds(20)));
+
+        // A new sample is taken at the start of the tick
+        assert!(event_loop.now() >= start);
+    }
+
+    struct Recorder {
+        seen: Arc<Mutex<Vec<(&'static str, Token)>>>
+    }
+
+    impl LoopObserver for Recorder {
+        fn on_register(&mut self, token: Token, _interest: event::Interest, _opt: event::PollOpt) {
+            self.seen.lock().unwrap().push(("register", token));
+        }
+
+        fn on_deregister(&mut self, token: Token) {
+            self.seen.lock().unwrap().push(("deregister", token));
+        }
+
+        fn on_event(&mut self, token: Token, readiness: event::Ready) {
+            assert!(readiness.is_readable());
+            self.seen.lock().unwrap().push(("event", token));
+        }
+
+        fn on_dispatch(&mut self, token: Token, _readiness: event::Ready) {
+            self.seen.lock().unwrap().push(("dispatch", token));
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let seen = Arc::new(Mutex::new(vec![]));
+
+        event_loop.set_observer(Box::new(Recorder { seen: seen.clone() }));
+
+        let (reader, writer) = io::pipe().unwrap();
+        event_loop.register(&reader, Token(10)).unwrap();
+        writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+
+        let handler = Funtimes::new(Arc::new(AtomicIsize::new(0)), Arc::new(AtomicIsize::new(0)));
+        event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+
+        assert_eq!(vec![("register", Token(10)), ("event", Token(10)), ("dispatch", Token(10))], *seen.lock().unwrap());
+
+        event_loop.deregister(&reader).unwrap();
+        assert_eq!(("deregister", Token(10)), seen.lock().unwrap()[3]);
+
+        // Synthetic events are reported like polled ones
+        let (idle, _idle_writer) = io::pipe().unwrap();
+        event_loop.register(&idle, Token(10)).unwrap();
+        event_loop.synthetic.push(event::IoEvent::new(event::Interest::readable(), 10));
+
+        let handler = Funtimes::new(Arc::new(AtomicIsize::new(0)), Arc::new(AtomicIsize::new(0)));
+        event_loop.run_once(handler, Some(Duration::milliseconds(50))).ok().expect("failed to run event loop");
+
+        assert_eq!(vec![("register", Token(10)), ("event", Token(10)), ("dispatch", Token(10))], seen.lock().unwrap()[4..].to_vec());
+
+        // Without an observer, nothing is recorded
+        assert!(event_loop.take_observer().is_some());
+        event_loop.register(&io::pipe().unwrap().0, Token(11)).unwrap();
+        assert_eq!(7, seen.lock().unwrap().len());
+    }
+
+    #[test]
+    fn test_run_for() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let handler = Funtimes::new(Arc::new(AtomicIsize::new(0)), Arc::new(AtomicIsize::new(0)));
+
+        let start = timer::now_ms();
+        event_loop.run_for(handler, Duration::milliseconds(200)).ok().expect("failed to run event loop");
+        let elapsed = timer::now_ms() - start;
+
+        // Well before the one second an idle poll would otherwise wait
+        assert!(elapsed >= 200, "returned early; elapsed={}", elapsed);
+        assert!(elapsed < 500, "returned late; elapsed={}", elapsed);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_register_twice() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let (reader, _writer) = io::pipe().unwrap();
+
+        event_loop.register(&reader, Token(1)).unwrap();
+
+        let err = event_loop.register(&reader, Token(2)).err()
+            .expect("expected registering twice to fail");
+
+        assert!(err.is_already_registered());
+
+        // The original registration is left alone
+        assert!(event_loop.is_registered(Token(1)));
+        assert!(!event_loop.is_registered(Token(2)));
+    }
+
+    struct Deadlines {
+        expired: Vec<Token>
+    }
+
+    impl Handler for Deadlines {
+        type Timeout = usize;
+        type Message = ();
+
+        fn deadline_expired(&mut self, event_loop: &mut TestEventLoop, token: Token) {
+            self.expired.push(token);
+
+            if self.expired.len() == 2 {
+                event_loop.shutdown();
+            }
+        }
+    }
+
+    #[test]
+    fn test_deadlines() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let now = event_loop.now();
+
+        event_loop.set_deadline(Token(1), now + 100).unwrap();
+        event_loop.set_deadline(Token(2), now + 200).unwrap();
+        event_loop.set_deadline(Token(3), now + 100).unwrap();
+
+        // Setting a deadline again replaces the previous one
+        event_loop.set_deadline(Token(1), now + 300).unwrap();
+        assert!(event_loop.clear_deadline(Token(3)));
+        assert!(!event_loop.clear_deadline(Token(3)));
+
+        let handler = event_loop.run(Deadlines { expired: vec![] })
+            .ok().expect("failed to run event loop");
+
+        assert_eq!(vec![Token(2), Token(1)], handler.expired);
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+        let mut pipes = vec![];
+
+        for i in range(0, 4us) {
+            let (reader, writer) = io::pipe().unwrap();
+            writer.write(&mut buf::SliceBuf::wrap("hello".as_bytes())).unwrap();
+            event_loop.register(&reader, Token(i)).unwrap();
+            pipes.push((reader, writer));
+        }
+
+        event_loop.set_priority(Token(1), 10);
+        event_loop.set_priority(Token(3), 20);
+        assert_eq!(20, event_loop.priority(Token(3)));
+        assert_eq!(0, event_loop.priority(Token(0)));
+
+        let mut handler = FirstReadable { first: None };
+
+        for _ in range(0, 3us) {
+            handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+            assert_eq!(Some(Token(3)), handler.first.take());
+        }
+
+        // Back to the default, the next highest goes first
+        event_loop.set_priority(Token(3), 0);
+
+        handler = event_loop.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+        assert_eq!(Some(Token(1)), handler.first.take());
+    }
 }
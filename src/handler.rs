@@ -1,18 +1,135 @@
+use std::time::duration::Duration;
 use event_loop::EventLoop;
 use os::token::Token;
 use os::event;
 
+/// Receives the events of an `EventLoop`.
+///
+/// The types of the timeouts and messages the handler deals with are
+/// declared once, as associated types, and the event loop running the
+/// handler is an `EventLoop<Self::Timeout, Self::Message>`:
+///
+/// ```
+/// use mio::{EventLoop, Handler};
+///
+/// struct MyHandler;
+///
+/// impl Handler for MyHandler {
+///     type Timeout = u64;
+///     type Message = String;
+///
+///     fn notify(&mut self, event_loop: &mut EventLoop<u64, String>, msg: String) {
+///         event_loop.shutdown();
+///     }
+/// }
+/// ```
+///
+/// Code written against the former `Handler<T, M>` trait migrates by moving
+/// the type parameters into the impl: `impl Handler<T, M> for H` becomes
+/// `impl Handler for H` with `type Timeout = T;` and `type Message = M;`.
+/// Callbacks keep their signatures.
 #[allow(unused_variables)]
-pub trait Handler<T, M: Send> {
-    fn readable(&mut self, event_loop: &mut EventLoop<T, M>, token: Token, hint: event::ReadHint) {
+pub trait Handler {
+    /// The value handed back when a timeout set with `EventLoop::timeout`
+    /// fires.
+    type Timeout;
+
+    /// The messages sent to the event loop through its channel.
+    type Message: Send;
+
+    fn readable(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, token: Token, hint: event::ReadHint) {
+    }
+
+    fn writable(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, token: Token) {
+    }
+
+    /// Invoked by the event loop when a handle becomes writable. The hint
+    /// tells apart a plain writable handle from one that hung up or errored,
+    /// which is how the outcome of a non-blocking connect is reported.
+    ///
+    /// The default implementation discards the hint and forwards to
+    /// `writable`.
+    fn writable_hint(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, token: Token, hint: event::WriteHint) {
+        self.writable(event_loop, token);
+    }
+
+    /// Invoked instead of `readable` and `writable_hint` when the event
+    /// loop is configured with `unified_readiness`, see `EventLoopConfig`.
+    /// All the readiness reported for `token` within a tick is delivered in
+    /// a single call, which leaves the order in which reading, writing and
+    /// hangups are handled up to the handler.
+    ///
+    /// The default implementation forwards to `readable` and then
+    /// `writable_hint`, like the split dispatch does.
+    fn ready(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, token: Token, readiness: event::Ready) {
+        if readiness.is_readable() || readiness.is_hup() {
+            let mut hint = event::ReadHint::none();
+
+            if readiness.is_readable() { hint.insert(event::ReadHint::data()); }
+            if readiness.is_hup() { hint.insert(event::ReadHint::hup()); }
+            if readiness.is_error() { hint.insert(event::ReadHint::error()); }
+            if readiness.is_oob() { hint.insert(event::ReadHint::oob()); }
+            if readiness.is_rdhup() { hint.insert(event::ReadHint::rdhup()); }
+
+            self.readable(event_loop, token, hint);
+        }
+
+        if readiness.is_writable() {
+            let mut hint = event::WriteHint::ready();
+
+            if readiness.is_hup() { hint.insert(event::WriteHint::hup()); }
+            if readiness.is_error() { hint.insert(event::WriteHint::error()); }
+
+            self.writable_hint(event_loop, token, hint);
+        }
+    }
+
+    fn notify(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, msg: Self::Message) {
+    }
+
+    fn timeout(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, timeout: Self::Timeout) {
+    }
+
+    /// Invoked when a timeout set with `EventLoop::timeout` fires, along with
+    /// the time it was scheduled for, in milliseconds on the clock of
+    /// `EventLoop::now`. Timeouts firing in the same tick are delivered in
+    /// the order of their scheduled times, and, for equal times, in the
+    /// order they were set.
+    ///
+    /// The default implementation discards the time and forwards to
+    /// `timeout`.
+    fn timeout_at(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, timeout: Self::Timeout, scheduled: u64) {
+        self.timeout(event_loop, timeout);
     }
 
-    fn writable(&mut self, event_loop: &mut EventLoop<T, M>, token: Token) {
+    /// Invoked when the deadline set for `token` with
+    /// `EventLoop::set_deadline` passes before being set again or cleared.
+    /// Typically used to close idle connections.
+    fn deadline_expired(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>, token: Token) {
     }
 
-    fn notify(&mut self, event_loop: &mut EventLoop<T, M>, msg: M) {
+    /// Invoked when a signal interrupted the event loop while it was waiting
+    /// for IO events. The poll is simply retried on the next tick, so the
+    /// default implementation does nothing. Call `event_loop.shutdown()` to
+    /// stop the loop in response to a signal instead.
+    fn interrupted(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>) {
     }
 
-    fn timeout(&mut self, event_loop: &mut EventLoop<T, M>, timeout: T) {
+    /// Invoked on every tick right before the event loop waits for IO
+    /// events, which makes it the last chance to act on work accumulated
+    /// while handling the previous tick, e.g. to flush batched writes.
+    ///
+    /// Within a tick, the order is: `before_poll`, the wait for IO events,
+    /// then `readable` / `writable`, `notify` and finally `timeout`
+    /// callbacks. So `before_poll` runs after all of the previous tick's
+    /// callbacks, including its timeouts.
+    ///
+    /// Returning `Some` overrides how long the loop waits for IO events.
+    /// The default wait already ends when the next timeout is due; an
+    /// override does not, so a longer one delays timeouts. The loop does not
+    /// wait at all if notifications are pending, whatever the override. The
+    /// default implementation returns `None`.
+    fn before_poll(&mut self, event_loop: &mut EventLoop<Self::Timeout, Self::Message>) -> Option<Duration> {
+        None
     }
 }
@@ -7,6 +7,14 @@ use os;
 pub use os::IoDesc;
 
 /// The result of a non-blocking operation.
+///
+/// A readiness event is a hint, not a guarantee: an operation attempted
+/// right after `readable` or `writable` may still return `WouldBlock`. This
+/// happens when the data was already consumed through another handle to the
+/// same file, when a previous callback drained the socket before an edge
+/// that was already queued got delivered, or because of kernel quirks.
+/// Handlers should treat it as a no-op and wait for the next event rather
+/// than as an error.
 #[derive(Debug)]
 pub enum NonBlock<T> {
     Ready(T),
@@ -21,6 +29,16 @@ impl<T> NonBlock<T> {
         }
     }
 
+    /// Returns the result if the operation completed, and `None` if it would
+    /// have blocked, which is not an error even right after a readiness
+    /// event.
+    pub fn ready(self) -> Option<T> {
+        match self {
+            Ready(v) => Some(v),
+            WouldBlock => None
+        }
+    }
+
     pub fn unwrap(self) -> T {
         match self {
             Ready(v) => v,
@@ -38,17 +56,26 @@ pub trait FromIoDesc {
 }
 
 pub trait IoReader {
+    /// Reads into the writable part of `buf` and advances it by the number
+    /// of bytes read. Any `MutBuf` works, not only the buffers in `buf`, so
+    /// data can be read straight into an application's own buffer type.
     fn read<B: MutBuf>(&self, buf: &mut B) -> MioResult<NonBlock<usize>>;
     fn read_slice(&self, buf: &mut [u8]) -> MioResult<NonBlock<usize>>;
 }
 
 pub trait IoWriter {
+    /// Writes from the readable part of `buf` and advances it by the number
+    /// of bytes written. Like `read`, it takes any `Buf`.
     fn write<B: Buf>(&self, buf: &mut B) -> MioResult<NonBlock<usize>>;
     fn write_slice(&self, buf: &[u8]) -> MioResult<NonBlock<usize>>;
 }
 
 pub trait IoAcceptor {
     type Output;
+
+    /// Accepts a pending connection. Returns `WouldBlock` when there is no
+    /// connection waiting, the same way reads and writes report that they
+    /// would have blocked. The outer `MioResult` only carries actual errors.
     fn accept(&mut self) -> MioResult<NonBlock<Self::Output>>;
 }
 
@@ -57,10 +84,47 @@ pub fn pipe() -> MioResult<(PipeReader, PipeWriter)> {
     Ok((PipeReader { desc: rd }, PipeWriter { desc: wr }))
 }
 
+/// Returns a reader for standard input, see `PipeReader::from_raw_fd`.
+/// Descriptor 0 is duplicated, so dropping the reader leaves standard
+/// input open.
+pub fn stdin() -> MioResult<PipeReader> {
+    let desc = try!(os::dup(0));
+    try!(os::set_nonblock(&desc, true));
+
+    Ok(PipeReader { desc: desc })
+}
+
 pub struct PipeReader {
     desc: os::IoDesc
 }
 
+impl PipeReader {
+    /// Creates a reader for a descriptor opened outside of mio, such as the
+    /// read end of a pipe inherited from a parent process. The descriptor
+    /// is made non-blocking: reading an empty pipe returns `WouldBlock`.
+    ///
+    /// Like `TcpAcceptor::from_raw_fd`, the reader takes ownership of `fd`
+    /// and closes it when dropped, or right away if this fails. Use `stdin`
+    /// for standard input.
+    ///
+    /// The non-blocking flag belongs to the open file, not the descriptor,
+    /// so it also applies to any other process sharing it, a shell's
+    /// terminal for example.
+    ///
+    /// Unlike sockets, pipes have no half-close: once every write end is
+    /// closed, the reader becomes readable with a hup hint, and reads
+    /// return the remaining data followed by an EOF error. As with any
+    /// edge triggered registration, keep reading until `WouldBlock` or EOF.
+    /// Regular files, e.g. standard input redirected from a file, cannot be
+    /// registered with epoll.
+    pub fn from_raw_fd(fd: i32) -> MioResult<PipeReader> {
+        let desc = os::IoDesc { fd: fd };
+        try!(os::set_nonblock(&desc, true));
+
+        Ok(PipeReader { desc: desc })
+    }
+}
+
 impl IoHandle for PipeReader {
     fn desc(&self) -> &os::IoDesc {
         &self.desc
@@ -53,7 +53,10 @@
 //! // Define a handler to process the events
 //! struct MyHandler(TcpAcceptor);
 //!
-//! impl Handler<(), ()> for MyHandler {
+//! impl Handler for MyHandler {
+//!     type Timeout = ();
+//!     type Message = ();
+//!
 //!     fn readable(&mut self, event_loop: &mut EventLoop<(), ()>, token: Token, _: ReadHint) {
 //!         match token {
 //!             SERVER => {
@@ -88,6 +91,7 @@
 
 extern crate alloc;
 extern crate bytes;
+extern crate libc;
 extern crate nix;
 extern crate time;
 
@@ -103,11 +107,15 @@ pub use error::{
     MioError,
     MioErrorKind
 };
+pub use event_fd::{
+    EventFd
+};
 pub use handler::{
     Handler,
 };
 pub use io::{
     pipe,
+    stdin,
     NonBlock,
     FromIoDesc,
     IoReader,
@@ -118,9 +126,22 @@ pub use io::{
     PipeReader,
     PipeWriter,
 };
+pub use observer::{
+    LoopObserver
+};
+pub use notify::{
+    NotifyError
+};
 pub use poll::{
     Poll
 };
+pub use registration::{
+    Registration
+};
+pub use shared_queue::{
+    SharedQueue,
+    QueueReceiver
+};
 pub use event_loop::{
     EventLoop,
     EventLoopConfig,
@@ -131,7 +152,8 @@ pub use event_loop::{
 pub use timer::{
     Timeout,
     TimerError,
-    TimerResult
+    TimerResult,
+    TimerStats
 };
 pub use os::token::{
     Token,
@@ -140,18 +162,27 @@ pub use os::event::{
     PollOpt,
     Interest,
     ReadHint,
+    WriteHint,
+    Ready,
 };
 
+pub mod dns;
 pub mod net;
+#[cfg(any(test, feature = "testing"))]
+pub mod test;
 pub mod util;
 
 mod error;
+mod event_fd;
 mod event_loop;
 mod handler;
 mod io;
 mod notify;
+mod observer;
 mod os;
 mod poll;
+mod registration;
+mod shared_queue;
 mod timer;
 
 // Re-export bytes
@@ -167,4 +198,16 @@ pub mod buf {
         SliceBuf,
         MutSliceBuf,
     };
+
+    pub use self::buf_ext::{BufExt, MutBufExt};
+    pub use self::buf_reader::BufReader;
+    pub use self::buffered_writer::BufferedWriter;
+    pub use self::length_delimited::{LengthDelimitedCodec, Endianness, CodecError, DEFAULT_MAX_FRAME_LEN};
+    pub use self::vec_buf::VecBuf;
+
+    mod buf_ext;
+    mod buf_reader;
+    mod buffered_writer;
+    mod length_delimited;
+    mod vec_buf;
 }
@@ -25,6 +25,27 @@ pub trait Socket : IoHandle {
         os::set_linger(self.desc(), dur_s)
     }
 
+    /// Makes closing the socket reset the connection rather than shut it
+    /// down, discarding any data not sent yet. This is `SO_LINGER` enabled
+    /// with a zero timeout, which `set_linger(0)` does not do: it turns
+    /// lingering off, leaving the close graceful.
+    fn set_reset_on_close(&self) -> MioResult<()> {
+        os::set_reset_on_close(self.desc())
+    }
+
+    /// Switches the socket between non-blocking and blocking mode. Sockets
+    /// created by mio are non-blocking, which the event loop relies on; this
+    /// is meant for handing a socket to, or taking one over from, code that
+    /// expects otherwise.
+    fn set_nonblocking(&self, val: bool) -> MioResult<()> {
+        os::set_nonblock(self.desc(), val)
+    }
+
+    /// Returns true if the socket is in non-blocking mode.
+    fn is_nonblocking(&self) -> MioResult<bool> {
+        os::is_nonblock(self.desc())
+    }
+
     fn set_reuseaddr(&self, val: bool) -> MioResult<()> {
         os::set_reuseaddr(self.desc(), val)
     }
@@ -32,6 +53,36 @@ pub trait Socket : IoHandle {
     fn set_reuseport(&self, val: bool) -> MioResult<()> {
         os::set_reuseport(self.desc(), val)
     }
+
+    /// Binds the socket to a specific network interface, such as `"eth0"`.
+    /// Only packets received on that interface are processed by the socket
+    /// and, for connecting sockets, outgoing packets leave through it.
+    ///
+    /// Wraps `SO_BINDTODEVICE`, which is only available on Linux and requires
+    /// the `CAP_NET_RAW` capability. Other platforms return an `Unsupported`
+    /// error.
+    fn bind_device(&self, ifname: &str) -> MioResult<()> {
+        os::bind_device(self.desc(), ifname)
+    }
+
+    /// Returns the name of the interface the socket is bound to, if any.
+    fn device(&self) -> MioResult<Option<String>> {
+        os::device(self.desc())
+    }
+
+    /// Sets the type-of-service byte for outbound packets. The upper six bits
+    /// are the DSCP value, so marking traffic as expedited forwarding (EF,
+    /// DSCP 46) is `set_tos(46 << 2)`.
+    ///
+    /// Wraps `IP_TOS` on IPv4 sockets and `IPV6_TCLASS` on IPv6 sockets.
+    fn set_tos(&self, tos: u8) -> MioResult<()> {
+        os::set_tos(self.desc(), tos)
+    }
+
+    /// Returns the type-of-service byte for outbound packets.
+    fn tos(&self) -> MioResult<u8> {
+        os::tos(self.desc())
+    }
 }
 
 pub trait MulticastSocket : Socket {
@@ -48,6 +99,11 @@ pub trait MulticastSocket : Socket {
     }
 }
 
+/// Sending and receiving datagrams on sockets that are not connected.
+///
+/// Zero-length datagrams are valid: `send_to` with an empty buffer sends
+/// one, and receiving one returns `Ready` with the sender's address without
+/// advancing the buffer. Neither is treated as "nothing to do" or EOF.
 pub trait UnconnectedSocket {
 
     fn send_to<B: Buf>(&mut self, buf: &mut B, tgt: &SockAddr) -> MioResult<NonBlock<()>>;
@@ -56,7 +112,7 @@ pub trait UnconnectedSocket {
 }
 
 // Types of sockets
-#[derive(Copy)]
+#[derive(Copy, Debug, PartialEq, Eq)]
 pub enum AddressFamily {
     Inet,
     Inet6,
@@ -82,6 +138,39 @@ impl SockAddr {
         }
     }
 
+    /// Returns the IP address and port of an inet address, or `None` for a
+    /// unix address.
+    pub fn as_inet(&self) -> Option<(IpAddr, Port)> {
+        match *self {
+            InetAddr(ip, port) => Some((ip, port)),
+            _ => None
+        }
+    }
+
+    /// Returns the IP address of an inet address, or `None` for a unix
+    /// address.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.as_inet().map(|(ip, _)| ip)
+    }
+
+    /// Returns the port of an inet address, or `None` for a unix address.
+    pub fn port(&self) -> Option<Port> {
+        self.as_inet().map(|(_, port)| port)
+    }
+
+    /// Returns the path of a unix address, or `None` for an inet address.
+    pub fn as_unix(&self) -> Option<&Path> {
+        match *self {
+            UnixAddr(ref path) => Some(path),
+            _ => None
+        }
+    }
+
+    /// Creates an inet address, IPv4 or IPv6 depending on `ip`.
+    pub fn inet(ip: IpAddr, port: Port) -> SockAddr {
+        InetAddr(ip, port)
+    }
+
     pub fn from_path(p: Path) -> SockAddr {
         UnixAddr(p)
     }
@@ -137,20 +226,28 @@ impl fmt::Debug for SockAddr {
 pub enum SocketType {
     Dgram,
     Stream,
+    /// Reliable, ordered, and connection based like `Stream`, but preserving
+    /// message boundaries like `Dgram`.
+    SeqPacket,
 }
 
 /// TCP networking primitives
 ///
 pub mod tcp {
+    use std::{slice, usize};
+    use std::time::Duration;
     use os;
     use error::MioResult;
     use buf::{Buf, MutBuf};
     use io;
     use io::{FromIoDesc, IoHandle, IoAcceptor, IoReader, IoWriter, NonBlock};
     use io::NonBlock::{Ready, WouldBlock};
-    use net::{Socket, SockAddr};
+    use net::{Socket, SockAddr, IpAddr, IPv4Addr, IPv6Addr};
     use net::SocketType::Stream;
     use net::AddressFamily::{self, Inet, Inet6};
+    use os::event::Interest;
+
+    pub use os::TcpInfo;
 
     #[derive(Debug)]
     pub struct TcpSocket {
@@ -182,27 +279,326 @@ pub mod tcp {
 
             // Attempt establishing the context. This may not complete immediately.
             if try!(os::connect(&self.desc, addr)) {
-                // On some OSs, connecting to localhost succeeds immediately. In
-                // this case, queue the writable callback for execution during the
-                // next event loop tick.
+                // On some OSs, connecting to localhost succeeds immediately.
+                // Nothing special is needed in this case: both epoll and
+                // kqueue check the current readiness of a handle when it is
+                // (re)registered, so even an edge-triggered registration made
+                // after the connection completed yields a writable event.
                 debug!("socket connected immediately; addr={:?}", addr);
             }
 
             Ok(())
         }
 
+        /// Binds the socket to `addr` and turns it into a listener. To pick
+        /// the source address of an outgoing connection instead, use
+        /// `bind_local`.
         pub fn bind(self, addr: &SockAddr) -> MioResult<TcpListener> {
             try!(os::bind(&self.desc, addr));
             Ok(TcpListener { desc: self.desc })
         }
 
+        /// Binds the socket to `addr` before it connects, which sets the
+        /// source address and port of the connection rather than letting
+        /// the kernel pick them. Port 0 still has the kernel choose the
+        /// port, but from the given address; `getsockname` returns the
+        /// result.
+        ///
+        /// Unlike `bind`, which is followed by `listen`, the socket stays a
+        /// `TcpSocket` and is connected as usual. Pinning a fixed source
+        /// port usually requires `set_reuseaddr` as well, so that the port
+        /// can be reused while an earlier connection from it is in
+        /// TIME_WAIT.
+        pub fn bind_local(&self, addr: &SockAddr) -> MioResult<()> {
+            os::bind(&self.desc, addr)
+        }
+
         pub fn getpeername(&self) -> MioResult<SockAddr> {
             os::getpeername(&self.desc)
         }
 
+        /// Reads into `header` and spills whatever does not fit into
+        /// `body`, with a single `readv`. Each buffer is advanced by the
+        /// number of bytes that landed in it; the total is returned.
+        ///
+        /// Useful for messages made of a fixed size header followed by a
+        /// body, which can then be read without copying the body out of the
+        /// header buffer.
+        pub fn read_split<H: MutBuf, B: MutBuf>(&self, header: &mut H, body: &mut B) -> MioResult<NonBlock<usize>> {
+            let res = {
+                let hdr = header.mut_bytes();
+                let len = hdr.len();
+
+                os::read_split(&self.desc, hdr, body.mut_bytes()).map(|cnt| (cnt, len))
+            };
+
+            match res {
+                Ok((cnt, len)) => {
+                    if cnt > len {
+                        header.advance(len);
+                        body.advance(cnt - len);
+                    } else {
+                        header.advance(cnt);
+                    }
+
+                    Ok(Ready(cnt))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        /// Reads at most `max` bytes and appends them to `vec`, whose length
+        /// grows by exactly the number of bytes read. The spare capacity is
+        /// filled by the kernel directly, without zeroing it first, and
+        /// never becomes part of the vector unless it was written to.
+        pub fn read_into_vec(&self, vec: &mut Vec<u8>, max: usize) -> MioResult<NonBlock<usize>> {
+            let len = vec.len();
+            vec.reserve(max);
+
+            let res = unsafe {
+                let spare = slice::from_raw_parts_mut(vec.as_mut_ptr().offset(len as isize), max);
+                os::read(&self.desc, spare)
+            };
+
+            match res {
+                Ok(cnt) => {
+                    unsafe { vec.set_len(len + cnt) };
+                    Ok(Ready(cnt))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        /// Connects like `connect`, sending `data` along with the SYN using
+        /// TCP Fast Open, which saves a round trip when the server supports
+        /// it and was connected to before. Returns `Ready` with the number
+        /// of bytes that were queued, which the server receives once it
+        /// accepts. Returns `WouldBlock` when nothing was sent, typically on
+        /// the first connection to a server, as the kernel has no cookie for
+        /// it yet: write the data once the socket becomes writable, as after
+        /// a plain `connect`. Either way, the handler is notified once the
+        /// connection completes.
+        ///
+        /// Only IPv4 addresses are supported. On Linux, client side Fast Open
+        /// is controlled by the `net.ipv4.tcp_fastopen` sysctl. Other
+        /// platforms fall back to a plain connect, and write `data` only if
+        /// the connection completes right away.
+        pub fn connect_with_data(&self, addr: &SockAddr, data: &[u8]) -> MioResult<NonBlock<usize>> {
+            debug!("socket connect with data; addr={:?}", addr);
+
+            match os::connect_fastopen(&self.desc, data, addr) {
+                Ok(Some(cnt)) => Ok(Ready(cnt)),
+                Ok(None) => Ok(WouldBlock),
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        /// Writes `slices` one after the other with a single `writev`, for
+        /// messages assembled from borrowed pieces, such as a cached header
+        /// and a shared payload, without copying them into one buffer first.
+        /// Returns the total number of bytes written, which may end in the
+        /// middle of any slice; the caller tracks the progress and passes
+        /// what is left on the next call. At most 1024 slices (`IOV_MAX`)
+        /// are written per call.
+        pub fn write_slices(&self, slices: &[&[u8]]) -> MioResult<NonBlock<usize>> {
+            match os::writev(&self.desc, slices) {
+                Ok(cnt) => Ok(Ready(cnt)),
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        /// Writes like `write`, but with `MSG_MORE`: the data is held back
+        /// until a write without it, so that a message built up over several
+        /// writes goes out in full segments. The next plain `write` flushes
+        /// everything. Lighter than `TCP_CORK` for a single message. Only
+        /// supported on Linux.
+        pub fn write_more<B: Buf>(&self, buf: &mut B) -> MioResult<NonBlock<usize>> {
+            match os::send_more(&self.desc, buf.bytes()) {
+                Ok(cnt) => {
+                    buf.advance(cnt);
+                    Ok(Ready(cnt))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        /// Reads incoming data into `buf` without consuming it: the next
+        /// read returns the same bytes. Useful to sniff a protocol from the
+        /// first bytes of a connection before handing it off.
+        pub fn peek<B: MutBuf>(&self, buf: &mut B) -> MioResult<NonBlock<usize>> {
+            match os::peek(&self.desc, buf.mut_bytes()) {
+                Ok(cnt) => {
+                    buf.advance(cnt);
+                    Ok(Ready(cnt))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        /// Reads the byte of urgent (out-of-band) data, as used by telnet
+        /// and rlogin. Returns `None` if no urgent data is pending.
+        ///
+        /// On Linux, the handle's registration must include
+        /// `Interest::priority()` for the arrival of urgent data to be
+        /// reported, with `ReadHint::oob()` set. Urgent data is not
+        /// available here once `set_oob_inline` has been enabled.
+        pub fn recv_urgent(&self) -> MioResult<Option<u8>> {
+            os::recv_urgent(&self.desc)
+        }
+
+        /// When enabled, urgent data is received in line with the regular
+        /// data instead of through `recv_urgent`.
+        pub fn set_oob_inline(&self, val: bool) -> MioResult<()> {
+            os::set_oob_inline(&self.desc, val)
+        }
+
+        /// Returns the number of bytes that are waiting to be read, which
+        /// is useful to size a read buffer up front.
+        ///
+        /// The value is only a snapshot: more data may have arrived by the
+        /// time the socket is read.
+        pub fn bytes_available(&self) -> MioResult<usize> {
+            os::bytes_available(&self.desc)
+        }
+
         pub fn getsockname(&self) -> MioResult<SockAddr> {
             os::getsockname(&self.desc)
         }
+
+        /// Asks the kernel to hand connections that arrive on CPU `cpu` to
+        /// this socket, among the listeners sharing a port through
+        /// `SO_REUSEPORT`. With one event loop pinned to each CPU and a
+        /// listener per loop, each connection is then accepted and served
+        /// on the CPU that received its packets, which is better for cache
+        /// locality. Set it on each listener, with the number of the CPU its
+        /// loop runs on.
+        ///
+        /// Wraps `SO_INCOMING_CPU`, which requires Linux 4.4 for listeners.
+        /// Connections arriving on a CPU no listener claims are spread over
+        /// the group as usual. Other platforms return an `Unsupported`
+        /// error.
+        pub fn set_incoming_cpu(&self, cpu: i32) -> MioResult<()> {
+            os::set_incoming_cpu(&self.desc, cpu)
+        }
+
+        /// Returns the CPU set with `set_incoming_cpu`, or, for a connected
+        /// socket, the CPU its packets were last processed on. -1 means
+        /// none.
+        pub fn incoming_cpu(&self) -> MioResult<i32> {
+            os::incoming_cpu(&self.desc)
+        }
+
+        /// Returns the kernel's statistics for the connection: round trip
+        /// time, retransmissions and congestion window. Cheap enough to
+        /// poll periodically, e.g. to notice a degraded link and slow down
+        /// sending to it. Only supported on Linux; other platforms return
+        /// an `Unsupported` error.
+        pub fn tcp_info(&self) -> MioResult<TcpInfo> {
+            os::get_tcp_info(&self.desc)
+        }
+
+        /// Returns the error pending on the socket as an `Err`, and clears
+        /// it. Once a non-blocking `connect` reports the socket writable,
+        /// this tells whether the connection was established or why it
+        /// failed, e.g. because it was refused.
+        pub fn take_socket_error(&self) -> MioResult<()> {
+            os::take_socket_error(&self.desc)
+        }
+
+        /// Returns true if a write would not block right now, i.e. the
+        /// socket has room in its send buffer.
+        ///
+        /// This is a live probe, a `poll` with a zero timeout, not a flag
+        /// cached from the last event: it is accurate at the time of the
+        /// call, at the cost of a system call. A handler with queued data
+        /// can use it to keep writing instead of reregistering for writable
+        /// events and waiting for the next tick. Errors, including a
+        /// failed connect, are reported as not writable; the next write
+        /// returns them.
+        pub fn is_writable(&self) -> bool {
+            match os::readiness(&self.desc, Interest::writable()) {
+                Ok(ready) => ready.is_writable() && !ready.is_error(),
+                Err(_) => false
+            }
+        }
+
+        /// Sets a timeout for blocking reads, after which they fail with
+        /// `WouldBlock` instead of hanging the thread, or clears it with
+        /// `None`. Wraps `SO_RCVTIMEO`.
+        ///
+        /// Sockets created by mio are non-blocking and never wait, so the
+        /// timeout has no effect on them. It is a safety net for sockets
+        /// taken over from other code that may have left them in blocking
+        /// mode, see `set_nonblocking`.
+        pub fn set_read_timeout(&self, dur: Option<Duration>) -> MioResult<()> {
+            os::set_read_timeout(&self.desc, dur)
+        }
+
+        /// Returns the timeout for blocking reads, if any. The kernel may
+        /// round the value set with `set_read_timeout` to its clock
+        /// resolution.
+        pub fn read_timeout(&self) -> MioResult<Option<Duration>> {
+            os::read_timeout(&self.desc)
+        }
+
+        /// Sets a timeout for blocking writes, or clears it with `None`.
+        /// Wraps `SO_SNDTIMEO`, see `set_read_timeout`.
+        pub fn set_write_timeout(&self, dur: Option<Duration>) -> MioResult<()> {
+            os::set_write_timeout(&self.desc, dur)
+        }
+
+        /// Returns the timeout for blocking writes, if any.
+        pub fn write_timeout(&self) -> MioResult<Option<Duration>> {
+            os::write_timeout(&self.desc)
+        }
+
+        /// Starts closing the connection cleanly by shutting down its
+        /// writing half: the peer receives everything written so far,
+        /// followed by EOF. Flush any buffered data before calling this.
+        ///
+        /// The socket can still be read from, and should be until the peer
+        /// closes its end too. Hand it to a
+        /// [DrainClose](../../util/struct.DrainClose.html) to take care of
+        /// that.
+        pub fn begin_close(&self) -> MioResult<()> {
+            os::shutdown_write(&self.desc)
+        }
     }
 
     impl IoHandle for TcpSocket {
@@ -248,7 +644,7 @@ pub mod tcp {
     impl TcpListener {
         pub fn listen(self, backlog: usize) -> MioResult<TcpAcceptor> {
             try!(os::listen(self.desc(), backlog));
-            Ok(TcpAcceptor { desc: self.desc })
+            Ok(TcpAcceptor::from_desc(self.desc))
         }
     }
 
@@ -264,9 +660,31 @@ pub mod tcp {
         }
     }
 
+    /// Accepts incoming connections.
+    ///
+    /// A spare file descriptor is held for when the process runs out of
+    /// them. Instead of leaving the connection pending, which keeps the
+    /// acceptor readable and spins the event loop, `accept` then uses the
+    /// spare to accept the connection and close it right away, and returns an
+    /// error for which `is_resource_limit()` is true.
+    ///
+    /// The spare is a descriptor open on `/dev/null`, taken when the
+    /// acceptor is created and held until it is dropped, so every acceptor
+    /// uses two descriptors of the process limit. If none is left when the
+    /// acceptor is created, or the spare cannot be taken back after shedding
+    /// a connection, the acceptor goes without and pending connections stay
+    /// queued until descriptors are freed.
+    ///
+    /// Connections that were reset while waiting to be accepted are skipped,
+    /// so a readable event that only covered such a connection, which is
+    /// common with edge triggering, results in `WouldBlock` rather than an
+    /// error.
     #[derive(Debug)]
     pub struct TcpAcceptor {
         desc: os::IoDesc,
+        spare: Option<os::IoDesc>,
+        // Prefixes connections have to come from, if restricted
+        allowed: Option<Vec<(IpAddr, u8)>>,
     }
 
     impl TcpAcceptor {
@@ -275,6 +693,191 @@ pub mod tcp {
             let listener = try!(sock.bind(addr));
             listener.listen(backlog)
         }
+
+        /// Adopts a socket that is already bound and listening, such as one
+        /// passed in by systemd socket activation (`LISTEN_FDS`, starting at
+        /// fd 3). The socket is made non-blocking and can then be registered
+        /// and accepted from like any other acceptor. mio never calls bind
+        /// or listen on it, so the address and backlog are whatever the
+        /// creator of the socket chose.
+        ///
+        /// The acceptor takes ownership of `fd` and closes it when dropped,
+        /// or right away if this fails.
+        pub fn from_raw_fd(fd: i32) -> MioResult<TcpAcceptor> {
+            let desc = os::IoDesc { fd: fd };
+            try!(os::set_nonblock(&desc, true));
+            Ok(FromIoDesc::from_desc(desc))
+        }
+
+        /// Returns the number of connections that are waiting to be
+        /// accepted, which helps tuning the backlog passed to `listen`.
+        ///
+        /// This is a best effort, platform specific metric: it is read from
+        /// TCP_INFO on linux, and fails with `Unsupported` elsewhere. A
+        /// server that wants to throttle accepts can compare it against its
+        /// backlog and reregister the acceptor without readable interest
+        /// until it catches up.
+        pub fn backlog_len(&self) -> MioResult<usize> {
+            os::backlog_len(&self.desc)
+        }
+
+        /// Changes the backlog passed to `listen`, by calling it again,
+        /// which Linux and the BSDs allow on a listening socket. Widening
+        /// the backlog during a traffic spike lets more connections wait to
+        /// be accepted instead of having their SYN dropped. Shrinking it
+        /// does not drop connections already queued, it only stops new ones
+        /// from queuing until the queue is back under the new limit.
+        ///
+        /// The kernel caps the backlog, at `net.core.somaxconn` on Linux.
+        pub fn set_backlog(&self, backlog: usize) -> MioResult<()> {
+            os::listen(&self.desc, backlog)
+        }
+
+        /// Keeps connections whose client has not sent anything yet out of
+        /// the accept queue, so the acceptor only becomes readable once
+        /// there is a request to serve. Passing 0 turns it off.
+        ///
+        /// On linux this is `TCP_DEFER_ACCEPT`: a connection that stays
+        /// silent for about `secs` seconds is dropped, or, depending on the
+        /// kernel version, handed to accept anyway, so the usual idle
+        /// timeouts are still needed. Other platforms, including OS X,
+        /// return an `Unsupported` error.
+        ///
+        /// Only useful for protocols where the client speaks first.
+        pub fn set_defer_accept(&self, secs: u32) -> MioResult<()> {
+            os::set_defer_accept(&self.desc, secs)
+        }
+
+        /// Accepts TCP Fast Open connections, whose first request arrives
+        /// with the SYN and is readable as soon as the connection is
+        /// accepted. `qlen` caps the connections accepted that way whose
+        /// handshake has not completed yet, which limits the damage of a
+        /// SYN flood carrying forged cookies.
+        ///
+        /// Linux only, where the server side also has to be allowed by the
+        /// `net.ipv4.tcp_fastopen` sysctl. Other platforms return an
+        /// `Unsupported` error.
+        pub fn set_fastopen(&self, qlen: u32) -> MioResult<()> {
+            os::set_fastopen(&self.desc, qlen)
+        }
+
+        /// Accepts up to `max` pending connections in one call, along with
+        /// the address of each peer. Stops early, without an error, once no
+        /// more connections are pending, so the returned vector is empty if
+        /// the call would have blocked. Connections whose peer is already
+        /// gone are closed and skipped.
+        ///
+        /// With edge triggered registrations a single readable event can
+        /// cover many pending connections; capping the batch prevents one
+        /// acceptor from monopolizing a tick.
+        ///
+        /// An error is only returned for a call that accepted nothing. When
+        /// accepting fails after some connections were accepted, those are
+        /// returned, and the handler should call again right away, as no new
+        /// event is guaranteed for the connections still pending.
+        pub fn accept_many(&mut self, max: usize) -> MioResult<Vec<(TcpSocket, SockAddr)>> {
+            let mut conns = Vec::new();
+
+            while conns.len() < max {
+                match self.accept_peer() {
+                    Ok(Ready(conn)) => conns.push(conn),
+                    Ok(WouldBlock) => break,
+                    Err(e) => {
+                        if conns.is_empty() {
+                            return Err(e);
+                        }
+
+                        debug!("accept interrupted; accepted={}; err={:?}", conns.len(), e);
+                        break;
+                    }
+                }
+            }
+
+            Ok(conns)
+        }
+
+        /// Only accepts connections whose peer address is within one of
+        /// `prefixes`, each an address and a prefix length in bits. Any
+        /// other connection is closed as soon as it is accepted and skipped,
+        /// so the handler never sees it. An empty list rejects everything.
+        ///
+        /// IPv4 and IPv6 prefixes can be mixed, an address only matches
+        /// prefixes of its own family. IPv4-mapped IPv6 addresses
+        /// (`::ffff:a.b.c.d`), which a dual-stack acceptor sees for IPv4
+        /// peers, count as the IPv4 address they map, and so do mapped
+        /// prefixes, whose length then covers the mapped bits only. Prefix
+        /// lengths larger than the address are treated as the full length.
+        pub fn set_allowed_sources(&mut self, prefixes: Vec<(IpAddr, u8)>) {
+            self.allowed = Some(prefixes);
+        }
+
+        /// Accepts connections from any source again.
+        pub fn clear_allowed_sources(&mut self) {
+            self.allowed = None;
+        }
+
+        // Accepts the next connection along with its peer address, which is
+        // looked up once and checked against the allowed sources. Connections
+        // whose peer is already gone are skipped like rejected ones.
+        fn accept_peer(&mut self) -> MioResult<NonBlock<(TcpSocket, SockAddr)>> {
+            loop {
+                let sock = match try!(self.accept_any()) {
+                    Ready(sock) => sock,
+                    WouldBlock => return Ok(WouldBlock)
+                };
+
+                // Dropping the socket closes the connection
+                match sock.getpeername() {
+                    Ok(addr) => {
+                        if self.is_allowed(&addr) {
+                            return Ok(Ready((sock, addr)));
+                        }
+
+                        debug!("rejecting connection; peer={:?}", addr);
+                    }
+                    Err(e) => debug!("dropping accepted connection; err={:?}", e)
+                }
+            }
+        }
+
+        // Accepts the next connection, whatever its source
+        fn accept_any(&mut self) -> MioResult<NonBlock<TcpSocket>> {
+            match os::accept_or_shed(&self.desc, &mut self.spare) {
+                Ok(sock) => Ok(Ready(TcpSocket { desc: sock })),
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        // Returns false if a connection from `addr` has to be rejected
+        fn is_allowed(&self, addr: &SockAddr) -> bool {
+            let prefixes = match self.allowed {
+                Some(ref prefixes) => prefixes,
+                None => return true
+            };
+
+            let ip = match addr.ip() {
+                Some(ip) => ip,
+                None => return false
+            };
+
+            prefixes.iter().any(|&(prefix, len)| prefix_matches(ip, prefix, len))
+        }
+
+        /// Accepts every pending connection, until accepting would block,
+        /// which is what a handler registered with edge triggering has to do
+        /// on each readable event. This is `accept_many` without a cap, so
+        /// aborted connections and ones whose peer is already gone are
+        /// skipped the same way, and errors are reported the same way.
+        pub fn accept_loop(&mut self) -> MioResult<Vec<TcpSocket>> {
+            let conns = try!(self.accept_many(usize::MAX));
+            Ok(conns.into_iter().map(|(sock, _)| sock).collect())
+        }
     }
 
     impl IoHandle for TcpAcceptor {
@@ -285,7 +888,11 @@ pub mod tcp {
 
     impl FromIoDesc for TcpAcceptor {
         fn from_desc(desc: os::IoDesc) -> Self {
-            TcpAcceptor { desc: desc }
+            TcpAcceptor {
+                desc: desc,
+                spare: os::reserve_fd().ok(),
+                allowed: None,
+            }
         }
     }
 
@@ -296,18 +903,123 @@ pub mod tcp {
         type Output = TcpSocket;
 
         fn accept(&mut self) -> MioResult<NonBlock<TcpSocket>> {
-            match os::accept(self.desc()) {
-                Ok(sock) => Ok(Ready(TcpSocket { desc: sock })),
-                Err(e) => {
-                    if e.is_would_block() {
-                        return Ok(WouldBlock);
-                    }
+            // Without a filter there is no need to know the peer
+            if self.allowed.is_none() {
+                return self.accept_any();
+            }
 
-                    return Err(e);
+            match try!(self.accept_peer()) {
+                Ready((sock, _)) => Ok(Ready(sock)),
+                WouldBlock => Ok(WouldBlock)
+            }
+        }
+    }
+
+    // Returns true if the first `len` bits of `ip` and `prefix` are equal
+    fn prefix_matches(ip: IpAddr, prefix: IpAddr, len: u8) -> bool {
+        let ip = unmap(ip);
+
+        // The length of a mapped prefix counts the 96 bits of the mapping
+        let (prefix, len) = match (prefix, unmap(prefix)) {
+            (IPv6Addr(..), v4 @ IPv4Addr(..)) => (v4, if len > 96 { len - 96 } else { 0 }),
+            _ => (prefix, len)
+        };
+
+        match (ip, prefix) {
+            (IPv4Addr(..), IPv4Addr(..)) | (IPv6Addr(..), IPv6Addr(..)) => {}
+            _ => return false
+        }
+
+        let (ip, n) = octets(ip);
+        let (prefix, _) = octets(prefix);
+        let mut bits = len as usize;
+
+        for (x, y) in ip[..n].iter().zip(prefix[..n].iter()) {
+            if bits == 0 {
+                break;
+            }
+
+            let mask = if bits >= 8 { 0xffu8 } else { !(0xffu8 >> bits) };
+
+            if x & mask != y & mask {
+                return false;
+            }
+
+            bits -= if bits >= 8 { 8 } else { bits };
+        }
+
+        true
+    }
+
+    // Turns an IPv4-mapped IPv6 address into the IPv4 address it maps
+    fn unmap(ip: IpAddr) -> IpAddr {
+        match ip {
+            IPv6Addr(0, 0, 0, 0, 0, 0xffff, g, h) => {
+                IPv4Addr((g >> 8) as u8, g as u8, (h >> 8) as u8, h as u8)
+            }
+            ip => ip
+        }
+    }
+
+    // Network byte order, along with the number of octets used
+    fn octets(ip: IpAddr) -> ([u8; 16], usize) {
+        let mut octets = [0u8; 16];
+
+        match ip {
+            IPv4Addr(a, b, c, d) => {
+                octets[0] = a;
+                octets[1] = b;
+                octets[2] = c;
+                octets[3] = d;
+
+                (octets, 4)
+            }
+            IPv6Addr(a, b, c, d, e, f, g, h) => {
+                for (i, seg) in [a, b, c, d, e, f, g, h].iter().enumerate() {
+                    octets[2 * i] = (*seg >> 8) as u8;
+                    octets[2 * i + 1] = *seg as u8;
                 }
+
+                (octets, 16)
             }
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use net::{IPv4Addr, IPv6Addr};
+        use super::prefix_matches;
+
+        #[test]
+        fn test_prefix_matches() {
+            let net = IPv4Addr(10, 1, 0, 0);
+
+            assert!(prefix_matches(IPv4Addr(10, 1, 200, 3), net, 16));
+            assert!(!prefix_matches(IPv4Addr(10, 2, 0, 1), net, 16));
+            assert!(prefix_matches(IPv4Addr(10, 1, 0, 1), net, 15));
+            assert!(prefix_matches(IPv4Addr(10, 3, 0, 1), net, 14));
+            assert!(!prefix_matches(IPv4Addr(10, 1, 0, 1), net, 32));
+            assert!(prefix_matches(IPv4Addr(10, 1, 0, 0), net, 40));
+            assert!(prefix_matches(IPv4Addr(192, 168, 0, 1), net, 0));
+
+            let net = IPv6Addr(0xfd00, 0, 0, 0, 0, 0, 0, 0);
+
+            assert!(prefix_matches(IPv6Addr(0xfd12, 0, 0, 0, 0, 0, 0, 1), net, 8));
+            assert!(!prefix_matches(IPv6Addr(0xfe80, 0, 0, 0, 0, 0, 0, 1), net, 8));
+
+            // Families never match each other
+            assert!(!prefix_matches(IPv4Addr(10, 1, 0, 1), IPv6Addr(0, 0, 0, 0, 0, 0, 0, 0), 0));
+
+            // Mapped addresses match as the IPv4 address they map
+            let mapped = IPv6Addr(0, 0, 0, 0, 0, 0xffff, 0x0a01, 0x0203);
+
+            assert!(prefix_matches(mapped, IPv4Addr(10, 1, 0, 0), 16));
+            assert!(!prefix_matches(mapped, IPv4Addr(10, 2, 0, 0), 16));
+            assert!(prefix_matches(IPv4Addr(10, 1, 2, 3), IPv6Addr(0, 0, 0, 0, 0, 0xffff, 0x0a01, 0), 112));
+            assert!(!prefix_matches(IPv4Addr(10, 2, 2, 3), IPv6Addr(0, 0, 0, 0, 0, 0xffff, 0x0a01, 0), 112));
+            assert!(!prefix_matches(mapped, IPv6Addr(0xfd00, 0, 0, 0, 0, 0, 0, 0), 8));
+        }
+    }
 }
 
 pub mod udp {
@@ -350,6 +1062,119 @@ pub mod udp {
             try!(sock.bind(addr));
             Ok(sock)
         }
+
+        /// Asks the kernel to report the local address every datagram was
+        /// sent to, for `recv_from_pktinfo`. Only supported on Linux.
+        pub fn set_recv_pktinfo(&self, val: bool) -> MioResult<()> {
+            os::set_recv_pktinfo(&self.desc, val)
+        }
+
+        /// Receives a datagram and returns its length, the address it came
+        /// from and the local address it was sent to. For a socket bound to
+        /// the wildcard address, this tells which of the host's addresses
+        /// the peer used, so that the reply can be sent from it with
+        /// `send_from`.
+        ///
+        /// The local address is only known with `set_recv_pktinfo` enabled,
+        /// otherwise the address the socket is bound to is returned. Only
+        /// IPv4 sockets on Linux are supported.
+        pub fn recv_from_pktinfo<B: MutBuf>(&self, buf: &mut B) -> MioResult<NonBlock<(usize, SockAddr, SockAddr)>> {
+            match os::recv_from_pktinfo(&self.desc, buf.mut_bytes()) {
+                Ok((cnt, src, dst)) => {
+                    buf.advance(cnt);
+                    Ok(Ready((cnt, src, dst)))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        Ok(WouldBlock)
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        }
+
+        /// Sends like `send_to`, but with `MSG_MORE`: the data is appended to
+        /// a datagram that is only sent by the next `send_to` to the same
+        /// target, which completes it. Only IPv4 sockets on Linux are
+        /// supported.
+        pub fn send_to_more<B: Buf>(&self, buf: &mut B, tgt: &SockAddr) -> MioResult<NonBlock<()>> {
+            match os::sendto_more(&self.desc, buf.bytes(), tgt) {
+                Ok(cnt) => {
+                    buf.advance(cnt);
+                    Ok(Ready(()))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        Ok(WouldBlock)
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        }
+
+        /// Sends a batch of datagrams, each to its own target, in a single
+        /// system call, which cuts the per datagram overhead of high rate
+        /// senders. Returns the number of datagrams sent: if the send buffer
+        /// fills up part way, the rest are not sent and can be retried once
+        /// the socket is writable. `WouldBlock` means none were sent.
+        ///
+        /// Uses sendmmsg on Linux, where only IPv4 targets are supported.
+        /// Other platforms fall back to one `send_to` per datagram.
+        pub fn send_mmsg(&self, packets: &[(&[u8], SockAddr)]) -> MioResult<NonBlock<usize>> {
+            match os::send_mmsg(&self.desc, packets) {
+                Ok(cnt) => Ok(Ready(cnt)),
+                Err(e) => {
+                    if e.is_would_block() {
+                        Ok(WouldBlock)
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        }
+
+        /// Receives up to one datagram into each buffer of `bufs` in a
+        /// single system call, and returns the length and source address of
+        /// each datagram received, in order. Fewer datagrams than buffers
+        /// are returned when no more are queued, and `WouldBlock` when none
+        /// are. Like `recv_from`, a datagram longer than its buffer is
+        /// truncated.
+        ///
+        /// Uses recvmmsg on Linux, for IPv4 sockets. Other platforms fall
+        /// back to one `recv_from` per datagram.
+        pub fn recv_mmsg(&self, bufs: &mut [&mut [u8]]) -> MioResult<NonBlock<Vec<(usize, SockAddr)>>> {
+            match os::recv_mmsg(&self.desc, bufs) {
+                Ok(received) => Ok(Ready(received)),
+                Err(e) => {
+                    if e.is_would_block() {
+                        Ok(WouldBlock)
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        }
+
+        /// Sends a datagram to `tgt` from the local address `local`, usually
+        /// the destination returned by `recv_from_pktinfo`. The port of
+        /// `local` is ignored. Only IPv4 sockets on Linux are supported.
+        pub fn send_from<B: Buf>(&self, buf: &mut B, tgt: &SockAddr, local: &SockAddr) -> MioResult<NonBlock<()>> {
+            match os::send_from(&self.desc, buf.bytes(), tgt, local) {
+                Ok(cnt) => {
+                    buf.advance(cnt);
+                    Ok(Ready(()))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        Ok(WouldBlock)
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
+        }
     }
 
     impl IoHandle for UdpSocket {
@@ -370,13 +1195,29 @@ pub mod udp {
     impl MulticastSocket for UdpSocket {
     }
 
+    // Empty datagrams are valid, so a read of zero bytes is not EOF
     impl IoReader for UdpSocket {
         fn read<B: MutBuf>(&self, buf: &mut B) -> MioResult<NonBlock<(usize)>> {
-            io::read(self, buf)
+            let res = self.read_slice(buf.mut_bytes());
+
+            if let Ok(Ready(cnt)) = res {
+                buf.advance(cnt);
+            }
+
+            res
         }
 
         fn read_slice(&self, buf: &mut[u8]) -> MioResult<NonBlock<usize>> {
-            io::read_slice(self, buf)
+            match os::read_datagram(&self.desc, buf) {
+                Ok(cnt) => Ok(Ready(cnt)),
+                Err(e) => {
+                    if e.is_would_block() {
+                        Ok(WouldBlock)
+                    } else {
+                        Err(e)
+                    }
+                }
+            }
         }
     }
 
@@ -426,8 +1267,207 @@ pub mod udp {
     }
 }
 
+/// SCTP networking primitives
+///
+/// SCTP is message oriented: each `send` is received by a single `recv`, on
+/// one of several independent streams, so that a message lost on one stream
+/// does not hold up the others. Only IPv4 on Linux is supported, where the
+/// `sctp` kernel module has to be available. Elsewhere, or without the
+/// module, creating a socket fails with an `Unsupported` error.
+pub mod sctp {
+    use os;
+    use error::MioResult;
+    use io::{FromIoDesc, IoHandle, IoAcceptor, NonBlock};
+    use io::NonBlock::{Ready, WouldBlock};
+    use net::{Socket, SockAddr};
+
+    /// A received message, or part of one.
+    #[derive(Debug)]
+    pub struct SctpRecv {
+        /// Number of bytes received.
+        pub len: usize,
+        /// The stream the message was sent on.
+        pub stream: u16,
+        /// The peer that sent the message.
+        pub addr: SockAddr,
+        /// False if the message did not fit into the buffer, in which case
+        /// the rest of it is returned by the next calls to `recv`.
+        pub complete: bool,
+    }
+
+    /// An SCTP socket, in one of two styles.
+    ///
+    /// A one-to-one socket carries a single association and is used like a
+    /// TCP socket: it connects to a peer, or is accepted from an
+    /// `SctpAcceptor` for an incoming association.
+    ///
+    /// A one-to-many socket carries associations with any number of peers.
+    /// It binds and listens to take part in associations that peers set
+    /// up, and sets up its own by sending to a new peer with `send_to`.
+    /// `recv` tells which peer every message came from.
+    ///
+    /// Either style is registered with the event loop like any other
+    /// socket, and reports readable when a message is waiting.
+    #[derive(Debug)]
+    pub struct SctpSocket {
+        desc: os::IoDesc
+    }
+
+    impl SctpSocket {
+        pub fn one_to_one() -> MioResult<SctpSocket> {
+            Ok(SctpSocket { desc: try!(os::sctp_socket(false)) })
+        }
+
+        pub fn one_to_many() -> MioResult<SctpSocket> {
+            Ok(SctpSocket { desc: try!(os::sctp_socket(true)) })
+        }
+
+        pub fn bind(&self, addr: &SockAddr) -> MioResult<()> {
+            os::bind(&self.desc, addr)
+        }
+
+        /// Lets peers set up associations with a one-to-many socket. A
+        /// one-to-one socket accepts associations through an `SctpAcceptor`
+        /// instead.
+        pub fn listen(&self, backlog: usize) -> MioResult<()> {
+            os::listen(&self.desc, backlog)
+        }
+
+        /// Sets up an association with `addr`. Like `TcpSocket::connect`,
+        /// the handler is notified once it is established.
+        pub fn connect(&self, addr: &SockAddr) -> MioResult<()> {
+            debug!("socket connect; addr={:?}", addr);
+
+            try!(os::connect(&self.desc, addr));
+            Ok(())
+        }
+
+        pub fn getsockname(&self) -> MioResult<SockAddr> {
+            os::getsockname(&self.desc)
+        }
+
+        /// Sends `buf` as a single message on `stream`, over the
+        /// association of a one-to-one socket.
+        pub fn send(&self, buf: &[u8], stream: u16) -> MioResult<NonBlock<usize>> {
+            self.send_msg(buf, stream, None)
+        }
+
+        /// Sends `buf` as a single message on `stream` to `addr`, over the
+        /// association of a one-to-many socket with that peer, which is set
+        /// up first if needed.
+        pub fn send_to(&self, buf: &[u8], stream: u16, addr: &SockAddr) -> MioResult<NonBlock<usize>> {
+            self.send_msg(buf, stream, Some(addr))
+        }
+
+        /// Receives the next message into `buf`. A message larger than
+        /// `buf` is returned over several calls, the last of which reports
+        /// it `complete`.
+        pub fn recv(&self, buf: &mut [u8]) -> MioResult<NonBlock<SctpRecv>> {
+            match os::sctp_recv(&self.desc, buf) {
+                Ok((len, stream, addr, complete)) => {
+                    Ok(Ready(SctpRecv {
+                        len: len,
+                        stream: stream,
+                        addr: addr,
+                        complete: complete,
+                    }))
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+
+        fn send_msg(&self, buf: &[u8], stream: u16, addr: Option<&SockAddr>) -> MioResult<NonBlock<usize>> {
+            match os::sctp_send(&self.desc, buf, stream, addr) {
+                Ok(cnt) => Ok(Ready(cnt)),
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+    }
+
+    impl IoHandle for SctpSocket {
+        fn desc(&self) -> &os::IoDesc {
+            &self.desc
+        }
+    }
+
+    impl FromIoDesc for SctpSocket {
+        fn from_desc(desc: os::IoDesc) -> Self {
+            SctpSocket { desc: desc }
+        }
+    }
+
+    impl Socket for SctpSocket {
+    }
+
+    /// A listening one-to-one socket, which accepts every incoming
+    /// association as a one-to-one `SctpSocket` of its own.
+    #[derive(Debug)]
+    pub struct SctpAcceptor {
+        desc: os::IoDesc
+    }
+
+    impl SctpAcceptor {
+        pub fn new(addr: &SockAddr, backlog: usize) -> MioResult<SctpAcceptor> {
+            let sock = try!(SctpSocket::one_to_one());
+            try!(sock.bind(addr));
+            try!(sock.listen(backlog));
+
+            Ok(SctpAcceptor { desc: sock.desc })
+        }
+
+        pub fn getsockname(&self) -> MioResult<SockAddr> {
+            os::getsockname(&self.desc)
+        }
+    }
+
+    impl IoHandle for SctpAcceptor {
+        fn desc(&self) -> &os::IoDesc {
+            &self.desc
+        }
+    }
+
+    impl FromIoDesc for SctpAcceptor {
+        fn from_desc(desc: os::IoDesc) -> Self {
+            SctpAcceptor { desc: desc }
+        }
+    }
+
+    impl Socket for SctpAcceptor {
+    }
+
+    impl IoAcceptor for SctpAcceptor {
+        type Output = SctpSocket;
+
+        fn accept(&mut self) -> MioResult<NonBlock<SctpSocket>> {
+            match os::accept(&self.desc) {
+                Ok(sock) => Ok(Ready(SctpSocket { desc: sock })),
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(WouldBlock);
+                    }
+
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
 /// Named pipes
 pub mod pipe {
+    use std::old_io::fs;
     use os;
     use error::MioResult;
     use buf::{Buf, MutBuf};
@@ -435,7 +1475,7 @@ pub mod pipe {
     use io::{FromIoDesc, IoHandle, IoAcceptor, IoReader, IoWriter, NonBlock};
     use io::NonBlock::{Ready, WouldBlock};
     use net::{Socket, SockAddr, SocketType};
-    use net::SocketType::Stream;
+    use net::SocketType::{SeqPacket, Stream};
     use net::AddressFamily::Unix;
 
     #[derive(Debug)]
@@ -448,6 +1488,17 @@ pub mod pipe {
             UnixSocket::new(Stream)
         }
 
+        /// Creates a `SOCK_SEQPACKET` socket. Connected sequenced-packet
+        /// sockets deliver messages reliably and in order, like streams, but
+        /// keep them apart: every read returns exactly one message, so no
+        /// framing is needed on top. A message that does not fit in the read
+        /// buffer is truncated.
+        ///
+        /// Sequenced-packet unix sockets are not available on OS X.
+        pub fn seqpacket() -> MioResult<UnixSocket> {
+            UnixSocket::new(SeqPacket)
+        }
+
         fn new(socket_type: SocketType) -> MioResult<UnixSocket> {
             Ok(UnixSocket { desc: try!(os::socket(Unix, socket_type)) })
         }
@@ -468,7 +1519,62 @@ pub mod pipe {
 
         pub fn bind(self, addr: &SockAddr) -> MioResult<UnixListener> {
             try!(os::bind(&self.desc, addr));
-            Ok(UnixListener { desc: self.desc })
+            Ok(UnixListener { desc: self.desc, path: None })
+        }
+
+        /// Binds the socket like `bind`, but first removes a socket file left
+        /// behind by a server that exited without cleaning up.
+        ///
+        /// The path is only unlinked when it is a unix socket and connecting
+        /// to it is refused. If a live server is listening on the path, or the
+        /// path is not a socket, the `AddrInUse` error from the first bind
+        /// attempt is returned and the file is left in place.
+        pub fn bind_clean(self, addr: &SockAddr) -> MioResult<UnixListener> {
+            let err = match os::bind(&self.desc, addr) {
+                Ok(()) => return Ok(UnixListener { desc: self.desc, path: None }),
+                Err(e) => e
+            };
+
+            if !err.is_addr_in_use() || !try!(os::is_stale_unix_socket(addr)) {
+                return Err(err);
+            }
+
+            if let SockAddr::UnixAddr(ref path) = *addr {
+                debug!("removing stale unix socket; path={}", path.display());
+
+                if fs::unlink(path).is_err() {
+                    return Err(err);
+                }
+            }
+
+            self.bind(addr)
+        }
+
+        /// Binds the socket like `bind`, and arranges for the socket file to
+        /// be removed when the resulting listener, or the acceptor it turns
+        /// into, is dropped.
+        ///
+        /// The inode of the file is recorded at bind time. On drop, the path
+        /// is only unlinked if it still refers to that same socket, so a file
+        /// that was replaced in the meantime, for instance by another server
+        /// binding the same path, is left alone.
+        pub fn bind_with_cleanup(self, addr: &SockAddr) -> MioResult<UnixListener> {
+            let mut listener = try!(self.bind(addr));
+            listener.path = BoundPath::new(addr);
+            Ok(listener)
+        }
+
+        /// Returns the credentials of the process on the other end of the
+        /// connection as `(uid, gid, pid)`. The credentials are the ones the
+        /// peer had when the connection was established, which makes them
+        /// suitable for authorizing a client right after
+        /// `UnixAcceptor::accept`.
+        ///
+        /// Linux reads them with `SO_PEERCRED` and reports all three values.
+        /// BSD and OS X use `getpeereid`, which has no pid, so the pid is
+        /// always 0 there.
+        pub fn peer_cred(&self) -> MioResult<(u32, u32, u32)> {
+            os::peer_cred(&self.desc)
         }
     }
 
@@ -507,15 +1613,57 @@ pub mod pipe {
     impl Socket for UnixSocket {
     }
 
+    /// The path a socket was bound to with `bind_with_cleanup`, along with
+    /// the identity of the socket file. The file is unlinked when this is
+    /// dropped, provided the path still refers to the same inode.
+    #[derive(Debug)]
+    struct BoundPath {
+        path: Path,
+        device: u64,
+        inode: u64,
+    }
+
+    impl BoundPath {
+        fn new(addr: &SockAddr) -> Option<BoundPath> {
+            let path = match *addr {
+                SockAddr::UnixAddr(ref path) => path,
+                _ => return None
+            };
+
+            fs::lstat(path).ok().map(|stat| {
+                BoundPath {
+                    path: path.clone(),
+                    device: stat.unstable.device,
+                    inode: stat.unstable.inode,
+                }
+            })
+        }
+    }
+
+    impl Drop for BoundPath {
+        fn drop(&mut self) {
+            if let Ok(stat) = fs::lstat(&self.path) {
+                if stat.unstable.device == self.device && stat.unstable.inode == self.inode {
+                    debug!("removing unix socket; path={}", self.path.display());
+                    let _ = fs::unlink(&self.path);
+                }
+            }
+        }
+    }
+
     #[derive(Debug)]
     pub struct UnixListener {
         desc: os::IoDesc,
+        path: Option<BoundPath>,
     }
 
     impl UnixListener {
         pub fn listen(self, backlog: usize) -> MioResult<UnixAcceptor> {
             try!(os::listen(self.desc(), backlog));
-            Ok(UnixAcceptor { desc: self.desc })
+
+            let mut acceptor = UnixAcceptor::from_desc(self.desc);
+            acceptor.path = self.path;
+            Ok(acceptor)
         }
     }
 
@@ -527,13 +1675,25 @@ pub mod pipe {
 
     impl FromIoDesc for UnixListener {
         fn from_desc(desc: os::IoDesc) -> Self {
-            UnixListener { desc: desc }
+            UnixListener { desc: desc, path: None }
         }
     }
 
+    /// Accepts incoming connections.
+    ///
+    /// A spare file descriptor is held for when the process runs out of
+    /// them. Instead of leaving the connection pending, which keeps the
+    /// acceptor readable and spins the event loop, `accept` then uses the
+    /// spare to accept the connection and close it right away, and returns an
+    /// error for which `is_resource_limit()` is true.
+    ///
+    /// An acceptor created from `UnixSocket::bind_with_cleanup` removes its
+    /// socket file when dropped.
     #[derive(Debug)]
     pub struct UnixAcceptor {
         desc: os::IoDesc,
+        spare: Option<os::IoDesc>,
+        path: Option<BoundPath>,
     }
 
     impl UnixAcceptor {
@@ -542,6 +1702,15 @@ pub mod pipe {
             let listener = try!(sock.bind(addr));
             listener.listen(backlog)
         }
+
+        /// Adopts a socket that is already bound and listening, see
+        /// `TcpAcceptor::from_raw_fd`. The socket file is left in place when
+        /// the acceptor is dropped.
+        pub fn from_raw_fd(fd: i32) -> MioResult<UnixAcceptor> {
+            let desc = os::IoDesc { fd: fd };
+            try!(os::set_nonblock(&desc, true));
+            Ok(FromIoDesc::from_desc(desc))
+        }
     }
 
     impl IoHandle for UnixAcceptor {
@@ -552,7 +1721,11 @@ pub mod pipe {
 
     impl FromIoDesc for UnixAcceptor {
         fn from_desc(desc: os::IoDesc) -> Self {
-            UnixAcceptor { desc: desc }
+            UnixAcceptor {
+                desc: desc,
+                spare: os::reserve_fd().ok(),
+                path: None,
+            }
         }
     }
 
@@ -563,7 +1736,7 @@ pub mod pipe {
         type Output = UnixSocket;
 
         fn accept(&mut self) -> MioResult<NonBlock<UnixSocket>> {
-            match os::accept(self.desc()) {
+            match os::accept_or_shed(&self.desc, &mut self.spare) {
                 Ok(sock) => Ok(Ready(UnixSocket { desc: sock })),
                 Err(e) => {
                     if e.is_would_block() {
@@ -575,4 +1748,42 @@ pub mod pipe {
             }
         }
     }
+
+    /// Accepts `SOCK_SEQPACKET` connections, see `UnixSocket::seqpacket`.
+    #[derive(Debug)]
+    pub struct UnixSeqpacketAcceptor {
+        inner: UnixAcceptor,
+    }
+
+    impl UnixSeqpacketAcceptor {
+        pub fn new(addr: &SockAddr, backlog: usize) -> MioResult<UnixSeqpacketAcceptor> {
+            let sock = try!(UnixSocket::seqpacket());
+            let listener = try!(sock.bind(addr));
+
+            Ok(UnixSeqpacketAcceptor { inner: try!(listener.listen(backlog)) })
+        }
+    }
+
+    impl IoHandle for UnixSeqpacketAcceptor {
+        fn desc(&self) -> &os::IoDesc {
+            self.inner.desc()
+        }
+    }
+
+    impl FromIoDesc for UnixSeqpacketAcceptor {
+        fn from_desc(desc: os::IoDesc) -> Self {
+            UnixSeqpacketAcceptor { inner: FromIoDesc::from_desc(desc) }
+        }
+    }
+
+    impl Socket for UnixSeqpacketAcceptor {
+    }
+
+    impl IoAcceptor for UnixSeqpacketAcceptor {
+        type Output = UnixSocket;
+
+        fn accept(&mut self) -> MioResult<NonBlock<UnixSocket>> {
+            self.inner.accept()
+        }
+    }
 }
@@ -1,7 +1,8 @@
-use std::{fmt, cmp};
+use std::{fmt, cmp, usize};
 use std::sync::Arc;
-use std::sync::atomic::AtomicIsize;
-use std::sync::atomic::Ordering::Relaxed;
+use std::sync::atomic::{AtomicIsize, AtomicUsize};
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+use std::thread::Thread;
 use error::MioResult;
 use io::IoHandle;
 use os;
@@ -9,6 +10,53 @@ use util::BoundedQueue;
 
 const SLEEP: isize = -1;
 
+// Set in `NotifyInner::gate` once the queue is closed, the other bits count
+// the senders that are pushing a message
+const CLOSED: usize = 1 << (usize::BITS - 1);
+
+/// The reason a message could not be sent to the event loop. The message is
+/// handed back in either case.
+pub enum NotifyError<M> {
+    /// The notification queue is at capacity.
+    Full(M),
+    /// The event loop no longer accepts messages, see
+    /// [EventLoop::drain_notifications](struct.EventLoop.html#method.drain_notifications).
+    Closed(M),
+}
+
+impl<M> NotifyError<M> {
+    /// Returns the message that could not be sent.
+    pub fn into_inner(self) -> M {
+        match self {
+            NotifyError::Full(m) => m,
+            NotifyError::Closed(m) => m,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        match *self {
+            NotifyError::Full(..) => true,
+            _ => false
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        match *self {
+            NotifyError::Closed(..) => true,
+            _ => false
+        }
+    }
+}
+
+impl<M> fmt::Debug for NotifyError<M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NotifyError::Full(..) => write!(fmt, "Full(..)"),
+            NotifyError::Closed(..) => write!(fmt, "Closed(..)"),
+        }
+    }
+}
+
 /// Send notifications to the event loop, waking it up if necessary. If the
 /// event loop is not currently sleeping, avoid using an OS wake-up strategy
 /// (eventfd, pipe, ...). Backed by a pre-allocated lock free MPMC queue.
@@ -32,10 +80,15 @@ impl<M: Send> Notify<M> {
     }
 
     #[inline]
-    pub fn notify(&self, value: M) -> Result<(), M> {
+    pub fn notify(&self, value: M) -> Result<(), NotifyError<M>> {
         self.inner.notify(value)
     }
 
+    /// Stops accepting messages and returns the ones still queued.
+    pub fn close(&self) -> Vec<M> {
+        self.inner.close()
+    }
+
     #[inline]
     pub fn poll(&self) -> Option<M> {
         self.inner.poll()
@@ -66,6 +119,7 @@ unsafe impl<M: Send> Send for Notify<M> { }
 
 struct NotifyInner<M> {
     state: AtomicIsize,
+    gate: AtomicUsize,
     queue: BoundedQueue<M>,
     awaken: os::Awakener
 }
@@ -74,6 +128,7 @@ impl<M: Send> NotifyInner<M> {
     fn with_capacity(capacity: usize) -> MioResult<NotifyInner<M>> {
         Ok(NotifyInner {
             state: AtomicIsize::new(0),
+            gate: AtomicUsize::new(0),
             queue: BoundedQueue::with_capacity(capacity),
             awaken: try!(os::Awakener::new())
         })
@@ -122,11 +177,35 @@ impl<M: Send> NotifyInner<M> {
         self.queue.pop()
     }
 
-    fn notify(&self, value: M) -> Result<(), M> {
+    fn notify(&self, value: M) -> Result<(), NotifyError<M>> {
+        // Enter the gate, unless the queue is closed. `close` waits for the
+        // senders that got in, so their messages are either drained or the
+        // send fails, never lost in between.
+        let mut cur = self.gate.load(SeqCst);
+
+        loop {
+            if cur & CLOSED != 0 {
+                return Err(NotifyError::Closed(value));
+            }
+
+            let val = self.gate.compare_and_swap(cur, cur + 1, SeqCst);
+
+            if val == cur {
+                break;
+            }
+
+            cur = val;
+        }
+
+        let res = self.push(value);
+        self.gate.fetch_sub(1, SeqCst);
+        res
+    }
+
+    fn push(&self, value: M) -> Result<(), NotifyError<M>> {
         // First, push the message onto the queue
-        if !self.queue.push(value) {
-            // TODO: Don't fail
-            panic!("queue full");
+        if let Err(value) = self.queue.push(value) {
+            return Err(NotifyError::Full(value));
         }
 
         let mut cur = self.state.load(Relaxed);
@@ -154,6 +233,26 @@ impl<M: Send> NotifyInner<M> {
         Ok(())
     }
 
+    fn close(&self) -> Vec<M> {
+        self.gate.fetch_or(CLOSED, SeqCst);
+
+        // Let the senders that got in before the gate closed finish pushing
+        while self.gate.load(SeqCst) != CLOSED {
+            Thread::yield_now();
+        }
+
+        let mut remaining = vec![];
+
+        while let Some(msg) = self.queue.pop() {
+            remaining.push(msg);
+        }
+
+        // Nothing is pending anymore
+        self.state.store(0, Relaxed);
+
+        remaining
+    }
+
     fn cleanup(&self) {
         self.awaken.cleanup();
     }
@@ -0,0 +1,39 @@
+use os::event::{Interest, PollOpt, Ready};
+use os::token::Token;
+
+/// Receives structured notifications about what happens inside an event
+/// loop, for collecting metrics or tracing individual events without
+/// parsing log output.
+///
+/// Installed with
+/// [EventLoop::set_observer](struct.EventLoop.html#method.set_observer).
+/// Every method has an empty default implementation, so an observer only
+/// implements the ones it needs. The methods are called synchronously from
+/// the event loop thread and should be cheap. The observer has to be
+/// `Send`, like the event loop that owns it.
+#[allow(unused_variables)]
+pub trait LoopObserver {
+    /// A handle was registered with `token`.
+    fn on_register(&mut self, token: Token, interest: Interest, opt: PollOpt) {
+    }
+
+    /// The registration of `token` was changed.
+    fn on_reregister(&mut self, token: Token, interest: Interest, opt: PollOpt) {
+    }
+
+    /// The handle registered with `token` was deregistered.
+    fn on_deregister(&mut self, token: Token) {
+    }
+
+    /// A poll returned readiness for `token`, or a handle registered with
+    /// `PollOpt::synthesize_initial()` was found ready. Called for every
+    /// event before any of them is dispatched, so events that end up
+    /// dropped, because the handler deregistered the token in the meantime,
+    /// are reported as well.
+    fn on_event(&mut self, token: Token, readiness: Ready) {
+    }
+
+    /// The readiness of `token` is about to be passed to the handler.
+    fn on_dispatch(&mut self, token: Token, readiness: Ready) {
+    }
+}
@@ -1,4 +1,6 @@
-use std::mem;
+use std::{mem, usize};
+use nix::NixError;
+use nix::errno::{Errno, EBADF, EEXIST, ENOENT};
 use nix::fcntl::Fd;
 use nix::sys::epoll::*;
 use nix::unistd::close;
@@ -17,8 +19,12 @@ impl Selector {
         Ok(Selector { epfd: epfd })
     }
 
-    /// Wait for events from the OS
-    pub fn select(&mut self, evts: &mut Events, timeout_ms: usize) -> MioResult<()> {
+    /// Wait for events from the OS. A timeout of `None` waits until an event
+    /// is received.
+    pub fn select(&mut self, evts: &mut Events, timeout_ms: Option<usize>) -> MioResult<()> {
+        // epoll_wait takes the timeout as a c_int, where -1 means forever
+        let timeout_ms = timeout_ms.unwrap_or(usize::MAX);
+
         // Wait for epoll events for at most timeout_ms milliseconds
         let cnt = try!(epoll_wait(self.epfd, evts.events.as_mut_slice(), timeout_ms)
                            .map_err(MioError::from_nix_error));
@@ -28,17 +34,30 @@ impl Selector {
     }
 
     /// Register event interests for the given IO handle with the OS
+    ///
+    /// Fails with `AlreadyRegistered` if the descriptor is registered
+    /// already.
     pub fn register(&mut self, io: &IoDesc, token: usize, interests: Interest, opts: PollOpt) -> MioResult<()> {
+        if opts.is_exclusive() {
+            return self.register_exclusive(io, token, interests, opts);
+        }
+
         let info = EpollEvent {
             events: ioevent_to_epoll(interests, opts),
             data: token as u64
         };
 
         epoll_ctl(self.epfd, EpollOp::EpollCtlAdd, io.fd, &info)
-            .map_err(MioError::from_nix_error)
+            .map_err(|err| match err {
+                NixError::Sys(EEXIST) => MioError::already_registered(Some(err)),
+                _ => MioError::from_nix_error(err)
+            })
     }
 
     /// Register event interests for the given IO handle with the OS
+    ///
+    /// Fails with `NotRegistered` when epoll does not know the descriptor,
+    /// either because it was never registered or because it has been closed.
     pub fn reregister(&mut self, io: &IoDesc, token: usize, interests: Interest, opts: PollOpt) -> MioResult<()> {
         let info = EpollEvent {
             events: ioevent_to_epoll(interests, opts),
@@ -46,7 +65,36 @@ impl Selector {
         };
 
         epoll_ctl(self.epfd, EpollOp::EpollCtlMod, io.fd, &info)
-            .map_err(MioError::from_nix_error)
+            .map_err(|err| match err {
+                NixError::Sys(ENOENT) | NixError::Sys(EBADF) => MioError::not_registered(Some(err)),
+                _ => MioError::from_nix_error(err)
+            })
+    }
+
+    /// Same as `reregister`: epoll never adds a registration when modifying
+    /// one.
+    pub fn reregister_existing(&mut self, io: &IoDesc, token: usize, interests: Interest, opts: PollOpt) -> MioResult<()> {
+        self.reregister(io, token, interests, opts)
+    }
+
+    // nix predates EPOLLEXCLUSIVE, so exclusive registrations go through
+    // epoll_ctl directly
+    fn register_exclusive(&mut self, io: &IoDesc, token: usize, interests: Interest, opts: PollOpt) -> MioResult<()> {
+        let mut info = ffi::epoll_event {
+            events: ioevent_to_epoll(interests, opts).bits() | ffi::EPOLLEXCLUSIVE,
+            data: token as u64
+        };
+
+        if unsafe { ffi::epoll_ctl(self.epfd, ffi::EPOLL_CTL_ADD, io.fd, &mut info) } < 0 {
+            let err = NixError::Sys(Errno::last());
+
+            return Err(match err {
+                NixError::Sys(EEXIST) => MioError::already_registered(Some(err)),
+                _ => MioError::from_nix_error(err)
+            });
+        }
+
+        Ok(())
     }
 
     /// Deregister event interests for the given IO handle with the OS
@@ -64,6 +112,24 @@ impl Selector {
     }
 }
 
+mod ffi {
+    use libc::c_int;
+
+    pub const EPOLL_CTL_ADD: c_int = 1;
+    pub const EPOLLEXCLUSIVE: u32 = 1 << 28;
+
+    #[repr(C)]
+    #[cfg_attr(target_arch = "x86_64", repr(packed))]
+    pub struct epoll_event {
+        pub events: u32,
+        pub data: u64,
+    }
+
+    extern {
+        pub fn epoll_ctl(epfd: c_int, op: c_int, fd: c_int, event: *mut epoll_event) -> c_int;
+    }
+}
+
 fn ioevent_to_epoll(interest: Interest, opts: PollOpt) -> EpollEventKind {
     let mut kind = EpollEventKind::empty();
 
@@ -79,6 +145,10 @@ fn ioevent_to_epoll(interest: Interest, opts: PollOpt) -> EpollEventKind {
         kind.insert(EPOLLRDHUP);
     }
 
+    if interest.is_priority() {
+        kind.insert(EPOLLPRI);
+    }
+
     if opts.is_edge() {
         kind.insert(EPOLLET);
     }
@@ -135,6 +205,10 @@ impl Events {
             kind = kind | Interest::writable();
         }
 
+        if epoll.contains(EPOLLPRI) {
+            kind = kind | Interest::priority();
+        }
+
         // EPOLLHUP - Usually means a socket error happened
         if epoll.contains(EPOLLERR) {
             kind = kind | Interest::error();
@@ -144,6 +218,12 @@ impl Events {
             kind = kind | Interest::hup();
         }
 
+        // EPOLLRDHUP without EPOLLHUP - The peer only shut down its writing
+        // half, the connection is still open in the other direction
+        if epoll.contains(EPOLLRDHUP) && !epoll.contains(EPOLLHUP) {
+            kind = kind | Interest::rdhup();
+        }
+
         let token = self.events[idx].data;
 
         IoEvent::new(kind, token as usize)
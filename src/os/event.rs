@@ -25,9 +25,49 @@ impl PollOpt {
         PollOpt(0x080)
     }
 
+    /// Checks the readiness of the handle when it is registered and, if it
+    /// is already ready, delivers an event for it on the next tick. With
+    /// edge triggering, readiness that predates the registration is
+    /// otherwise only reported once a new edge occurs.
+    ///
+    /// The handle is dispatched once either way: if the next poll reports
+    /// it too, as most selectors do for readiness that is already there
+    /// when a handle is added, the synthetic event is dropped.
+    ///
+    /// Only applies to `register_opt` and `register_owned`.
+    #[inline]
+    pub fn synthesize_initial() -> PollOpt {
+        PollOpt(0x100)
+    }
+
+    /// Stands for the event loop's `default_poll_opt`, see
+    /// `EventLoopConfig`. Other options may be added to it, for example
+    /// `PollOpt::configured() | PollOpt::oneshot()`.
+    ///
+    /// It is a placeholder rather than an option, so it is not part of
+    /// `all()`.
+    #[inline]
+    pub fn configured() -> PollOpt {
+        PollOpt(0x200)
+    }
+
+    /// When several event loops register the same handle, typically a
+    /// listening socket shared between threads, wakes up only one of them
+    /// per event instead of all of them. Each connection then wakes a
+    /// single loop rather than causing a thundering herd.
+    ///
+    /// Uses `EPOLLEXCLUSIVE`, which requires Linux 4.5, and is ignored on
+    /// other platforms. The kernel only accepts it when the handle is first
+    /// registered, not with `oneshot`: reregistering an exclusive
+    /// registration fails, so deregister and register it again instead.
+    #[inline]
+    pub fn exclusive() -> PollOpt {
+        PollOpt(0x400)
+    }
+
     #[inline]
     pub fn all() -> PollOpt {
-        PollOpt::edge() | PollOpt::level() | PollOpt::oneshot()
+        PollOpt::edge() | PollOpt::level() | PollOpt::oneshot() | PollOpt::synthesize_initial() | PollOpt::exclusive()
     }
 
     #[inline]
@@ -45,6 +85,21 @@ impl PollOpt {
         self.contains(PollOpt::oneshot())
     }
 
+    #[inline]
+    pub fn is_synthesize_initial(&self) -> bool {
+        self.contains(PollOpt::synthesize_initial())
+    }
+
+    #[inline]
+    pub fn is_configured(&self) -> bool {
+        self.contains(PollOpt::configured())
+    }
+
+    #[inline]
+    pub fn is_exclusive(&self) -> bool {
+        self.contains(PollOpt::exclusive())
+    }
+
     #[inline]
     pub fn bits(&self) -> usize {
         self.0
@@ -117,7 +172,10 @@ impl fmt::Debug for PollOpt {
         let flags = [
             (PollOpt::edge(), "Edge-Triggered"),
             (PollOpt::level(), "Level-Triggered"),
-            (PollOpt::oneshot(), "OneShot")];
+            (PollOpt::oneshot(), "OneShot"),
+            (PollOpt::synthesize_initial(), "SynthesizeInitial"),
+            (PollOpt::configured(), "Configured"),
+            (PollOpt::exclusive(), "Exclusive")];
 
         for &(flag, msg) in flags.iter() {
             if self.contains(flag) {
@@ -132,6 +190,19 @@ impl fmt::Debug for PollOpt {
     }
 }
 
+/// Renders the set options by name, for example `edge|oneshot`.
+impl fmt::Display for PollOpt {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        display_flags(fmt, self.bits(), &[
+            (PollOpt::edge().bits(), "edge"),
+            (PollOpt::level().bits(), "level"),
+            (PollOpt::oneshot().bits(), "oneshot"),
+            (PollOpt::synthesize_initial().bits(), "synthesize_initial"),
+            (PollOpt::configured().bits(), "configured"),
+            (PollOpt::exclusive().bits(), "exclusive")])
+    }
+}
+
 #[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord)]
 pub struct Interest(usize);
 
@@ -165,6 +236,21 @@ impl Interest {
         Interest(0x010)
     }
 
+    /// Urgent (out-of-band) data is available to read. Only reported by
+    /// epoll.
+    #[inline]
+    pub fn priority() -> Interest {
+        Interest(0x020)
+    }
+
+    /// The peer shut down its writing half of the connection, but the
+    /// connection is not fully hung up. Only reported by epoll, for handles
+    /// registered with `hup` interest.
+    #[inline]
+    pub fn rdhup() -> Interest {
+        Interest(0x040)
+    }
+
     #[inline]
     pub fn all() -> Interest {
         Interest::readable() |
@@ -198,6 +284,16 @@ impl Interest {
         self.contains(Interest::hinted())
     }
 
+    #[inline]
+    pub fn is_priority(&self) -> bool {
+        self.contains(Interest::priority())
+    }
+
+    #[inline]
+    pub fn is_rdhup(&self) -> bool {
+        self.contains(Interest::rdhup())
+    }
+
     #[inline]
     pub fn insert(&mut self, other: Interest) {
         self.0 |= other.0;
@@ -272,7 +368,9 @@ impl fmt::Debug for Interest {
             (Interest::writable(), "Writable"),
             (Interest::error(),    "Error"),
             (Interest::hup(),      "HupHint"),
-            (Interest::hinted(),   "Hinted")];
+            (Interest::hinted(),   "Hinted"),
+            (Interest::priority(), "Priority"),
+            (Interest::rdhup(),    "RdHupHint")];
 
         for &(flag, msg) in flags.iter() {
             if self.contains(flag) {
@@ -287,6 +385,40 @@ impl fmt::Debug for Interest {
     }
 }
 
+/// Renders the set interests by name, for example `readable|writable`.
+impl fmt::Display for Interest {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        display_flags(fmt, self.bits(), &[
+            (Interest::readable().bits(), "readable"),
+            (Interest::writable().bits(), "writable"),
+            (Interest::error().bits(),    "error"),
+            (Interest::hup().bits(),      "hup"),
+            (Interest::hinted().bits(),   "hinted"),
+            (Interest::priority().bits(), "priority"),
+            (Interest::rdhup().bits(),    "rdhup")])
+    }
+}
+
+// Writes the names of the flags set in `bits` separated by `|`, or `none`
+fn display_flags(fmt: &mut fmt::Formatter, bits: usize, flags: &[(usize, &str)]) -> fmt::Result {
+    let mut one = false;
+
+    for &(flag, name) in flags.iter() {
+        if bits & flag == flag {
+            if one { try!(write!(fmt, "|")) }
+            try!(write!(fmt, "{}", name));
+
+            one = true
+        }
+    }
+
+    if !one {
+        try!(write!(fmt, "none"));
+    }
+
+    Ok(())
+}
+
 #[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord)]
 pub struct ReadHint(usize);
 
@@ -298,7 +430,7 @@ impl ReadHint {
 
     #[inline]
     pub fn all() -> ReadHint {
-        ReadHint::data() | ReadHint::hup() | ReadHint::error()
+        ReadHint::data() | ReadHint::hup() | ReadHint::error() | ReadHint::oob() | ReadHint::rdhup()
     }
 
     #[inline]
@@ -316,6 +448,21 @@ impl ReadHint {
         ReadHint(0x004)
     }
 
+    /// Urgent (out-of-band) data arrived, see `TcpSocket::recv_urgent`.
+    #[inline]
+    pub fn oob() -> ReadHint {
+        ReadHint(0x008)
+    }
+
+    /// The peer shut down its writing half: the data already received can
+    /// still be read, but no more will arrive. Always comes along with
+    /// `hup`, which on its own may also mean the connection is fully closed.
+    /// Only reported by epoll.
+    #[inline]
+    pub fn rdhup() -> ReadHint {
+        ReadHint(0x010)
+    }
+
     #[inline]
     pub fn is_data(&self) -> bool {
         self.contains(ReadHint::data())
@@ -331,6 +478,16 @@ impl ReadHint {
         self.contains(ReadHint::error())
     }
 
+    #[inline]
+    pub fn is_oob(&self) -> bool {
+        self.contains(ReadHint::oob())
+    }
+
+    #[inline]
+    pub fn is_rdhup(&self) -> bool {
+        self.contains(ReadHint::rdhup())
+    }
+
     #[inline]
     pub fn insert(&mut self, other: ReadHint) {
         self.0 |= other.0;
@@ -403,7 +560,151 @@ impl fmt::Debug for ReadHint {
         let flags = [
             (ReadHint::data(),  "DataHint"),
             (ReadHint::hup(),   "HupHint"),
-            (ReadHint::error(), "ErrorHint")];
+            (ReadHint::error(), "ErrorHint"),
+            (ReadHint::oob(),   "OobHint"),
+            (ReadHint::rdhup(), "RdHupHint")];
+
+        for &(flag, msg) in flags.iter() {
+            if self.contains(flag) {
+                if one { try!(write!(fmt, " | ")) }
+                try!(write!(fmt, "{}", msg));
+
+                one = true
+            }
+        }
+
+        Ok(())
+    }
+}
+
+
+// Set operations and operators of a flag type wrapping a `usize`, which
+// must have an `all()` constructor for `Not`
+macro_rules! flag_ops {
+    ($name:ident) => {
+        impl $name {
+            #[inline]
+            pub fn insert(&mut self, other: $name) {
+                self.0 |= other.0;
+            }
+
+            #[inline]
+            pub fn remove(&mut self, other: $name) {
+                self.0 &= !other.0;
+            }
+
+            #[inline]
+            pub fn contains(&self, other: $name) -> bool {
+                (*self & other) == other
+            }
+
+            #[inline]
+            pub fn bits(&self) -> usize {
+                self.0
+            }
+        }
+
+        impl ops::BitOr for $name {
+            type Output = $name;
+
+            #[inline]
+            fn bitor(self, other: $name) -> $name {
+                $name(self.bits() | other.bits())
+            }
+        }
+
+        impl ops::BitXor for $name {
+            type Output = $name;
+
+            #[inline]
+            fn bitxor(self, other: $name) -> $name {
+                $name(self.bits() ^ other.bits())
+            }
+        }
+
+        impl ops::BitAnd for $name {
+            type Output = $name;
+
+            #[inline]
+            fn bitand(self, other: $name) -> $name {
+                $name(self.bits() & other.bits())
+            }
+        }
+
+        impl ops::Sub for $name {
+            type Output = $name;
+
+            #[inline]
+            fn sub(self, other: $name) -> $name {
+                $name(self.bits() & !other.bits())
+            }
+        }
+
+        impl ops::Not for $name {
+            type Output = $name;
+
+            #[inline]
+            fn not(self) -> $name {
+                $name(!self.bits() & $name::all().bits())
+            }
+        }
+    }
+}
+
+#[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord)]
+pub struct WriteHint(usize);
+
+impl WriteHint {
+    #[inline]
+    pub fn none() -> WriteHint {
+        WriteHint(0)
+    }
+
+    #[inline]
+    pub fn all() -> WriteHint {
+        WriteHint::ready() | WriteHint::hup() | WriteHint::error()
+    }
+
+    #[inline]
+    pub fn ready() -> WriteHint {
+        WriteHint(0x001)
+    }
+
+    #[inline]
+    pub fn hup() -> WriteHint {
+        WriteHint(0x002)
+    }
+
+    #[inline]
+    pub fn error() -> WriteHint {
+        WriteHint(0x004)
+    }
+
+    #[inline]
+    pub fn is_ready(&self) -> bool {
+        self.contains(WriteHint::ready())
+    }
+
+    #[inline]
+    pub fn is_hup(&self) -> bool {
+        self.contains(WriteHint::hup())
+    }
+
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.contains(WriteHint::error())
+    }
+}
+
+flag_ops!(WriteHint);
+
+impl fmt::Debug for WriteHint {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut one = false;
+        let flags = [
+            (WriteHint::ready(), "ReadyHint"),
+            (WriteHint::hup(),   "HupHint"),
+            (WriteHint::error(), "ErrorHint")];
 
         for &(flag, msg) in flags.iter() {
             if self.contains(flag) {
@@ -418,6 +719,125 @@ impl fmt::Debug for ReadHint {
     }
 }
 
+/// The readiness of a handle as reported by the unified
+/// `Handler::ready` callback: all of a token's events of a tick folded
+/// into one value.
+#[derive(Copy, PartialEq, Eq, Clone, PartialOrd, Ord)]
+pub struct Ready(usize);
+
+impl Ready {
+    #[inline]
+    pub fn none() -> Ready {
+        Ready(0)
+    }
+
+    #[inline]
+    pub fn all() -> Ready {
+        Ready::readable() | Ready::writable() | Ready::hup() | Ready::error() | Ready::oob() | Ready::rdhup()
+    }
+
+    #[inline]
+    pub fn readable() -> Ready {
+        Ready(0x001)
+    }
+
+    #[inline]
+    pub fn writable() -> Ready {
+        Ready(0x002)
+    }
+
+    #[inline]
+    pub fn hup() -> Ready {
+        Ready(0x004)
+    }
+
+    #[inline]
+    pub fn error() -> Ready {
+        Ready(0x008)
+    }
+
+    /// Urgent (out-of-band) data arrived, see `ReadHint::oob`. Always comes
+    /// along with `readable`.
+    #[inline]
+    pub fn oob() -> Ready {
+        Ready(0x010)
+    }
+
+    /// The peer shut down its writing half, see `ReadHint::rdhup`.
+    #[inline]
+    pub fn rdhup() -> Ready {
+        Ready(0x020)
+    }
+
+    #[inline]
+    pub fn is_readable(&self) -> bool {
+        self.contains(Ready::readable())
+    }
+
+    #[inline]
+    pub fn is_writable(&self) -> bool {
+        self.contains(Ready::writable())
+    }
+
+    #[inline]
+    pub fn is_hup(&self) -> bool {
+        self.contains(Ready::hup())
+    }
+
+    #[inline]
+    pub fn is_error(&self) -> bool {
+        self.contains(Ready::error())
+    }
+
+    #[inline]
+    pub fn is_oob(&self) -> bool {
+        self.contains(Ready::oob())
+    }
+
+    #[inline]
+    pub fn is_rdhup(&self) -> bool {
+        self.contains(Ready::rdhup())
+    }
+}
+
+flag_ops!(Ready);
+
+impl fmt::Debug for Ready {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let mut one = false;
+        let flags = [
+            (Ready::readable(), "Readable"),
+            (Ready::writable(), "Writable"),
+            (Ready::hup(),      "Hup"),
+            (Ready::error(),    "Error"),
+            (Ready::oob(),      "Oob"),
+            (Ready::rdhup(),    "RdHup")];
+
+        for &(flag, msg) in flags.iter() {
+            if self.contains(flag) {
+                if one { try!(write!(fmt, " | ")) }
+                try!(write!(fmt, "{}", msg));
+
+                one = true
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Renders the set bits by name, for example `readable|hup`.
+impl fmt::Display for Ready {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        display_flags(fmt, self.bits(), &[
+            (Ready::readable().bits(), "readable"),
+            (Ready::writable().bits(), "writable"),
+            (Ready::hup().bits(),      "hup"),
+            (Ready::error().bits(),    "error"),
+            (Ready::oob().bits(),      "oob"),
+            (Ready::rdhup().bits(),    "rdhup")])
+    }
+}
 
 #[derive(Copy, Debug)]
 pub struct IoEvent {
@@ -468,12 +888,89 @@ impl IoEvent {
             hint = hint | ReadHint::error();
         }
 
+        if self.kind.is_priority() {
+            hint = hint | ReadHint::oob();
+        }
+
+        if self.kind.is_rdhup() {
+            hint = hint | ReadHint::rdhup();
+        }
+
+        hint
+    }
+
+    /// Return an optional hint for a writable handle. A handle that has a
+    /// connect in progress reports a writable event once the connection
+    /// attempt completes: if neither the HupHint nor the ErrorHint is set,
+    /// the connection has been established, otherwise it failed.
+    pub fn write_hint(&self) -> WriteHint {
+        let mut hint = WriteHint::none();
+
+        // The backend doesn't support hinting
+        if !self.kind.is_hinted() {
+            return hint;
+        }
+
+        if self.kind.is_writable() {
+            hint = hint | WriteHint::ready();
+        }
+
+        if self.kind.is_hup() {
+            hint = hint | WriteHint::hup();
+        }
+
+        if self.kind.is_error() {
+            hint = hint | WriteHint::error();
+        }
+
         hint
     }
 
+    /// Returns the readiness carried by the event, as passed to
+    /// `Handler::ready`.
+    pub fn readiness(&self) -> Ready {
+        let mut ready = Ready::none();
+
+        if self.kind.is_readable() || self.kind.is_priority() {
+            ready.insert(Ready::readable());
+        }
+
+        if self.kind.is_priority() {
+            ready.insert(Ready::oob());
+        }
+
+        if self.kind.is_rdhup() {
+            ready.insert(Ready::rdhup());
+        }
+
+        if self.kind.is_writable() {
+            ready.insert(Ready::writable());
+        }
+
+        if self.kind.is_hup() {
+            ready.insert(Ready::hup());
+        }
+
+        if self.kind.is_error() {
+            ready.insert(Ready::error());
+        }
+
+        ready
+    }
+
+    /// Combines two events of the same token into one carrying the
+    /// readiness of both. Some selectors, kqueue among them, report
+    /// readable and writable readiness as separate events.
+    pub fn merge(self, other: IoEvent) -> IoEvent {
+        IoEvent {
+            kind: self.kind | other.kind,
+            token: self.token
+        }
+    }
+
     /// This event indicated that the  handle is now readable
     pub fn is_readable(&self) -> bool {
-        self.kind.is_readable() || self.kind.is_hup()
+        self.kind.is_readable() || self.kind.is_hup() || self.kind.is_priority()
     }
 
     /// This event indicated that the  handle is now writable
@@ -485,4 +982,58 @@ impl IoEvent {
     pub fn is_error(&self) -> bool {
         self.kind.is_error()
     }
+
+    /// Drops readable or writable readiness that is not part of `interest`.
+    /// Errors and hangups are always kept.
+    pub fn restrict(self, interest: Interest) -> IoEvent {
+        let mut kind = self.kind;
+
+        if !interest.is_readable() {
+            kind.remove(Interest::readable());
+        }
+
+        if !interest.is_writable() {
+            kind.remove(Interest::writable());
+        }
+
+        IoEvent {
+            kind: kind,
+            token: self.token
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interest, PollOpt, Ready, IoEvent};
+
+    #[test]
+    fn test_interest_fmt() {
+        let interest = Interest::readable() | Interest::writable();
+
+        assert_eq!("readable|writable", format!("{}", interest).as_slice());
+        assert_eq!("Readable | Writable", format!("{:?}", interest).as_slice());
+        assert_eq!("none", format!("{}", Interest::none()).as_slice());
+    }
+
+    #[test]
+    fn test_poll_opt_fmt() {
+        let opts = PollOpt::edge() | PollOpt::oneshot();
+
+        assert_eq!("edge|oneshot", format!("{}", opts).as_slice());
+        assert_eq!("Edge-Triggered | OneShot", format!("{:?}", opts).as_slice());
+        assert_eq!("none", format!("{}", PollOpt::empty()).as_slice());
+    }
+
+    #[test]
+    fn test_ready_merge() {
+        let evt = IoEvent::new(Interest::readable(), 1)
+            .merge(IoEvent::new(Interest::writable() | Interest::hup(), 1));
+
+        let ready = evt.readiness();
+
+        assert!(ready.is_readable() && ready.is_writable() && ready.is_hup());
+        assert!(!ready.is_error());
+        assert_eq!("readable|writable|hup", format!("{}", ready).as_slice());
+    }
 }
@@ -1,4 +1,6 @@
 use std::mem;
+use nix::NixError;
+use nix::errno::{EBADF, ENOENT};
 use nix::fcntl::Fd;
 use nix::sys::event::*;
 use nix::sys::event::EventFilter::*;
@@ -6,6 +8,9 @@ use error::{MioResult, MioError};
 use os::IoDesc;
 use os::event::{IoEvent, Interest, PollOpt};
 
+// About 24 days
+const MAX_TIMEOUT_MS: usize = 0x7fff_ffff;
+
 pub struct Selector {
     kq: Fd,
     changes: Events
@@ -19,7 +24,13 @@ impl Selector {
         })
     }
 
-    pub fn select(&mut self, evts: &mut Events, timeout_ms: usize) -> MioResult<()> {
+    /// Wait for events from the OS. A timeout of `None` waits until an event
+    /// is received.
+    pub fn select(&mut self, evts: &mut Events, timeout_ms: Option<usize>) -> MioResult<()> {
+        // kevent always receives a timeout, so waiting forever is
+        // approximated by the longest wait it accepts
+        let timeout_ms = timeout_ms.unwrap_or(MAX_TIMEOUT_MS);
+
         let cnt = try!(kevent(self.kq, self.changes.as_slice(),
                               evts.as_mut_slice(), timeout_ms)
                                   .map_err(MioError::from_nix_error));
@@ -45,6 +56,27 @@ impl Selector {
         self.register(io, token, interests, opts)
     }
 
+    /// Like `reregister`, but fails with `NotRegistered` instead of adding a
+    /// registration when `io` is not registered, for example because it
+    /// was closed and its number reused by another file. The change is
+    /// applied right away rather than with the next select.
+    pub fn reregister_existing(&mut self, io: &IoDesc, token: usize, interests: Interest, opts: PollOpt) -> MioResult<()> {
+        // Apply the pending changes first, they may concern `io`
+        try!(self.flush_changes());
+
+        // Without EV_ADD, a change fails with ENOENT if the filter is not
+        // registered. `register` adds both filters, so probing the read
+        // one is enough. With no room for events, kevent reports the error
+        // itself and stops there, before the changes that add filters.
+        try!(self.ev_push(io, token, EVFILT_READ, EV_DISABLE));
+        try!(self.register(io, token, interests, opts));
+
+        self.flush_changes().map_err(|err| match err.sys().map(|e| *e) {
+            Some(sys @ NixError::Sys(ENOENT)) | Some(sys @ NixError::Sys(EBADF)) => MioError::not_registered(Some(sys)),
+            _ => err
+        })
+    }
+
     pub fn deregister(&mut self, io: &IoDesc) -> MioResult<()> {
         try!(self.ev_push(io, 0, EVFILT_READ, EV_DELETE));
         try!(self.ev_push(io, 0, EVFILT_WRITE, EV_DELETE));
@@ -86,11 +118,21 @@ impl Selector {
 
     fn maybe_flush_changes(&mut self) -> MioResult<()> {
         if self.changes.is_full() {
-            try!(kevent(self.kq, self.changes.as_slice(), &mut [], 0)
-                    .map_err(MioError::from_nix_error));
-            self.changes.len = 0;
+            try!(self.flush_changes());
+        }
+
+        Ok(())
+    }
+
+    fn flush_changes(&mut self) -> MioResult<()> {
+        if self.changes.len == 0 {
+            return Ok(());
         }
 
+        let res = kevent(self.kq, self.changes.as_slice(), &mut [], 0);
+        self.changes.len = 0;
+
+        try!(res.map_err(MioError::from_nix_error));
         Ok(())
     }
 }
@@ -45,3 +45,41 @@ fn eventfd() -> MioResult<IoDesc> {
 
     Ok(IoDesc { fd: fd })
 }
+
+/// A counter that can be waited on, backed by an eventfd.
+pub struct EventFd {
+    eventfd: IoDesc
+}
+
+impl EventFd {
+    pub fn new() -> MioResult<EventFd> {
+        Ok(EventFd { eventfd: try!(eventfd()) })
+    }
+
+    pub fn wake(&self, count: u64) -> MioResult<()> {
+        let buf: [u8; 8] = unsafe { mem::transmute(count) };
+
+        write(&self.eventfd, &buf)
+            .map(|_| ())
+    }
+
+    pub fn reset(&self) -> MioResult<u64> {
+        let mut buf = [0u8; 8];
+
+        // Reading returns the counter and sets it back to zero
+        match read(&self.eventfd, &mut buf) {
+            Ok(_) => Ok(unsafe { mem::transmute(buf) }),
+            Err(e) => {
+                if e.is_would_block() {
+                    return Ok(0);
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    pub fn desc(&self) -> &IoDesc {
+        &self.eventfd
+    }
+}
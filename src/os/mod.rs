@@ -8,11 +8,14 @@ pub use self::kqueue::{Events, Selector};
 pub use self::posix::*;
 
 #[cfg(target_os = "linux")]
-pub use self::linux::Awakener;
+pub use self::linux::{Awakener, EventFd};
 
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 pub use self::posix::PipeAwakener as Awakener;
 
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub use self::posix::PipeEventFd as EventFd;
+
 #[cfg(windows)]
 pub use self::windows::*;
 
@@ -1,21 +1,341 @@
 use std::mem;
 use std::num::Int;
+use std::time::Duration;
+use libc;
 use error::{MioResult, MioError};
 use io::IoHandle;
 use net::{AddressFamily, SockAddr, IPv4Addr, SocketType};
-use net::SocketType::{Dgram, Stream};
+use net::SocketType::{Dgram, SeqPacket, Stream};
 use net::SockAddr::{InetAddr, UnixAddr};
 use net::AddressFamily::{Inet, Inet6, Unix};
+use os::event::Interest;
 pub use std::old_io::net::ip::IpAddr;
 
 mod nix {
     pub use nix::{c_int, NixError};
+    pub use nix::errno::Errno;
+    pub use nix::errno::from_i32 as errno_from_i32;
     pub use nix::fcntl::{Fd, O_NONBLOCK, O_CLOEXEC};
-    pub use nix::errno::EINPROGRESS;
+    pub use nix::errno::{EINPROGRESS, ECONNREFUSED};
     pub use nix::sys::socket::*;
     pub use nix::unistd::*;
 }
 
+// Bindings for the handful of calls that nix does not expose (yet)
+mod ffi {
+    use libc::{c_char, c_int, c_short, c_uint, c_ulong, c_void, gid_t, in_addr, pid_t, size_t, sockaddr, socklen_t, ssize_t, uid_t};
+
+    pub const F_GETFD: c_int = 1;
+    pub const F_SETFD: c_int = 2;
+    pub const F_GETFL: c_int = 3;
+    pub const F_SETFL: c_int = 4;
+
+    #[cfg(target_os = "linux")]
+    pub const F_DUPFD_CLOEXEC: c_int = 1030;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const F_DUPFD_CLOEXEC: c_int = 67;
+
+    pub const FD_CLOEXEC: c_int = 1;
+
+    #[cfg(target_os = "linux")]
+    pub const O_NONBLOCK: c_int = 0o4000;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const O_NONBLOCK: c_int = 0x0004;
+
+    #[cfg(target_os = "linux")]
+    pub const SOL_SOCKET: c_int = 1;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const SOL_SOCKET: c_int = 0xffff;
+
+    #[cfg(target_os = "linux")]
+    pub const SO_BINDTODEVICE: c_int = 25;
+
+    #[cfg(target_os = "linux")]
+    pub const SO_ERROR: c_int = 4;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const SO_ERROR: c_int = 0x1007;
+
+    #[cfg(target_os = "linux")]
+    pub const SO_INCOMING_CPU: c_int = 49;
+
+    #[cfg(target_os = "linux")]
+    pub const SO_RCVTIMEO: c_int = 20;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const SO_RCVTIMEO: c_int = 0x1006;
+
+    #[cfg(target_os = "linux")]
+    pub const SO_SNDTIMEO: c_int = 21;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const SO_SNDTIMEO: c_int = 0x1005;
+
+    #[cfg(target_os = "linux")]
+    pub const IFNAMSIZ: usize = 16;
+
+    pub const IPPROTO_IP: c_int = 0;
+    pub const IPPROTO_IPV6: c_int = 41;
+
+    #[cfg(target_os = "linux")]
+    pub const AF_INET6: c_int = 10;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const AF_INET6: c_int = 30;
+
+    #[cfg(target_os = "linux")]
+    pub const IP_TOS: c_int = 1;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const IP_TOS: c_int = 3;
+
+    #[cfg(target_os = "linux")]
+    pub const IPV6_TCLASS: c_int = 67;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const IPV6_TCLASS: c_int = 36;
+
+    #[cfg(target_os = "linux")]
+    pub const IP_PKTINFO: c_int = 8;
+
+    #[cfg(target_os = "linux")]
+    pub const MSG_CTRUNC: c_int = 0x8;
+
+    #[cfg(target_os = "linux")]
+    pub const MSG_DONTWAIT: c_int = 0x40;
+
+    #[cfg(target_os = "linux")]
+    pub const MSG_MORE: c_int = 0x8000;
+
+    #[cfg(target_os = "linux")]
+    pub const MSG_FASTOPEN: c_int = 0x20000000;
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct in_pktinfo {
+        pub ipi_ifindex: c_int,
+        pub ipi_spec_dst: in_addr,
+        pub ipi_addr: in_addr,
+    }
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct msghdr {
+        pub msg_name: *mut c_void,
+        pub msg_namelen: socklen_t,
+        pub msg_iov: *mut iovec,
+        pub msg_iovlen: size_t,
+        pub msg_control: *mut c_void,
+        pub msg_controllen: size_t,
+        pub msg_flags: c_int,
+    }
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct cmsghdr {
+        pub cmsg_len: size_t,
+        pub cmsg_level: c_int,
+        pub cmsg_type: c_int,
+    }
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct mmsghdr {
+        pub msg_hdr: msghdr,
+        pub msg_len: c_uint,
+    }
+
+    #[cfg(target_os = "linux")]
+    pub const IPPROTO_SCTP: c_int = 132;
+
+    #[cfg(target_os = "linux")]
+    pub const SCTP_SNDRCV: c_int = 1;
+
+    #[cfg(target_os = "linux")]
+    pub const SCTP_EVENTS: c_int = 11;
+
+    #[cfg(target_os = "linux")]
+    pub const MSG_EOR: c_int = 0x80;
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct sctp_sndrcvinfo {
+        pub sinfo_stream: u16,
+        pub sinfo_ssn: u16,
+        pub sinfo_flags: u16,
+        pub sinfo_ppid: u32,
+        pub sinfo_context: u32,
+        pub sinfo_timetolive: u32,
+        pub sinfo_tsn: u32,
+        pub sinfo_cumtsn: u32,
+        pub sinfo_assoc_id: i32,
+    }
+
+    // Only the leading fields, which every kernel with SCTP knows about.
+    // Shorter option values are accepted, the other events stay off.
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct sctp_event_subscribe {
+        pub sctp_data_io_event: u8,
+        pub sctp_association_event: u8,
+        pub sctp_address_event: u8,
+        pub sctp_send_failure_event: u8,
+        pub sctp_peer_error_event: u8,
+        pub sctp_shutdown_event: u8,
+        pub sctp_partial_delivery_event: u8,
+        pub sctp_adaptation_layer_event: u8,
+    }
+
+    #[cfg(target_os = "linux")]
+    extern {
+        pub fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int;
+        pub fn recvmsg(fd: c_int, msg: *mut msghdr, flags: c_int) -> ssize_t;
+        pub fn sendmsg(fd: c_int, msg: *const msghdr, flags: c_int) -> ssize_t;
+        pub fn recvmmsg(fd: c_int, msgvec: *mut mmsghdr, vlen: c_uint, flags: c_int,
+                        timeout: *mut c_void) -> c_int;
+        pub fn sendmmsg(fd: c_int, msgvec: *mut mmsghdr, vlen: c_uint, flags: c_int) -> c_int;
+    }
+
+    #[cfg(target_os = "linux")]
+    pub const SO_PEERCRED: c_int = 17;
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct ucred {
+        pub pid: pid_t,
+        pub uid: uid_t,
+        pub gid: gid_t,
+    }
+
+    // Maximum number of buffers a single readv or writev accepts
+    pub const IOV_MAX: usize = 1024;
+
+    #[cfg(target_os = "linux")]
+    pub const FIONREAD: c_ulong = 0x541b;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const FIONREAD: c_ulong = 0x4004667f;
+
+    #[cfg(target_os = "linux")]
+    pub const O_CLOEXEC: c_int = 0o2000000;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const O_CLOEXEC: c_int = 0x1000000;
+
+    pub const O_RDONLY: c_int = 0;
+
+    pub const SHUT_WR: c_int = 1;
+
+    pub const MSG_OOB: c_int = 1;
+    pub const MSG_PEEK: c_int = 2;
+
+    #[cfg(target_os = "linux")]
+    pub const IPPROTO_TCP: c_int = 6;
+
+    #[cfg(target_os = "linux")]
+    pub const TCP_INFO: c_int = 11;
+
+    #[cfg(target_os = "linux")]
+    pub const TCP_DEFER_ACCEPT: c_int = 9;
+
+    #[cfg(target_os = "linux")]
+    pub const TCP_FASTOPEN: c_int = 23;
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    pub struct tcp_info {
+        pub tcpi_state: u8,
+        pub tcpi_ca_state: u8,
+        pub tcpi_retransmits: u8,
+        pub tcpi_probes: u8,
+        pub tcpi_backoff: u8,
+        pub tcpi_options: u8,
+        pub tcpi_wscale: u8,
+        pub tcpi_rto: u32,
+        pub tcpi_ato: u32,
+        pub tcpi_snd_mss: u32,
+        pub tcpi_rcv_mss: u32,
+        pub tcpi_unacked: u32,
+        pub tcpi_sacked: u32,
+        pub tcpi_lost: u32,
+        pub tcpi_retrans: u32,
+        pub tcpi_fackets: u32,
+        pub tcpi_last_data_sent: u32,
+        pub tcpi_last_ack_sent: u32,
+        pub tcpi_last_data_recv: u32,
+        pub tcpi_last_ack_recv: u32,
+        pub tcpi_pmtu: u32,
+        pub tcpi_rcv_ssthresh: u32,
+        pub tcpi_rtt: u32,
+        pub tcpi_rttvar: u32,
+        pub tcpi_snd_ssthresh: u32,
+        pub tcpi_snd_cwnd: u32,
+        pub tcpi_advmss: u32,
+        pub tcpi_reordering: u32,
+        pub tcpi_rcv_rtt: u32,
+        pub tcpi_rcv_space: u32,
+        pub tcpi_total_retrans: u32,
+    }
+
+    #[cfg(target_os = "linux")]
+    pub const SO_OOBINLINE: c_int = 10;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const SO_OOBINLINE: c_int = 0x0100;
+
+    pub const POLLIN: c_short = 0x001;
+    pub const POLLPRI: c_short = 0x002;
+    pub const POLLOUT: c_short = 0x004;
+    pub const POLLERR: c_short = 0x008;
+    pub const POLLHUP: c_short = 0x010;
+
+    #[repr(C)]
+    pub struct iovec {
+        pub iov_base: *mut c_void,
+        pub iov_len: size_t,
+    }
+
+    #[repr(C)]
+    pub struct pollfd {
+        pub fd: c_int,
+        pub events: c_short,
+        pub revents: c_short,
+    }
+
+    #[cfg(target_os = "linux")]
+    #[allow(non_camel_case_types)]
+    pub type nfds_t = c_ulong;
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    #[allow(non_camel_case_types)]
+    pub type nfds_t = c_uint;
+
+    extern {
+        pub fn poll(fds: *mut pollfd, nfds: nfds_t, timeout: c_int) -> c_int;
+        pub fn readv(fd: c_int, iov: *const iovec, iovcnt: c_int) -> ssize_t;
+        pub fn writev(fd: c_int, iov: *const iovec, iovcnt: c_int) -> ssize_t;
+        pub fn open(path: *const c_char, flags: c_int, ...) -> c_int;
+        pub fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+        pub fn ioctl(fd: c_int, req: c_ulong, ...) -> c_int;
+        pub fn setsockopt(fd: c_int, level: c_int, name: c_int,
+                          val: *const c_void, len: socklen_t) -> c_int;
+        pub fn getsockopt(fd: c_int, level: c_int, name: c_int,
+                          val: *mut c_void, len: *mut socklen_t) -> c_int;
+        pub fn getsockname(fd: c_int, addr: *mut sockaddr, len: *mut socklen_t) -> c_int;
+        pub fn recv(fd: c_int, buf: *mut c_void, len: size_t, flags: c_int) -> ssize_t;
+        pub fn shutdown(fd: c_int, how: c_int) -> c_int;
+        pub fn send(fd: c_int, buf: *const c_void, len: size_t, flags: c_int) -> ssize_t;
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    extern {
+        pub fn getpeereid(fd: c_int, uid: *mut uid_t, gid: *mut gid_t) -> c_int;
+    }
+}
+
 /*
  *
  * ===== Awakener =====
@@ -59,6 +379,67 @@ impl PipeAwakener {
     }
 }
 
+/// A counter that can be waited on, for platforms without eventfd. Every
+/// `wake` writes the count as 8 bytes to a pipe, and `reset` sums up whatever
+/// is in the pipe.
+pub struct PipeEventFd {
+    reader: IoDesc,
+    writer: IoDesc
+}
+
+impl PipeEventFd {
+    pub fn new() -> MioResult<PipeEventFd> {
+        let (rd, wr) = try!(pipe());
+
+        Ok(PipeEventFd {
+            reader: rd,
+            writer: wr
+        })
+    }
+
+    pub fn wake(&self, count: u64) -> MioResult<()> {
+        let buf: [u8; 8] = unsafe { mem::transmute(count) };
+
+        // Writes smaller than PIPE_BUF are atomic, so the reader always sees
+        // whole counts
+        write(&self.writer, &buf)
+            .map(|_| ())
+    }
+
+    pub fn reset(&self) -> MioResult<u64> {
+        let mut buf = [0u8; 128];
+        let mut total = 0u64;
+
+        loop {
+            match read(&self.reader, &mut buf) {
+                Ok(n) => {
+                    for chunk in buf[..n].chunks(8) {
+                        let mut count = [0u8; 8];
+
+                        for (dst, src) in count.iter_mut().zip(chunk.iter()) {
+                            *dst = *src;
+                        }
+
+                        let count: u64 = unsafe { mem::transmute(count) };
+                        total += count;
+                    }
+                }
+                Err(e) => {
+                    if e.is_would_block() {
+                        return Ok(total);
+                    }
+
+                    return Err(e);
+                }
+            }
+        }
+    }
+
+    pub fn desc(&self) -> &IoDesc {
+        &self.reader
+    }
+}
+
 /// Represents the OS's handle to the IO instance. In this case, it is the file
 /// descriptor.
 #[derive(Debug)]
@@ -70,108 +451,1226 @@ impl IoHandle for IoDesc {
     fn desc(&self) -> &IoDesc {
         self
     }
-}
+}
+
+impl Drop for IoDesc {
+    fn drop(&mut self) {
+        let _ = nix::close(self.fd);
+    }
+}
+
+/*
+ *
+ * ===== Pipes =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub fn pipe() -> MioResult<(IoDesc, IoDesc)> {
+    let (rd, wr) = try!(nix::pipe2(nix::O_NONBLOCK | nix::O_CLOEXEC)
+                        .map_err(MioError::from_nix_error));
+
+    Ok((IoDesc { fd: rd }, IoDesc { fd: wr }))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pipe() -> MioResult<(IoDesc, IoDesc)> {
+    let (rd, wr) = try!(nix::pipe().map_err(MioError::from_nix_error));
+    let (rd, wr) = (IoDesc { fd: rd }, IoDesc { fd: wr });
+
+    // pipe2 is not available, fall back to setting the flags by hand
+    for desc in [&rd, &wr].iter() {
+        try!(set_nonblock(*desc, true));
+        try!(set_cloexec(*desc));
+    }
+
+    Ok((rd, wr))
+}
+
+/// Duplicates `fd` into a new close-on-exec descriptor, which can then be
+/// owned, and closed, independently of `fd`.
+pub fn dup(fd: libc::c_int) -> MioResult<IoDesc> {
+    let res = unsafe { ffi::fcntl(fd, ffi::F_DUPFD_CLOEXEC, 0) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(IoDesc { fd: res })
+}
+
+/*
+ *
+ * ===== Sockets =====
+ *
+ */
+
+pub fn socket(af: AddressFamily, sock_type: SocketType) -> MioResult<IoDesc> {
+    let family = match af {
+        Inet  => nix::AF_INET,
+        Inet6 => nix::AF_INET6,
+        Unix  => nix::AF_UNIX
+    };
+
+    let socket_type = match sock_type {
+        Dgram     => nix::SOCK_DGRAM,
+        Stream    => nix::SOCK_STREAM,
+        SeqPacket => nix::SOCK_SEQPACKET
+    };
+
+    new_socket(family, socket_type)
+}
+
+// Every socket is created non-blocking and close-on-exec. The latter ensures
+// that fds are not leaked into child processes when the application forks and
+// execs.
+#[cfg(target_os = "linux")]
+fn new_socket(family: nix::AddressFamily, ty: nix::SockType) -> MioResult<IoDesc> {
+    Ok(IoDesc {
+        fd: try!(nix::socket(family, ty, nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC)
+                    .map_err(MioError::from_nix_error))
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn new_socket(family: nix::AddressFamily, ty: nix::SockType) -> MioResult<IoDesc> {
+    let desc = IoDesc {
+        fd: try!(nix::socket(family, ty, nix::SockFlag::empty())
+                    .map_err(MioError::from_nix_error))
+    };
+
+    // The flags cannot be set atomically on this platform, set them before
+    // the socket is handed out.
+    try!(set_nonblock(&desc, true));
+    try!(set_cloexec(&desc));
+
+    Ok(desc)
+}
+
+pub fn connect(io: &IoDesc, addr: &SockAddr) -> MioResult<bool> {
+    match nix::connect(io.fd, &from_sockaddr(addr)) {
+        Ok(_) => Ok(true),
+        Err(e) => {
+            match e {
+                nix::NixError::Sys(nix::EINPROGRESS) => Ok(false),
+                _ => Err(MioError::from_nix_error(e))
+            }
+        }
+    }
+}
+
+pub fn bind(io: &IoDesc, addr: &SockAddr) -> MioResult<()> {
+    nix::bind(io.fd, &from_sockaddr(addr))
+        .map_err(MioError::from_nix_error)
+}
+
+/// Returns true if `addr` names a unix socket file that no process is
+/// listening on anymore. Anything else at the path, including a socket with a
+/// live listener, is reported as not stale.
+pub fn is_stale_unix_socket(addr: &SockAddr) -> MioResult<bool> {
+    use std::old_io::{fs, FileType};
+
+    let path = match *addr {
+        UnixAddr(ref path) => path,
+        _ => return Ok(false)
+    };
+
+    // old_io has no dedicated file type for sockets and reports them as
+    // Unknown. Never touch regular files, directories or symlinks.
+    match fs::lstat(path) {
+        Ok(stat) if stat.kind == FileType::Unknown => {}
+        _ => return Ok(false)
+    }
+
+    // Only a refused connection proves nobody owns the path. A connection
+    // that succeeds or is still in progress (or a full backlog) means a
+    // server is alive.
+    let probe = try!(socket(Unix, Stream));
+
+    match nix::connect(probe.fd, &from_sockaddr(addr)) {
+        Err(nix::NixError::Sys(nix::ECONNREFUSED)) => Ok(true),
+        _ => Ok(false)
+    }
+}
+
+/// Shuts down the writing half of the connection. The peer reads EOF once
+/// it has received everything written before.
+pub fn shutdown_write(io: &IoDesc) -> MioResult<()> {
+    if unsafe { ffi::shutdown(io.fd, ffi::SHUT_WR) } < 0 {
+        return Err(last_error());
+    }
+
+    Ok(())
+}
+
+pub fn listen(io: &IoDesc, backlog: usize) -> MioResult<()> {
+    nix::listen(io.fd, backlog)
+        .map_err(MioError::from_nix_error)
+}
+
+/// Accepts a pending connection. The returned socket is always non-blocking
+/// and close-on-exec.
+#[cfg(target_os = "linux")]
+pub fn accept(io: &IoDesc) -> MioResult<IoDesc> {
+    // accept4 sets both flags atomically, saving two fcntl calls per accept
+    Ok(IoDesc {
+        fd: try!(nix::accept4(io.fd, nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC)
+                     .map_err(MioError::from_nix_error))
+    })
+}
+
+/// Accepts a pending connection. The returned socket is always non-blocking
+/// and close-on-exec.
+#[cfg(not(target_os = "linux"))]
+pub fn accept(io: &IoDesc) -> MioResult<IoDesc> {
+    let desc = IoDesc {
+        fd: try!(nix::accept(io.fd).map_err(MioError::from_nix_error))
+    };
+
+    // accept4 is not available, fall back to setting the flags by hand
+    try!(set_nonblock(&desc, true));
+    try!(set_cloexec(&desc));
+
+    Ok(desc)
+}
+
+/// Accepts a pending connection, like `accept`, but keeps accept loops from
+/// spinning once the process runs out of file descriptors.
+///
+/// When that happens, the pending connection cannot be accepted and the
+/// listener stays readable forever. To make progress, the spare descriptor is
+/// released, the connection accepted and immediately closed, and the spare
+/// reacquired. The `ResourceLimit` error is still returned so that the caller
+/// can report the exhaustion.
+///
+/// Connections that were aborted while waiting in the backlog are skipped,
+/// see `skip_aborted`.
+pub fn accept_or_shed(io: &IoDesc, spare: &mut Option<IoDesc>) -> MioResult<IoDesc> {
+    match skip_aborted(|| accept(io)) {
+        Err(e) => {
+            if e.is_resource_limit() {
+                if let Some(fd) = spare.take() {
+                    drop(fd);
+                    let _ = accept(io);
+                    *spare = reserve_fd().ok();
+                }
+            }
+
+            Err(e)
+        }
+        res => res
+    }
+}
+
+/// Calls `accept` until it returns something other than a connection that
+/// failed while waiting to be accepted (`ECONNABORTED`, or `EPROTO` on some
+/// systems). Such an error only concerns that one connection, so the next
+/// pending connection is accepted instead, or `WouldBlock` returned if
+/// there is none.
+///
+/// `EPROTO` only means an aborted connection when returned by accept, so
+/// it is recognized here rather than mapped by `MioError::from_nix_error`. A readable listener can therefore still report
+/// `WouldBlock`.
+pub fn skip_aborted<F: FnMut() -> MioResult<IoDesc>>(mut accept: F) -> MioResult<IoDesc> {
+    loop {
+        match accept() {
+            Err(ref e) if is_aborted(e) => {
+                debug!("skipping aborted connection");
+            }
+            res => return res
+        }
+    }
+}
+
+fn is_aborted(err: &MioError) -> bool {
+    match err.sys() {
+        Some(&nix::NixError::Sys(nix::Errno::EPROTO)) => true,
+        _ => err.is_connection_aborted()
+    }
+}
+
+/// Opens a descriptor that does nothing, to be held in reserve for when the
+/// process runs out of file descriptors.
+pub fn reserve_fd() -> MioResult<IoDesc> {
+    let fd = unsafe {
+        ffi::open(b"/dev/null\0".as_ptr() as *const libc::c_char,
+                  ffi::O_RDONLY | ffi::O_CLOEXEC)
+    };
+
+    if fd < 0 {
+        return Err(last_error());
+    }
+
+    Ok(IoDesc { fd: fd })
+}
+
+#[inline]
+pub fn recvfrom(io: &IoDesc, buf: &mut [u8]) -> MioResult<(usize, SockAddr)> {
+    match nix::recvfrom(io.fd, buf).map_err(MioError::from_nix_error) {
+        Ok((cnt, addr)) => Ok((cnt, to_sockaddr(&addr))),
+        Err(e) => Err(e)
+    }
+}
+
+#[inline]
+pub fn sendto(io: &IoDesc, buf: &[u8], tgt: &SockAddr) -> MioResult<usize> {
+    let res = try!(nix::sendto(io.fd, buf, &from_sockaddr(tgt), nix::MSG_DONTWAIT).map_err(MioError::from_nix_error));
+    Ok(res)
+}
+
+/// Sends every `(data, target)` pair as a datagram with a single sendmmsg
+/// call. Returns the number of datagrams sent, which is less than given if
+/// the send buffer fills up. IPv4 only.
+#[cfg(target_os = "linux")]
+pub fn send_mmsg(io: &IoDesc, packets: &[(&[u8], SockAddr)]) -> MioResult<usize> {
+    if packets.is_empty() {
+        return Ok(0);
+    }
+
+    let mut addrs: Vec<nix::sockaddr_in> = Vec::with_capacity(packets.len());
+    let mut iovs: Vec<ffi::iovec> = Vec::with_capacity(packets.len());
+
+    for &(data, ref tgt) in packets.iter() {
+        match from_sockaddr(tgt) {
+            nix::SockAddr::SockIpV4(sin) => addrs.push(sin),
+            _ => return Err(MioError::unsupported())
+        }
+
+        iovs.push(ffi::iovec {
+            iov_base: data.as_ptr() as *mut libc::c_void,
+            iov_len: data.len() as libc::size_t,
+        });
+    }
+
+    let mut msgs: Vec<ffi::mmsghdr> = Vec::with_capacity(packets.len());
+
+    for (addr, iov) in addrs.iter_mut().zip(iovs.iter_mut()) {
+        msgs.push(ffi::mmsghdr {
+            msg_hdr: ffi::msghdr {
+                msg_name: addr as *mut nix::sockaddr_in as *mut libc::c_void,
+                msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+                msg_iov: iov,
+                msg_iovlen: 1,
+                msg_control: 0 as *mut libc::c_void,
+                msg_controllen: 0,
+                msg_flags: 0,
+            },
+            msg_len: 0,
+        });
+    }
+
+    let res = unsafe {
+        ffi::sendmmsg(io.fd, msgs.as_mut_ptr(), msgs.len() as libc::c_uint, ffi::MSG_DONTWAIT)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(res as usize)
+}
+
+/// Without sendmmsg, sends the datagrams one at a time, stopping once the
+/// send buffer is full.
+#[cfg(not(target_os = "linux"))]
+pub fn send_mmsg(io: &IoDesc, packets: &[(&[u8], SockAddr)]) -> MioResult<usize> {
+    let mut sent = 0;
+
+    for &(data, ref tgt) in packets.iter() {
+        match sendto(io, data, tgt) {
+            Ok(_) => sent += 1,
+            Err(ref e) if e.is_would_block() && sent > 0 => break,
+            Err(e) => return Err(e)
+        }
+    }
+
+    Ok(sent)
+}
+
+/// Receives up to one datagram into each of `bufs` with a single recvmmsg
+/// call, returning the length and source of each datagram received. IPv4
+/// only.
+#[cfg(target_os = "linux")]
+pub fn recv_mmsg(io: &IoDesc, bufs: &mut [&mut [u8]]) -> MioResult<Vec<(usize, SockAddr)>> {
+    if bufs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut addrs: Vec<nix::sockaddr_in> = range(0, bufs.len())
+        .map(|_| unsafe { mem::zeroed() })
+        .collect();
+
+    let mut iovs: Vec<ffi::iovec> = bufs.iter_mut().map(|buf| {
+        ffi::iovec {
+            iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+            iov_len: buf.len() as libc::size_t,
+        }
+    }).collect();
+
+    let mut msgs: Vec<ffi::mmsghdr> = Vec::with_capacity(bufs.len());
+
+    for (addr, iov) in addrs.iter_mut().zip(iovs.iter_mut()) {
+        msgs.push(ffi::mmsghdr {
+            msg_hdr: ffi::msghdr {
+                msg_name: addr as *mut nix::sockaddr_in as *mut libc::c_void,
+                msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+                msg_iov: iov,
+                msg_iovlen: 1,
+                msg_control: 0 as *mut libc::c_void,
+                msg_controllen: 0,
+                msg_flags: 0,
+            },
+            msg_len: 0,
+        });
+    }
+
+    let res = unsafe {
+        ffi::recvmmsg(io.fd, msgs.as_mut_ptr(), msgs.len() as libc::c_uint,
+                      ffi::MSG_DONTWAIT, 0 as *mut libc::c_void)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(range(0, res as usize).map(|i| {
+        (msgs[i].msg_len as usize, to_sockaddr(&nix::SockAddr::SockIpV4(addrs[i])))
+    }).collect())
+}
+
+/// Without recvmmsg, receives the datagrams one at a time, stopping once no
+/// more are queued.
+#[cfg(not(target_os = "linux"))]
+pub fn recv_mmsg(io: &IoDesc, bufs: &mut [&mut [u8]]) -> MioResult<Vec<(usize, SockAddr)>> {
+    let mut received = Vec::new();
+
+    for buf in bufs.iter_mut() {
+        match recvfrom(io, *buf) {
+            Ok(res) => received.push(res),
+            Err(ref e) if e.is_would_block() && !received.is_empty() => break,
+            Err(e) => return Err(e)
+        }
+    }
+
+    Ok(received)
+}
+
+/// Enables `IP_PKTINFO`, which makes the kernel attach the destination
+/// address of every received datagram, see `recv_from_pktinfo`.
+#[cfg(target_os = "linux")]
+pub fn set_recv_pktinfo(io: &IoDesc, val: bool) -> MioResult<()> {
+    setsockopt_int(io, ffi::IPPROTO_IP, ffi::IP_PKTINFO, val as libc::c_int)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_recv_pktinfo(_io: &IoDesc, _val: bool) -> MioResult<()> {
+    Err(MioError::unsupported())
+}
+
+// Large enough for a single in_pktinfo control message. Made of u64 words so
+// that the buffer is aligned for cmsghdr.
+#[cfg(target_os = "linux")]
+const PKTINFO_CONTROL_WORDS: usize = 8;
+
+// Rounds `len` up to the alignment of control message headers and data, as
+// CMSG_ALIGN does
+#[cfg(target_os = "linux")]
+fn cmsg_align(len: usize) -> usize {
+    let align = mem::size_of::<libc::size_t>();
+    (len + align - 1) & !(align - 1)
+}
+
+// Walks the control messages of `msg`, the way CMSG_FIRSTHDR / CMSG_NXTHDR
+// do, and returns the address of the data of the first one with the given
+// level and type that holds at least `size` bytes.
+#[cfg(target_os = "linux")]
+unsafe fn find_cmsg(msg: &ffi::msghdr, level: libc::c_int, ty: libc::c_int, size: usize) -> Option<usize> {
+    let hdr_len = cmsg_align(mem::size_of::<ffi::cmsghdr>());
+    let start = msg.msg_control as usize;
+    let end = start + msg.msg_controllen as usize;
+    let mut cur = start;
+
+    while cur + hdr_len <= end {
+        let cmsg = &*(cur as *const ffi::cmsghdr);
+        let len = cmsg.cmsg_len as usize;
+
+        // A header that does not even cover itself ends the list
+        if len < hdr_len || cur + len > end {
+            break;
+        }
+
+        if cmsg.cmsg_level == level && cmsg.cmsg_type == ty && len >= hdr_len + size {
+            return Some(cur + hdr_len);
+        }
+
+        cur += cmsg_align(len);
+    }
+
+    None
+}
+
+// Returns the destination address of the first IP_PKTINFO message.
+#[cfg(target_os = "linux")]
+unsafe fn pktinfo_addr(msg: &ffi::msghdr) -> Option<IpAddr> {
+    find_cmsg(msg, ffi::IPPROTO_IP, ffi::IP_PKTINFO, mem::size_of::<ffi::in_pktinfo>()).map(|data| {
+        let info = &*(data as *const ffi::in_pktinfo);
+        u32be_to_ipv4(info.ipi_addr.s_addr)
+    })
+}
+
+/// Receives a datagram along with its source address and the local address
+/// it was sent to. The local address is taken from the `IP_PKTINFO` control
+/// message, which requires `set_recv_pktinfo`. Without it, or if the control
+/// message was truncated, the address the socket is bound to is returned.
+/// IPv4 only.
+#[cfg(target_os = "linux")]
+pub fn recv_from_pktinfo(io: &IoDesc, buf: &mut [u8]) -> MioResult<(usize, SockAddr, SockAddr)> {
+    if try!(socket_family(io)) != nix::AF_INET as libc::c_int {
+        return Err(MioError::unsupported());
+    }
+
+    let mut src: nix::sockaddr_in = unsafe { mem::zeroed() };
+    let mut control = [0u64; PKTINFO_CONTROL_WORDS];
+
+    let mut iov = ffi::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len() as libc::size_t,
+    };
+
+    let mut msg = ffi::msghdr {
+        msg_name: &mut src as *mut nix::sockaddr_in as *mut libc::c_void,
+        msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+        msg_iov: &mut iov,
+        msg_iovlen: 1,
+        msg_control: control.as_mut_ptr() as *mut libc::c_void,
+        msg_controllen: mem::size_of_val(&control) as libc::size_t,
+        msg_flags: 0,
+    };
+
+    let res = unsafe { ffi::recvmsg(io.fd, &mut msg, 0) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    // The socket is bound, so this also provides the local port
+    let mut dst = try!(getsockname(io));
+
+    if msg.msg_flags & ffi::MSG_CTRUNC == 0 {
+        if let Some(ip) = unsafe { pktinfo_addr(&msg) } {
+            if let InetAddr(_, port) = dst {
+                dst = InetAddr(ip, port);
+            }
+        }
+    }
+
+    Ok((res as usize, to_sockaddr(&nix::SockAddr::SockIpV4(src)), dst))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn recv_from_pktinfo(_io: &IoDesc, _buf: &mut [u8]) -> MioResult<(usize, SockAddr, SockAddr)> {
+    Err(MioError::unsupported())
+}
+
+/// Sends a datagram to `tgt` with `local` as its source address, which must
+/// be one of the host's addresses. Its port is ignored: the datagram always
+/// leaves from the port the socket is bound to. IPv4 only.
+#[cfg(target_os = "linux")]
+pub fn send_from(io: &IoDesc, buf: &[u8], tgt: &SockAddr, local: &SockAddr) -> MioResult<usize> {
+    let mut dst = match from_sockaddr(tgt) {
+        nix::SockAddr::SockIpV4(sin) => sin,
+        _ => return Err(MioError::unsupported())
+    };
+
+    let spec_dst = match *local {
+        InetAddr(IPv4Addr(a, b, c, d), _) => ipv4_to_inaddr(a, b, c, d),
+        _ => return Err(MioError::unsupported())
+    };
+
+    let hdr_len = cmsg_align(mem::size_of::<ffi::cmsghdr>());
+    let mut control = [0u64; PKTINFO_CONTROL_WORDS];
+
+    unsafe {
+        let cmsg = control.as_mut_ptr() as *mut ffi::cmsghdr;
+
+        (*cmsg).cmsg_len = (hdr_len + mem::size_of::<ffi::in_pktinfo>()) as libc::size_t;
+        (*cmsg).cmsg_level = ffi::IPPROTO_IP;
+        (*cmsg).cmsg_type = ffi::IP_PKTINFO;
+
+        // A zero interface index lets the routing table pick the interface
+        let info = (cmsg as usize + hdr_len) as *mut ffi::in_pktinfo;
+        (*info).ipi_ifindex = 0;
+        (*info).ipi_spec_dst.s_addr = spec_dst.s_addr;
+        (*info).ipi_addr.s_addr = 0;
+    }
+
+    let mut iov = ffi::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len() as libc::size_t,
+    };
+
+    let msg = ffi::msghdr {
+        msg_name: &mut dst as *mut nix::sockaddr_in as *mut libc::c_void,
+        msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+        msg_iov: &mut iov,
+        msg_iovlen: 1,
+        msg_control: control.as_mut_ptr() as *mut libc::c_void,
+        msg_controllen: (hdr_len + cmsg_align(mem::size_of::<ffi::in_pktinfo>())) as libc::size_t,
+        msg_flags: 0,
+    };
+
+    let res = unsafe { ffi::sendmsg(io.fd, &msg, ffi::MSG_DONTWAIT) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(res as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn send_from(_io: &IoDesc, _buf: &[u8], _tgt: &SockAddr, _local: &SockAddr) -> MioResult<usize> {
+    Err(MioError::unsupported())
+}
+
+// Large enough for a single sctp_sndrcvinfo control message
+#[cfg(target_os = "linux")]
+const SCTP_CONTROL_WORDS: usize = 8;
+
+/// Creates an IPv4 SCTP socket, one-to-one style, which carries a single
+/// association and is used like a TCP socket, or one-to-many style, which
+/// carries associations with any number of peers. The socket reports the
+/// stream of every received message, see `sctp_recv`.
+#[cfg(target_os = "linux")]
+pub fn sctp_socket(one_to_many: bool) -> MioResult<IoDesc> {
+    let ty = if one_to_many { nix::SOCK_SEQPACKET } else { nix::SOCK_STREAM };
+    let flags = (nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC).bits();
+
+    let fd = unsafe { ffi::socket(nix::AF_INET as libc::c_int, ty as libc::c_int | flags, ffi::IPPROTO_SCTP) };
+
+    if fd < 0 {
+        // The sctp module is not loaded, or not built
+        if nix::Errno::last() == nix::Errno::EPROTONOSUPPORT {
+            return Err(MioError::unsupported());
+        }
+
+        return Err(last_error());
+    }
+
+    let desc = IoDesc { fd: fd };
+
+    // Without it, the kernel does not attach the sctp_sndrcvinfo
+    let mut events: ffi::sctp_event_subscribe = unsafe { mem::zeroed() };
+    events.sctp_data_io_event = 1;
+
+    try!(setsockopt_raw(&desc, ffi::IPPROTO_SCTP, ffi::SCTP_EVENTS,
+                        &events as *const ffi::sctp_event_subscribe as *const libc::c_void,
+                        mem::size_of::<ffi::sctp_event_subscribe>() as libc::socklen_t));
+
+    Ok(desc)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sctp_socket(_one_to_many: bool) -> MioResult<IoDesc> {
+    Err(MioError::unsupported())
+}
+
+/// Sends `buf` as a single message on `stream`. One-to-many sockets pass
+/// the peer in `tgt`, which sets up an association with it if there is none
+/// yet; connected one-to-one sockets pass `None`.
+#[cfg(target_os = "linux")]
+pub fn sctp_send(io: &IoDesc, buf: &[u8], stream: u16, tgt: Option<&SockAddr>) -> MioResult<usize> {
+    let mut dst = match tgt.map(from_sockaddr) {
+        Some(nix::SockAddr::SockIpV4(sin)) => Some(sin),
+        Some(_) => return Err(MioError::unsupported()),
+        None => None
+    };
+
+    let hdr_len = cmsg_align(mem::size_of::<ffi::cmsghdr>());
+    let mut control = [0u64; SCTP_CONTROL_WORDS];
+
+    unsafe {
+        let cmsg = control.as_mut_ptr() as *mut ffi::cmsghdr;
+
+        (*cmsg).cmsg_len = (hdr_len + mem::size_of::<ffi::sctp_sndrcvinfo>()) as libc::size_t;
+        (*cmsg).cmsg_level = ffi::IPPROTO_SCTP;
+        (*cmsg).cmsg_type = ffi::SCTP_SNDRCV;
+
+        // The rest stays zeroed, which means an ordered message
+        let info = (cmsg as usize + hdr_len) as *mut ffi::sctp_sndrcvinfo;
+        (*info).sinfo_stream = stream;
+    }
+
+    let mut iov = ffi::iovec {
+        iov_base: buf.as_ptr() as *mut libc::c_void,
+        iov_len: buf.len() as libc::size_t,
+    };
+
+    let (name, namelen) = match dst {
+        Some(ref mut sin) => (sin as *mut nix::sockaddr_in as *mut libc::c_void,
+                              mem::size_of::<nix::sockaddr_in>() as libc::socklen_t),
+        None => (0 as *mut libc::c_void, 0)
+    };
+
+    let msg = ffi::msghdr {
+        msg_name: name,
+        msg_namelen: namelen,
+        msg_iov: &mut iov,
+        msg_iovlen: 1,
+        msg_control: control.as_mut_ptr() as *mut libc::c_void,
+        msg_controllen: (hdr_len + cmsg_align(mem::size_of::<ffi::sctp_sndrcvinfo>())) as libc::size_t,
+        msg_flags: 0,
+    };
+
+    let res = unsafe { ffi::sendmsg(io.fd, &msg, 0) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(res as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sctp_send(_io: &IoDesc, _buf: &[u8], _stream: u16, _tgt: Option<&SockAddr>) -> MioResult<usize> {
+    Err(MioError::unsupported())
+}
+
+/// Receives a message, or the next part of one that did not fit into the
+/// previous buffer. Returns the number of bytes received, the stream the
+/// message arrived on, the peer, and whether this completes the message.
+#[cfg(target_os = "linux")]
+pub fn sctp_recv(io: &IoDesc, buf: &mut [u8]) -> MioResult<(usize, u16, SockAddr, bool)> {
+    let mut src: nix::sockaddr_in = unsafe { mem::zeroed() };
+    let mut control = [0u64; SCTP_CONTROL_WORDS];
+
+    let mut iov = ffi::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len() as libc::size_t,
+    };
+
+    let mut msg = ffi::msghdr {
+        msg_name: &mut src as *mut nix::sockaddr_in as *mut libc::c_void,
+        msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+        msg_iov: &mut iov,
+        msg_iovlen: 1,
+        msg_control: control.as_mut_ptr() as *mut libc::c_void,
+        msg_controllen: mem::size_of_val(&control) as libc::size_t,
+        msg_flags: 0,
+    };
+
+    let res = unsafe { ffi::recvmsg(io.fd, &mut msg, 0) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    // Messages cannot be empty, so reading nothing means that the peer shut
+    // the association down
+    if res == 0 {
+        return Err(MioError::eof());
+    }
+
+    let stream = unsafe {
+        find_cmsg(&msg, ffi::IPPROTO_SCTP, ffi::SCTP_SNDRCV, mem::size_of::<ffi::sctp_sndrcvinfo>())
+            .map(|data| (*(data as *const ffi::sctp_sndrcvinfo)).sinfo_stream)
+            .unwrap_or(0)
+    };
+
+    let complete = msg.msg_flags & ffi::MSG_EOR != 0;
+
+    Ok((res as usize, stream, to_sockaddr(&nix::SockAddr::SockIpV4(src)), complete))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sctp_recv(_io: &IoDesc, _buf: &mut [u8]) -> MioResult<(usize, u16, SockAddr, bool)> {
+    Err(MioError::unsupported())
+}
+
+#[inline]
+pub fn read(io: &IoDesc, dst: &mut [u8]) -> MioResult<usize> {
+    let res = try!(nix::read(io.fd, dst).map_err(MioError::from_nix_error));
+
+    if res == 0 {
+        return Err(MioError::eof());
+    }
+
+    Ok(res)
+}
+
+/// Reads a single datagram. Unlike `read`, a return value of zero is not
+/// EOF: it is an empty datagram.
+pub fn read_datagram(io: &IoDesc, dst: &mut [u8]) -> MioResult<usize> {
+    nix::read(io.fd, dst).map_err(MioError::from_nix_error)
+}
+
+/// Reads into `first` and, once it is full, into `second`, in a single
+/// system call.
+pub fn read_split(io: &IoDesc, first: &mut [u8], second: &mut [u8]) -> MioResult<usize> {
+    let iov = [
+        ffi::iovec { iov_base: first.as_mut_ptr() as *mut libc::c_void, iov_len: first.len() as libc::size_t },
+        ffi::iovec { iov_base: second.as_mut_ptr() as *mut libc::c_void, iov_len: second.len() as libc::size_t },
+    ];
+
+    let res = unsafe { ffi::readv(io.fd, iov.as_ptr(), iov.len() as libc::c_int) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    if res == 0 {
+        return Err(MioError::eof());
+    }
+
+    Ok(res as usize)
+}
+
+/// Writes `src` with `MSG_MORE`, telling the kernel that more data follows:
+/// it is held back until a write without the flag, instead of going out as
+/// a partial segment or datagram.
+#[cfg(target_os = "linux")]
+pub fn send_more(io: &IoDesc, src: &[u8]) -> MioResult<usize> {
+    let res = unsafe {
+        ffi::send(io.fd, src.as_ptr() as *const libc::c_void, src.len() as libc::size_t, ffi::MSG_MORE)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(res as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn send_more(_io: &IoDesc, _src: &[u8]) -> MioResult<usize> {
+    Err(MioError::unsupported())
+}
+
+/// Like `send_more`, for an unconnected datagram socket. IPv4 only.
+#[cfg(target_os = "linux")]
+pub fn sendto_more(io: &IoDesc, src: &[u8], tgt: &SockAddr) -> MioResult<usize> {
+    let mut dst = match from_sockaddr(tgt) {
+        nix::SockAddr::SockIpV4(sin) => sin,
+        _ => return Err(MioError::unsupported())
+    };
+
+    let mut iov = ffi::iovec {
+        iov_base: src.as_ptr() as *mut libc::c_void,
+        iov_len: src.len() as libc::size_t,
+    };
+
+    let msg = ffi::msghdr {
+        msg_name: &mut dst as *mut nix::sockaddr_in as *mut libc::c_void,
+        msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+        msg_iov: &mut iov,
+        msg_iovlen: 1,
+        msg_control: 0 as *mut libc::c_void,
+        msg_controllen: 0,
+        msg_flags: 0,
+    };
+
+    let res = unsafe { ffi::sendmsg(io.fd, &msg, ffi::MSG_MORE | ffi::MSG_DONTWAIT) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(res as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn sendto_more(_io: &IoDesc, _src: &[u8], _tgt: &SockAddr) -> MioResult<usize> {
+    Err(MioError::unsupported())
+}
+
+/// Starts connecting to `tgt` with `src` in the SYN, using TCP Fast Open.
+/// Returns the number of bytes queued, or `None` if the connection is in
+/// progress without them: with no cookie for the server yet, the kernel asks
+/// for one and sends nothing, so the data has to be written once the socket
+/// is writable. IPv4 only.
+#[cfg(target_os = "linux")]
+pub fn connect_fastopen(io: &IoDesc, src: &[u8], tgt: &SockAddr) -> MioResult<Option<usize>> {
+    let mut dst = match from_sockaddr(tgt) {
+        nix::SockAddr::SockIpV4(sin) => sin,
+        _ => return Err(MioError::unsupported())
+    };
+
+    let mut iov = ffi::iovec {
+        iov_base: src.as_ptr() as *mut libc::c_void,
+        iov_len: src.len() as libc::size_t,
+    };
+
+    let msg = ffi::msghdr {
+        msg_name: &mut dst as *mut nix::sockaddr_in as *mut libc::c_void,
+        msg_namelen: mem::size_of::<nix::sockaddr_in>() as libc::socklen_t,
+        msg_iov: &mut iov,
+        msg_iovlen: 1,
+        msg_control: 0 as *mut libc::c_void,
+        msg_controllen: 0,
+        msg_flags: 0,
+    };
+
+    let res = unsafe { ffi::sendmsg(io.fd, &msg, ffi::MSG_FASTOPEN) };
+
+    if res < 0 {
+        if nix::Errno::last() == nix::EINPROGRESS {
+            return Ok(None);
+        }
+
+        return Err(last_error());
+    }
+
+    Ok(Some(res as usize))
+}
+
+/// Without Fast Open, connects and writes `src` only if the connection
+/// completes right away, which is common on localhost.
+#[cfg(not(target_os = "linux"))]
+pub fn connect_fastopen(io: &IoDesc, src: &[u8], tgt: &SockAddr) -> MioResult<Option<usize>> {
+    if !try!(connect(io, tgt)) {
+        return Ok(None);
+    }
+
+    let res = unsafe {
+        ffi::send(io.fd, src.as_ptr() as *const libc::c_void, src.len() as libc::size_t, 0)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(Some(res as usize))
+}
+
+/// Enables TCP Fast Open on a listener, with at most `qlen` connections
+/// waiting for their handshake to complete after their data was accepted.
+#[cfg(target_os = "linux")]
+pub fn set_fastopen(io: &IoDesc, qlen: u32) -> MioResult<()> {
+    setsockopt_int(io, ffi::IPPROTO_TCP, ffi::TCP_FASTOPEN, qlen as libc::c_int)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_fastopen(_io: &IoDesc, _qlen: u32) -> MioResult<()> {
+    Err(MioError::unsupported())
+}
+
+/// Reads into `dst` without removing the data from the socket's receive
+/// buffer, so the next read returns the same bytes.
+pub fn peek(io: &IoDesc, dst: &mut [u8]) -> MioResult<usize> {
+    let res = unsafe {
+        ffi::recv(io.fd, dst.as_mut_ptr() as *mut libc::c_void, dst.len() as libc::size_t, ffi::MSG_PEEK)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    if res == 0 {
+        return Err(MioError::eof());
+    }
+
+    Ok(res as usize)
+}
+
+#[inline]
+pub fn write(io: &IoDesc, src: &[u8]) -> MioResult<usize> {
+    nix::write(io.fd, src).map_err(MioError::from_nix_error)
+}
+
+/// Writes `slices` in order, in a single system call, and returns the total
+/// number of bytes written. Only the first `IOV_MAX` slices are passed to
+/// the kernel, which rejects longer lists, so a long list always ends in a
+/// partial write.
+pub fn writev(io: &IoDesc, slices: &[&[u8]]) -> MioResult<usize> {
+    let iov: Vec<ffi::iovec> = slices.iter().take(ffi::IOV_MAX).map(|s| {
+        ffi::iovec { iov_base: s.as_ptr() as *mut libc::c_void, iov_len: s.len() as libc::size_t }
+    }).collect();
+
+    let res = unsafe { ffi::writev(io.fd, iov.as_ptr(), iov.len() as libc::c_int) };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(res as usize)
+}
+
+/// Returns the number of bytes that can be read without blocking.
+pub fn bytes_available(io: &IoDesc) -> MioResult<usize> {
+    let mut cnt: libc::c_int = 0;
+
+    if unsafe { ffi::ioctl(io.fd, ffi::FIONREAD, &mut cnt as *mut libc::c_int) } < 0 {
+        return Err(last_error());
+    }
+
+    Ok(cnt as usize)
+}
+
+#[cfg(target_os = "linux")]
+fn tcp_info(io: &IoDesc) -> MioResult<ffi::tcp_info> {
+    let mut info: ffi::tcp_info = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<ffi::tcp_info>() as libc::socklen_t;
+
+    let res = unsafe {
+        ffi::getsockopt(io.fd, ffi::IPPROTO_TCP, ffi::TCP_INFO,
+                        &mut info as *mut ffi::tcp_info as *mut libc::c_void, &mut len)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok(info)
+}
+
+/// Returns the number of connections waiting in the accept queue of a
+/// listening TCP socket. For listeners, linux reports the queue length in
+/// the `tcpi_unacked` field of TCP_INFO.
+#[cfg(target_os = "linux")]
+pub fn backlog_len(io: &IoDesc) -> MioResult<usize> {
+    let info = try!(tcp_info(io));
+    Ok(info.tcpi_unacked as usize)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn backlog_len(_io: &IoDesc) -> MioResult<usize> {
+    Err(MioError::unsupported())
+}
+
+/// The kernel's view of a TCP connection, from `TCP_INFO`.
+///
+/// Only the fields that most platforms with `TCP_INFO` report are exposed.
+/// The round trip times are the kernel's smoothed estimates and are zero
+/// until the first segment is acknowledged. Linux counts the congestion
+/// window in segments, where the BSDs count it in bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TcpInfo {
+    /// Smoothed round trip time.
+    pub rtt: Duration,
+    /// Mean deviation of the round trip time.
+    pub rtt_var: Duration,
+    /// Current retransmission timeout.
+    pub rto: Duration,
+    /// Segments retransmitted over the life of the connection.
+    pub total_retrans: u32,
+    /// Congestion window.
+    pub snd_cwnd: u32,
+    /// Slow start threshold.
+    pub snd_ssthresh: u32,
+    /// Maximum segment size used for sending.
+    pub snd_mss: u32,
+}
+
+#[cfg(target_os = "linux")]
+pub fn get_tcp_info(io: &IoDesc) -> MioResult<TcpInfo> {
+    let info = try!(tcp_info(io));
+
+    Ok(TcpInfo {
+        rtt: Duration::microseconds(info.tcpi_rtt as i64),
+        rtt_var: Duration::microseconds(info.tcpi_rttvar as i64),
+        rto: Duration::microseconds(info.tcpi_rto as i64),
+        total_retrans: info.tcpi_total_retrans,
+        snd_cwnd: info.tcpi_snd_cwnd,
+        snd_ssthresh: info.tcpi_snd_ssthresh,
+        snd_mss: info.tcpi_snd_mss,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn get_tcp_info(_io: &IoDesc) -> MioResult<TcpInfo> {
+    Err(MioError::unsupported())
+}
+
+/// Holds new connections back from accept until the client sends data, for
+/// at most `secs` seconds. Zero turns it off.
+#[cfg(target_os = "linux")]
+pub fn set_defer_accept(io: &IoDesc, secs: u32) -> MioResult<()> {
+    setsockopt_int(io, ffi::IPPROTO_TCP, ffi::TCP_DEFER_ACCEPT, secs as libc::c_int)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_defer_accept(_io: &IoDesc, _secs: u32) -> MioResult<()> {
+    Err(MioError::unsupported())
+}
+
+/// Returns the subset of `interest` the handle is ready for right now,
+/// along with any error or hangup, without blocking.
+pub fn readiness(io: &IoDesc, interest: Interest) -> MioResult<Interest> {
+    let mut events = 0;
+
+    if interest.is_readable() {
+        events |= ffi::POLLIN;
+    }
+
+    if interest.is_writable() {
+        events |= ffi::POLLOUT;
+    }
+
+    if interest.is_priority() {
+        events |= ffi::POLLPRI;
+    }
+
+    let mut fd = ffi::pollfd { fd: io.fd, events: events, revents: 0 };
+
+    if unsafe { ffi::poll(&mut fd, 1, 0) } < 0 {
+        return Err(last_error());
+    }
+
+    let mut ready = Interest::none();
+
+    if fd.revents & ffi::POLLIN != 0 {
+        ready.insert(Interest::readable());
+    }
+
+    if fd.revents & ffi::POLLOUT != 0 {
+        ready.insert(Interest::writable());
+    }
+
+    if fd.revents & ffi::POLLPRI != 0 {
+        ready.insert(Interest::priority());
+    }
+
+    if fd.revents & ffi::POLLERR != 0 {
+        ready.insert(Interest::error());
+    }
+
+    if fd.revents & ffi::POLLHUP != 0 {
+        ready.insert(Interest::hup());
+    }
+
+    Ok(ready)
+}
+
+/// Reads the urgent byte of a TCP socket, if there is one.
+pub fn recv_urgent(io: &IoDesc) -> MioResult<Option<u8>> {
+    let mut byte = 0u8;
+
+    let res = unsafe {
+        ffi::recv(io.fd, &mut byte as *mut u8 as *mut libc::c_void, 1, ffi::MSG_OOB)
+    };
+
+    if res < 0 {
+        let err = nix::Errno::last();
+
+        // EINVAL is returned when no urgent data is pending, or when it is
+        // received inline with the regular data
+        if err == nix::Errno::EINVAL || err == nix::Errno::EAGAIN {
+            return Ok(None);
+        }
+
+        return Err(MioError::from_nix_error(nix::NixError::Sys(err)));
+    }
 
-impl Drop for IoDesc {
-    fn drop(&mut self) {
-        let _ = nix::close(self.fd);
+    if res == 0 {
+        return Ok(None);
     }
+
+    Ok(Some(byte))
 }
 
-/*
- *
- * ===== Pipes =====
- *
- */
+pub fn set_oob_inline(io: &IoDesc, val: bool) -> MioResult<()> {
+    setsockopt_int(io, ffi::SOL_SOCKET, ffi::SO_OOBINLINE, if val { 1 } else { 0 })
+}
 
-pub fn pipe() -> MioResult<(IoDesc, IoDesc)> {
-    let (rd, wr) = try!(nix::pipe2(nix::O_NONBLOCK | nix::O_CLOEXEC)
-                        .map_err(MioError::from_nix_error));
+// ===== File descriptor flags =====
 
-    Ok((IoDesc { fd: rd }, IoDesc { fd: wr }))
-}
+pub fn set_nonblock(io: &IoDesc, val: bool) -> MioResult<()> {
+    let flags = unsafe { ffi::fcntl(io.fd, ffi::F_GETFL) };
 
-/*
- *
- * ===== Sockets =====
- *
- */
+    if flags < 0 {
+        return Err(last_error());
+    }
 
-pub fn socket(af: AddressFamily, sock_type: SocketType) -> MioResult<IoDesc> {
-    let family = match af {
-        Inet  => nix::AF_INET,
-        Inet6 => nix::AF_INET6,
-        Unix  => nix::AF_UNIX
+    let flags = if val {
+        flags | ffi::O_NONBLOCK
+    } else {
+        flags & !ffi::O_NONBLOCK
     };
 
-    let socket_type = match sock_type {
-        Dgram  => nix::SOCK_DGRAM,
-        Stream => nix::SOCK_STREAM
-    };
+    if unsafe { ffi::fcntl(io.fd, ffi::F_SETFL, flags) } < 0 {
+        return Err(last_error());
+    }
 
-    Ok(IoDesc {
-        fd: try!(nix::socket(family, socket_type, nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC)
-                    .map_err(MioError::from_nix_error))
-    })
+    Ok(())
 }
 
-pub fn connect(io: &IoDesc, addr: &SockAddr) -> MioResult<bool> {
-    match nix::connect(io.fd, &from_sockaddr(addr)) {
-        Ok(_) => Ok(true),
-        Err(e) => {
-            match e {
-                nix::NixError::Sys(nix::EINPROGRESS) => Ok(false),
-                _ => Err(MioError::from_nix_error(e))
-            }
-        }
+pub fn is_nonblock(io: &IoDesc) -> MioResult<bool> {
+    let flags = unsafe { ffi::fcntl(io.fd, ffi::F_GETFL) };
+
+    if flags < 0 {
+        return Err(last_error());
     }
+
+    Ok(flags & ffi::O_NONBLOCK == ffi::O_NONBLOCK)
 }
 
-pub fn bind(io: &IoDesc, addr: &SockAddr) -> MioResult<()> {
-    nix::bind(io.fd, &from_sockaddr(addr))
-        .map_err(MioError::from_nix_error)
+pub fn set_cloexec(io: &IoDesc) -> MioResult<()> {
+    let flags = unsafe { ffi::fcntl(io.fd, ffi::F_GETFD) };
+
+    if flags < 0 {
+        return Err(last_error());
+    }
+
+    if unsafe { ffi::fcntl(io.fd, ffi::F_SETFD, flags | ffi::FD_CLOEXEC) } < 0 {
+        return Err(last_error());
+    }
+
+    Ok(())
 }
 
-pub fn listen(io: &IoDesc, backlog: usize) -> MioResult<()> {
-    nix::listen(io.fd, backlog)
-        .map_err(MioError::from_nix_error)
+fn setsockopt_raw(io: &IoDesc, level: libc::c_int, name: libc::c_int,
+                  val: *const libc::c_void, len: libc::socklen_t) -> MioResult<()> {
+    if unsafe { ffi::setsockopt(io.fd, level, name, val, len) } < 0 {
+        return Err(last_error());
+    }
+
+    Ok(())
 }
 
-pub fn accept(io: &IoDesc) -> MioResult<IoDesc> {
-    Ok(IoDesc {
-        fd: try!(nix::accept4(io.fd, nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC)
-                     .map_err(MioError::from_nix_error))
-    })
+fn setsockopt_int(io: &IoDesc, level: libc::c_int, name: libc::c_int,
+                  val: libc::c_int) -> MioResult<()> {
+    setsockopt_raw(io, level, name,
+                   &val as *const libc::c_int as *const libc::c_void,
+                   mem::size_of::<libc::c_int>() as libc::socklen_t)
 }
 
-#[inline]
-pub fn recvfrom(io: &IoDesc, buf: &mut [u8]) -> MioResult<(usize, SockAddr)> {
-    match nix::recvfrom(io.fd, buf).map_err(MioError::from_nix_error) {
-        Ok((cnt, addr)) => Ok((cnt, to_sockaddr(&addr))),
-        Err(e) => Err(e)
+fn getsockopt_int(io: &IoDesc, level: libc::c_int, name: libc::c_int) -> MioResult<libc::c_int> {
+    let mut val: libc::c_int = 0;
+    let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+    let res = unsafe {
+        ffi::getsockopt(io.fd, level, name,
+                        &mut val as *mut libc::c_int as *mut libc::c_void, &mut len)
+    };
+
+    if res < 0 {
+        return Err(last_error());
     }
-}
 
-#[inline]
-pub fn sendto(io: &IoDesc, buf: &[u8], tgt: &SockAddr) -> MioResult<usize> {
-    let res = try!(nix::sendto(io.fd, buf, &from_sockaddr(tgt), nix::MSG_DONTWAIT).map_err(MioError::from_nix_error));
-    Ok(res)
+    Ok(val)
 }
 
-#[inline]
-pub fn read(io: &IoDesc, dst: &mut [u8]) -> MioResult<usize> {
-    let res = try!(nix::read(io.fd, dst).map_err(MioError::from_nix_error));
+// Returns the raw address family the socket was created with. The nix
+// getsockname binding only understands IPv4 and unix addresses, so the
+// family is read straight out of a sockaddr_storage.
+fn socket_family(io: &IoDesc) -> MioResult<libc::c_int> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
 
-    if res == 0 {
-        return Err(MioError::eof());
+    let res = unsafe {
+        ffi::getsockname(io.fd, &mut storage as *mut _ as *mut libc::sockaddr, &mut len)
+    };
+
+    if res < 0 {
+        return Err(last_error());
     }
 
-    Ok(res)
+    Ok(storage.ss_family as libc::c_int)
 }
 
-#[inline]
-pub fn write(io: &IoDesc, src: &[u8]) -> MioResult<usize> {
-    nix::write(io.fd, src).map_err(MioError::from_nix_error)
+// Converts the current value of errno into a MioError
+fn last_error() -> MioError {
+    MioError::from_nix_error(nix::NixError::Sys(nix::Errno::last()))
 }
 
 // ===== Socket options =====
@@ -201,6 +1700,205 @@ pub fn set_tcp_nodelay(io: &IoDesc, val: bool) -> MioResult<()> {
         .map_err(MioError::from_nix_error)
 }
 
+#[cfg(target_os = "linux")]
+pub fn bind_device(io: &IoDesc, ifname: &str) -> MioResult<()> {
+    let name = ifname.as_bytes();
+
+    // Leave room for the trailing nul
+    if name.len() >= ffi::IFNAMSIZ {
+        return Err(MioError::from_nix_error(nix::NixError::Sys(nix::Errno::EINVAL)));
+    }
+
+    setsockopt_raw(io, ffi::SOL_SOCKET, ffi::SO_BINDTODEVICE,
+                   name.as_ptr() as *const libc::c_void, name.len() as libc::socklen_t)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn bind_device(_io: &IoDesc, _ifname: &str) -> MioResult<()> {
+    Err(MioError::unsupported())
+}
+
+#[cfg(target_os = "linux")]
+pub fn device(io: &IoDesc) -> MioResult<Option<String>> {
+    let mut buf = [0u8; ffi::IFNAMSIZ];
+    let mut len = buf.len() as libc::socklen_t;
+
+    let res = unsafe {
+        ffi::getsockopt(io.fd, ffi::SOL_SOCKET, ffi::SO_BINDTODEVICE,
+                        buf.as_mut_ptr() as *mut libc::c_void, &mut len)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    // The returned length may or may not include the trailing nul
+    let name = buf[..len as usize].iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| b as char)
+        .collect::<String>();
+
+    if name.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(name))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn device(_io: &IoDesc) -> MioResult<Option<String>> {
+    Err(MioError::unsupported())
+}
+
+/// Sets the IP type-of-service byte (DSCP + ECN) used for outbound packets.
+/// IPv6 sockets set the traffic class instead.
+pub fn set_tos(io: &IoDesc, tos: u8) -> MioResult<()> {
+    if try!(socket_family(io)) == ffi::AF_INET6 {
+        setsockopt_int(io, ffi::IPPROTO_IPV6, ffi::IPV6_TCLASS, tos as libc::c_int)
+    } else {
+        setsockopt_int(io, ffi::IPPROTO_IP, ffi::IP_TOS, tos as libc::c_int)
+    }
+}
+
+pub fn tos(io: &IoDesc) -> MioResult<u8> {
+    let val = if try!(socket_family(io)) == ffi::AF_INET6 {
+        try!(getsockopt_int(io, ffi::IPPROTO_IPV6, ffi::IPV6_TCLASS))
+    } else {
+        try!(getsockopt_int(io, ffi::IPPROTO_IP, ffi::IP_TOS))
+    };
+
+    Ok(val as u8)
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_incoming_cpu(io: &IoDesc, cpu: i32) -> MioResult<()> {
+    setsockopt_int(io, ffi::SOL_SOCKET, ffi::SO_INCOMING_CPU, cpu as libc::c_int)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn set_incoming_cpu(_io: &IoDesc, _cpu: i32) -> MioResult<()> {
+    Err(MioError::unsupported())
+}
+
+/// Returns the CPU the socket's packets are processed on, or -1 if it is
+/// not known yet.
+#[cfg(target_os = "linux")]
+pub fn incoming_cpu(io: &IoDesc) -> MioResult<i32> {
+    let cpu = try!(getsockopt_int(io, ffi::SOL_SOCKET, ffi::SO_INCOMING_CPU));
+    Ok(cpu as i32)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn incoming_cpu(_io: &IoDesc) -> MioResult<i32> {
+    Err(MioError::unsupported())
+}
+
+/// Returns, and clears, the error pending on the socket, such as the
+/// outcome of a failed non-blocking connect.
+pub fn take_socket_error(io: &IoDesc) -> MioResult<()> {
+    let err = try!(getsockopt_int(io, ffi::SOL_SOCKET, ffi::SO_ERROR));
+
+    if err != 0 {
+        return Err(MioError::from_nix_error(nix::NixError::Sys(nix::errno_from_i32(err))));
+    }
+
+    Ok(())
+}
+
+pub fn set_read_timeout(io: &IoDesc, dur: Option<Duration>) -> MioResult<()> {
+    set_timeout(io, ffi::SO_RCVTIMEO, dur)
+}
+
+pub fn read_timeout(io: &IoDesc) -> MioResult<Option<Duration>> {
+    timeout(io, ffi::SO_RCVTIMEO)
+}
+
+pub fn set_write_timeout(io: &IoDesc, dur: Option<Duration>) -> MioResult<()> {
+    set_timeout(io, ffi::SO_SNDTIMEO, dur)
+}
+
+pub fn write_timeout(io: &IoDesc) -> MioResult<Option<Duration>> {
+    timeout(io, ffi::SO_SNDTIMEO)
+}
+
+// A zero timeval means no timeout, so a duration that rounds down to zero
+// microseconds is rounded up to one instead.
+fn set_timeout(io: &IoDesc, name: libc::c_int, dur: Option<Duration>) -> MioResult<()> {
+    let tv = match dur {
+        Some(dur) => {
+            let us = match dur.num_microseconds() {
+                Some(us) if us >= 0 => us,
+                _ => return Err(MioError::from_nix_error(nix::NixError::Sys(nix::Errno::EINVAL)))
+            };
+
+            let us = if us == 0 && dur > Duration::zero() { 1 } else { us };
+
+            libc::timeval {
+                tv_sec: (us / 1_000_000) as libc::time_t,
+                tv_usec: (us % 1_000_000) as libc::suseconds_t
+            }
+        }
+        None => libc::timeval { tv_sec: 0, tv_usec: 0 }
+    };
+
+    setsockopt_raw(io, ffi::SOL_SOCKET, name,
+                   &tv as *const libc::timeval as *const libc::c_void,
+                   mem::size_of::<libc::timeval>() as libc::socklen_t)
+}
+
+fn timeout(io: &IoDesc, name: libc::c_int) -> MioResult<Option<Duration>> {
+    let mut tv: libc::timeval = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::timeval>() as libc::socklen_t;
+
+    let res = unsafe {
+        ffi::getsockopt(io.fd, ffi::SOL_SOCKET, name,
+                        &mut tv as *mut libc::timeval as *mut libc::c_void, &mut len)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    if tv.tv_sec == 0 && tv.tv_usec == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(Duration::seconds(tv.tv_sec as i64) + Duration::microseconds(tv.tv_usec as i64)))
+}
+
+/// Returns the (uid, gid, pid) of the process on the other end of a unix
+/// socket.
+#[cfg(target_os = "linux")]
+pub fn peer_cred(io: &IoDesc) -> MioResult<(u32, u32, u32)> {
+    let mut cred: ffi::ucred = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<ffi::ucred>() as libc::socklen_t;
+
+    let res = unsafe {
+        ffi::getsockopt(io.fd, ffi::SOL_SOCKET, ffi::SO_PEERCRED,
+                        &mut cred as *mut ffi::ucred as *mut libc::c_void, &mut len)
+    };
+
+    if res < 0 {
+        return Err(last_error());
+    }
+
+    Ok((cred.uid as u32, cred.gid as u32, cred.pid as u32))
+}
+
+/// Returns the (uid, gid, pid) of the process on the other end of a unix
+/// socket. getpeereid does not report the pid, which is always 0.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn peer_cred(io: &IoDesc) -> MioResult<(u32, u32, u32)> {
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+
+    if unsafe { ffi::getpeereid(io.fd, &mut uid, &mut gid) } < 0 {
+        return Err(last_error());
+    }
+
+    Ok((uid as u32, gid as u32, 0))
+}
+
 pub fn join_multicast_group(io: &IoDesc, addr: &IpAddr, interface: &Option<IpAddr>) -> MioResult<()> {
     let grp_req = try!(make_ip_mreq(addr, interface));
 
@@ -263,6 +1961,16 @@ pub fn set_linger(io: &IoDesc, dur_s: usize) -> MioResult<()> {
         .map_err(MioError::from_nix_error)
 }
 
+pub fn set_reset_on_close(io: &IoDesc) -> MioResult<()> {
+    let linger = nix::linger {
+        l_onoff: 1,
+        l_linger: 0
+    };
+
+    nix::setsockopt(io.fd, nix::SOL_SOCKET, nix::SO_LINGER, &linger)
+        .map_err(MioError::from_nix_error)
+}
+
 fn make_ip_mreq(group_addr: &IpAddr, iface_addr: &Option<IpAddr>) -> MioResult<nix::ip_mreq> {
     Ok(nix::ip_mreq {
         imr_multiaddr: from_ip_addr_to_inaddr(&Some(*group_addr)),
@@ -358,3 +2066,351 @@ fn ipv4_to_inaddr(a: u8, b: u8, c: u8, d: u8) -> nix::in_addr {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use std::old_io::timer::sleep;
+    use std::time::Duration;
+    use libc;
+    use super::{ffi, socket, pipe, bind, listen, connect, accept, getsockname, recv_urgent, peek, read, write, writev, skip_aborted, IoDesc};
+    #[cfg(target_os = "linux")]
+    use super::{accept_or_shed, reserve_fd};
+    use error::MioError;
+    use nix::NixError;
+    use nix::errno::Errno;
+    #[cfg(target_os = "linux")]
+    use super::{bytes_available, send_more};
+    use net::SockAddr;
+    use net::AddressFamily::{Inet, Unix};
+    use net::SocketType::{Dgram, Stream};
+
+    fn is_cloexec(io: &IoDesc) -> bool {
+        let flags = unsafe { ffi::fcntl(io.fd, ffi::F_GETFD) };
+        assert!(flags >= 0, "fcntl failed");
+        flags & ffi::FD_CLOEXEC == ffi::FD_CLOEXEC
+    }
+
+    #[test]
+    fn test_sockets_are_cloexec() {
+        assert!(is_cloexec(&socket(Inet, Stream).unwrap()));
+        assert!(is_cloexec(&socket(Inet, Dgram).unwrap()));
+        assert!(is_cloexec(&socket(Unix, Stream).unwrap()));
+    }
+
+    #[test]
+    fn test_pipes_are_cloexec() {
+        let (rd, wr) = pipe().unwrap();
+
+        assert!(is_cloexec(&rd));
+        assert!(is_cloexec(&wr));
+    }
+
+    // Returns a connected (client, server) pair of TCP sockets
+    fn tcp_pair() -> (IoDesc, IoDesc) {
+        let srv = socket(Inet, Stream).unwrap();
+        bind(&srv, &SockAddr::parse("127.0.0.1:0").unwrap()).unwrap();
+        listen(&srv, 1).unwrap();
+
+        let cli = socket(Inet, Stream).unwrap();
+        connect(&cli, &getsockname(&srv).unwrap()).unwrap();
+
+        // The connection may take a moment to land in the accept queue
+        for _ in range(0, 100) {
+            match accept(&srv) {
+                Ok(sock) => return (cli, sock),
+                Err(ref e) if e.is_would_block() => sleep(Duration::milliseconds(10)),
+                Err(e) => panic!("accept failed; err={:?}", e)
+            }
+        }
+
+        panic!("connection never accepted");
+    }
+
+    #[test]
+    fn test_accepted_sockets_are_cloexec() {
+        let (_cli, sock) = tcp_pair();
+        assert!(is_cloexec(&sock));
+    }
+
+    #[cfg(target_os = "linux")]
+    extern {
+        fn fork() -> libc::pid_t;
+        fn execv(path: *const libc::c_char, argv: *const *const libc::c_char) -> libc::c_int;
+        fn waitpid(pid: libc::pid_t, status: *mut libc::c_int, options: libc::c_int) -> libc::pid_t;
+        fn _exit(status: libc::c_int) -> !;
+        fn dup(fd: libc::c_int) -> libc::c_int;
+        fn getrlimit(resource: libc::c_int, rlim: *mut RLimit) -> libc::c_int;
+        fn setrlimit(resource: libc::c_int, rlim: *const RLimit) -> libc::c_int;
+    }
+
+    #[cfg(target_os = "linux")]
+    const RLIMIT_NOFILE: libc::c_int = 7;
+
+    #[cfg(target_os = "linux")]
+    #[repr(C)]
+    struct RLimit {
+        rlim_cur: u64,
+        rlim_max: u64,
+    }
+
+    // Runs `f` in a forked child, which exits with the returned code, and
+    // returns that code. Process wide settings such as resource limits can
+    // be changed in the child without affecting the other tests.
+    #[cfg(target_os = "linux")]
+    fn in_child<F: FnOnce() -> libc::c_int>(f: F) -> libc::c_int {
+        unsafe {
+            let pid = fork();
+            assert!(pid >= 0, "fork failed");
+
+            if pid == 0 {
+                _exit(f());
+            }
+
+            let mut status = 0;
+            assert_eq!(pid, waitpid(pid, &mut status, 0));
+
+            // WIFEXITED and WEXITSTATUS
+            assert!(status & 0x7f == 0, "child did not exit");
+            (status >> 8) & 0xff
+        }
+    }
+
+    // Forks and execs a shell that exits with 0 if it inherited `fd`. The
+    // process spawning of the standard library closes extra fds in the
+    // child, so it cannot be used to check this.
+    #[cfg(target_os = "linux")]
+    fn is_inherited(io: &IoDesc) -> bool {
+        let sh = "/bin/sh\0";
+        let flag = "-c\0";
+        let script = format!("[ -e /proc/self/fd/{} ]\0", io.fd);
+        let argv = [
+            sh.as_ptr() as *const libc::c_char,
+            flag.as_ptr() as *const libc::c_char,
+            script.as_ptr() as *const libc::c_char,
+            0 as *const libc::c_char];
+
+        let code = in_child(|| {
+            unsafe { execv(argv[0], argv.as_ptr()); }
+            127
+        });
+
+        match code {
+            0 => true,
+            1 => false,
+            code => panic!("child failed; status={}", code)
+        }
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_fds_not_inherited_across_exec() {
+        let (cli, sock) = tcp_pair();
+        let udp = socket(Inet, Dgram).unwrap();
+        let unix = socket(Unix, Stream).unwrap();
+        let (rd, wr) = pipe().unwrap();
+
+        for io in [&cli, &sock, &udp, &unix, &rd, &wr].iter() {
+            assert!(!is_inherited(*io), "fd {} leaked into the child", io.fd);
+        }
+
+        // Make sure the check can tell: clear the flag and try again
+        unsafe { assert!(ffi::fcntl(udp.fd, ffi::F_SETFD, 0) >= 0); }
+        assert!(is_inherited(&udp));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_accept_sheds_connection_without_fds() {
+        let srv = socket(Inet, Stream).unwrap();
+        bind(&srv, &SockAddr::parse("127.0.0.1:0").unwrap()).unwrap();
+        listen(&srv, 1).unwrap();
+
+        let _cli = socket(Inet, Stream).unwrap();
+        connect(&_cli, &getsockname(&srv).unwrap()).unwrap();
+
+        // Let the connection land in the accept queue
+        sleep(Duration::milliseconds(50));
+
+        // Panicking in the child would run the test harness there, so each
+        // failed check exits with its own code instead
+        let code = in_child(|| unsafe {
+            let mut orig = RLimit { rlim_cur: 0, rlim_max: 0 };
+            if getrlimit(RLIMIT_NOFILE, &mut orig) < 0 { return 10; }
+
+            let low = RLimit { rlim_cur: 256, rlim_max: orig.rlim_max };
+            if setrlimit(RLIMIT_NOFILE, &low) < 0 { return 11; }
+
+            // Use up every descriptor, then hand the last one to the spare
+            let mut last = -1;
+            loop {
+                match dup(0) {
+                    fd if fd >= 0 => last = fd,
+                    _ => break
+                }
+            }
+
+            if last < 0 { return 12; }
+            drop(IoDesc { fd: last });
+
+            let mut spare = reserve_fd().ok();
+            if spare.is_none() { return 13; }
+
+            match accept_or_shed(&srv, &mut spare) {
+                Err(ref e) if e.is_resource_limit() => {}
+                _ => return 14
+            }
+
+            // The spare was taken back after shedding the connection
+            if spare.is_none() { return 15; }
+
+            // With descriptors available again, nothing is left to accept
+            if setrlimit(RLIMIT_NOFILE, &orig) < 0 { return 16; }
+
+            match accept(&srv) {
+                Err(ref e) if e.is_would_block() => 0,
+                _ => 17
+            }
+        });
+
+        assert_eq!(0, code);
+    }
+
+    #[test]
+    fn test_recv_urgent() {
+        let (cli, sock) = tcp_pair();
+
+        assert_eq!(None, recv_urgent(&sock).unwrap());
+
+        let byte = b'!';
+        let res = unsafe {
+            ffi::send(cli.fd, &byte as *const u8 as *const libc::c_void, 1, ffi::MSG_OOB)
+        };
+        assert_eq!(1, res);
+
+        // Give the urgent byte time to arrive over loopback
+        for _ in range(0, 100) {
+            match recv_urgent(&sock).unwrap() {
+                Some(b) => {
+                    assert_eq!(byte, b);
+                    return;
+                }
+                None => sleep(Duration::milliseconds(10))
+            }
+        }
+
+        panic!("urgent data never received");
+    }
+
+    #[test]
+    fn test_peek() {
+        let (cli, sock) = tcp_pair();
+        let mut buf = [0u8; 16];
+
+        assert!(peek(&sock, &mut buf).unwrap_err().is_would_block());
+        assert_eq!(5, write(&cli, b"hello").unwrap());
+
+        // Wait for the data to arrive over loopback
+        for _ in range(0, 100) {
+            match peek(&sock, &mut buf) {
+                Ok(cnt) => {
+                    assert_eq!(b"hello", &buf[..cnt]);
+
+                    // The peeked data is still there to be read
+                    let mut dst = [0u8; 16];
+                    assert_eq!(cnt, read(&sock, &mut dst).unwrap());
+                    assert_eq!(b"hello", &dst[..cnt]);
+                    return;
+                }
+                Err(ref e) if e.is_would_block() => sleep(Duration::milliseconds(10)),
+                Err(e) => panic!("peek failed; err={:?}", e)
+            }
+        }
+
+        panic!("data never received");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_writev_partial() {
+        let (rd, wr) = pipe().unwrap();
+        let page = [0u8; 4096];
+
+        // Fill the pipe one page at a time, then free a single page
+        while write(&wr, &page).is_ok() {}
+
+        let mut dst = [0u8; 4096];
+        assert_eq!(4096, read(&rd, &mut dst).unwrap());
+
+        let first = [1u8; 4096];
+        let second = [2u8; 4096];
+
+        // Only the first slice fits
+        assert_eq!(4096, writev(&wr, &[&first[..], &second[..]]).unwrap());
+        assert!(writev(&wr, &[&second[..]]).unwrap_err().is_would_block());
+    }
+
+    #[test]
+    fn test_writev_caps_slices() {
+        let (rd, wr) = pipe().unwrap();
+        let byte = [7u8];
+        let slices: Vec<&[u8]> = range(0, ffi::IOV_MAX + 10).map(|_| &byte[..]).collect();
+
+        // The slices past IOV_MAX are left for the next call
+        assert_eq!(ffi::IOV_MAX, writev(&wr, slices.as_slice()).unwrap());
+
+        let mut dst = [0u8; 2048];
+        assert_eq!(ffi::IOV_MAX, read(&rd, &mut dst).unwrap());
+    }
+
+    #[test]
+    fn test_skip_aborted() {
+        let mut results = vec![
+            Err(MioError::from_nix_error(NixError::Sys(Errno::ECONNABORTED))),
+            Err(MioError::from_nix_error(NixError::Sys(Errno::ECONNABORTED))),
+            Ok(pipe().unwrap().0),
+            Err(MioError::from_nix_error(NixError::Sys(Errno::EPROTO))),
+            Err(MioError::from_nix_error(NixError::Sys(Errno::EAGAIN))),
+            Err(MioError::from_nix_error(NixError::Sys(Errno::EBADF))),
+        ];
+
+        results.reverse();
+
+        // Aborted connections are skipped over
+        assert!(skip_aborted(|| results.pop().unwrap()).is_ok());
+        assert!(skip_aborted(|| results.pop().unwrap()).unwrap_err().is_would_block());
+
+        // Errors about the listener itself still come through
+        let err = skip_aborted(|| results.pop().unwrap()).unwrap_err();
+        assert!(!err.is_connection_aborted() && !err.is_would_block());
+        assert!(results.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_send_more() {
+        let (cli, sock) = tcp_pair();
+
+        assert_eq!(6, send_more(&cli, b"hello ").unwrap());
+        assert_eq!(3, send_more(&cli, b"big").unwrap());
+
+        // Held back by the kernel, which waits for up to 200ms for the rest
+        sleep(Duration::milliseconds(20));
+        assert_eq!(0, bytes_available(&sock).unwrap());
+
+        // A plain write sends everything at once
+        assert_eq!(6, write(&cli, b" world").unwrap());
+
+        for _ in range(0, 100) {
+            if bytes_available(&sock).unwrap() == 15 {
+                let mut dst = [0u8; 32];
+                assert_eq!(15, read(&sock, &mut dst).unwrap());
+                assert_eq!(b"hello big world", &dst[..15]);
+                return;
+            }
+
+            sleep(Duration::milliseconds(10));
+        }
+
+        panic!("data never received");
+    }
+}
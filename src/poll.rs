@@ -1,59 +1,103 @@
-use std::fmt;
-use error::MioResult;
-use io::IoHandle;
+use std::{fmt, mem};
+use std::ops::Deref;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use nix::fcntl::Fd;
+use error::{MioResult, MioError};
+use io::{IoHandle, IoDesc};
 use os;
 use os::token::Token;
 use os::event;
 
 pub struct Poll {
-    selector: os::Selector,
+    // Shared with any outstanding `Registration` guards so that they can
+    // deregister themselves when dropped
+    registry: Rc<RefCell<Registry>>,
     events: os::Events
 }
 
 impl Poll {
     pub fn new() -> MioResult<Poll> {
         Ok(Poll {
-            selector: try!(os::Selector::new()),
+            registry: Rc::new(RefCell::new(try!(Registry::new()))),
             events: os::Events::new()
         })
     }
 
     pub fn register<H: IoHandle>(&mut self, io: &H, token: Token, interest: event::Interest, opts: event::PollOpt) -> MioResult<()> {
-        debug!("registering  with poller");
+        self.registry.borrow_mut().register(io.desc(), token, interest, opts)
+    }
 
-        // Register interests for this socket
-        try!(self.selector.register(io.desc(), token.as_usize(), interest, opts));
+    pub fn reregister<H: IoHandle>(&mut self, io: &H, token: Token, interest: event::Interest, opts: event::PollOpt) -> MioResult<()> {
+        self.registry.borrow_mut().reregister(io.desc(), token, interest, opts)
+    }
 
-        Ok(())
+    pub fn deregister<H: IoHandle>(&mut self, io: &H) -> MioResult<()> {
+        self.registry.borrow_mut().deregister(io.desc())
     }
 
-    pub fn reregister<H: IoHandle>(&mut self, io: &H, token: Token, interest: event::Interest, opts: event::PollOpt) -> MioResult<()> {
-        debug!("registering  with poller");
+    /// Deregisters every handle, see `Registry::deregister_all`.
+    pub fn deregister_all(&mut self) {
+        self.registry.borrow_mut().deregister_all()
+    }
 
-        // Register interests for this socket
-        try!(self.selector.reregister(io.desc(), token.as_usize(), interest, opts));
+    /// Changes the registrations of several tokens, see
+    /// `Registry::reregister_tokens`.
+    pub fn reregister_tokens(&mut self, changes: &[(Token, event::Interest, event::PollOpt)]) -> MioResult<()> {
+        self.registry.borrow_mut().reregister_tokens(changes)
+    }
 
-        Ok(())
+    /// Waits for IO events for at most `timeout_ms` milliseconds, or until an
+    /// event is received if the timeout is `None`.
+    pub fn poll(&mut self, timeout_ms: Option<usize>) -> MioResult<usize> {
+        let mut registry = self.registry.borrow_mut();
+
+        // Events from previous polls have all been dispatched
+        registry.stale.clear();
+        registry.changed = false;
+
+        try!(registry.selector.select(&mut self.events, timeout_ms));
+        Ok(self.events.len())
     }
 
-    pub fn deregister<H: IoHandle>(&mut self, io: &H) -> MioResult<()> {
-        debug!("deregistering IO with poller");
+    pub fn is_registered(&self, token: Token) -> bool {
+        self.registry.borrow().registrations.contains_key(&token)
+    }
 
-        // Deregister interests for this socket
-        try!(self.selector.deregister(io.desc()));
+    /// Returns the token `io` is registered with, or `None` if it is not
+    /// registered.
+    pub fn token<H: IoHandle>(&self, io: &H) -> Option<Token> {
+        self.registry.borrow().tokens.get(&io.desc().fd).map(|&token| token)
+    }
 
-        Ok(())
+    /// Returns the interest and options `token` is registered with, or
+    /// `None` if it is not registered.
+    pub fn registration(&self, token: Token) -> Option<(event::Interest, event::PollOpt)> {
+        self.registry.borrow().registrations.get(&token).map(|&(_, interest, opts)| (interest, opts))
     }
 
-    pub fn poll(&mut self, timeout_ms: usize) -> MioResult<usize> {
-        try!(self.selector.select(&mut self.events, timeout_ms));
-        Ok(self.events.len())
+    /// Returns the number of registered handles.
+    pub fn len(&self) -> usize {
+        self.registry.borrow().registrations.len()
+    }
+
+    pub fn registry(&self) -> Rc<RefCell<Registry>> {
+        self.registry.clone()
     }
 
     pub fn event(&self, idx: usize) -> event::IoEvent {
         self.events.get(idx)
     }
 
+    /// Returns `evt` as it applies to the current registrations, which may
+    /// have changed since the poll. Returns `None` if the token was
+    /// deregistered in the meantime. If it was reregistered, readiness that
+    /// is no longer of interest is masked out.
+    pub fn refresh(&self, evt: event::IoEvent) -> Option<event::IoEvent> {
+        self.registry.borrow().current(evt)
+    }
+
     pub fn iter(&self) -> EventsIterator {
         EventsIterator { events: &self.events, index: 0 }
     }
@@ -65,6 +109,212 @@ impl fmt::Debug for Poll {
     }
 }
 
+/// The selector along with what is registered with it.
+///
+/// Registrations are tracked so that changes made while events are being
+/// dispatched apply to the events of the current tick that are not
+/// dispatched yet. Events are only looked up once a registration changed
+/// since the poll, which is rare, so dispatching does not pay for it.
+///
+/// A descriptor that is closed without being deregistered stays tracked
+/// until its number is registered again, at which point the kernel has
+/// already dropped the old registration and so is the entry.
+pub struct Registry {
+    selector: os::Selector,
+    // Token of every registered descriptor
+    tokens: HashMap<Fd, Token>,
+    // Descriptor, interest and options of every registered token
+    registrations: HashMap<Token, (Fd, event::Interest, event::PollOpt)>,
+    // Tokens deregistered since the last poll. Any event still pending for
+    // them belongs to the old registration.
+    stale: HashSet<Token>,
+    // Whether any registration changed since the last poll
+    changed: bool,
+}
+
+impl Registry {
+    fn new() -> MioResult<Registry> {
+        Ok(Registry {
+            selector: try!(os::Selector::new()),
+            tokens: HashMap::new(),
+            registrations: HashMap::new(),
+            stale: HashSet::new(),
+            changed: false,
+        })
+    }
+
+    pub fn register(&mut self, io: &IoDesc, token: Token, interest: event::Interest, opts: event::PollOpt) -> MioResult<()> {
+        debug!("registering with poller; token={:?}", token);
+
+        // Register interests for this socket
+        try!(self.selector.register(io, token.as_usize(), interest, opts));
+
+        self.track(io.fd, token, interest, opts);
+        Ok(())
+    }
+
+    pub fn reregister(&mut self, io: &IoDesc, token: Token, interest: event::Interest, opts: event::PollOpt) -> MioResult<()> {
+        debug!("reregistering with poller; token={:?}", token);
+
+        // Register interests for this socket
+        let res = self.selector.reregister(io, token.as_usize(), interest, opts);
+        self.reregistered(io, token, interest, opts, res)
+    }
+
+    /// Changes the registrations of several tokens, looking up the handles
+    /// they are registered for. Stops at the first change that fails, the
+    /// ones before it stay applied. A token that is not registered fails
+    /// with a `NotRegistered` error.
+    ///
+    /// A descriptor that was closed without being deregistered fails the
+    /// same way, even if its number now belongs to another file, which is
+    /// left untouched.
+    pub fn reregister_tokens(&mut self, changes: &[(Token, event::Interest, event::PollOpt)]) -> MioResult<()> {
+        for &(token, interest, opts) in changes.iter() {
+            let io = match self.registrations.get(&token) {
+                Some(&(fd, _, _)) => FdRef::new(fd),
+                None => return Err(MioError::not_registered(None))
+            };
+
+            debug!("reregistering with poller; token={:?}", token);
+
+            let res = self.selector.reregister_existing(&io, token.as_usize(), interest, opts);
+            try!(self.reregistered(&io, token, interest, opts, res));
+        }
+
+        Ok(())
+    }
+
+    pub fn deregister(&mut self, io: &IoDesc) -> MioResult<()> {
+        debug!("deregistering IO with poller");
+
+        // Deregister interests for this socket
+        try!(self.selector.deregister(io));
+
+        if let Some(&token) = self.tokens.get(&io.fd) {
+            self.forget(token);
+        }
+
+        Ok(())
+    }
+
+    /// Deregisters every tracked descriptor, leaving them open. Failures are
+    /// ignored: a descriptor that was closed without being deregistered is
+    /// already gone from the selector.
+    pub fn deregister_all(&mut self) {
+        let fds: Vec<Fd> = self.tokens.keys().map(|&fd| fd).collect();
+
+        for fd in fds.into_iter() {
+            if let Err(e) = self.selector.deregister(&FdRef::new(fd)) {
+                debug!("failed to deregister on teardown; fd={}; err={:?}", fd, e);
+            }
+
+            if let Some(&token) = self.tokens.get(&fd) {
+                self.forget(token);
+            }
+        }
+    }
+
+    // Updates the tracked registrations with the outcome of reregistering
+    // `io` with the selector
+    fn reregistered(&mut self, io: &IoDesc, token: Token, interest: event::Interest, opts: event::PollOpt, res: MioResult<()>) -> MioResult<()> {
+        if let Err(err) = res {
+            // The descriptor is gone, so is whatever was registered for it
+            if err.is_not_registered() {
+                if let Some(prev) = self.tokens.remove(&io.fd) {
+                    self.forget(prev);
+                }
+            }
+
+            return Err(err);
+        }
+
+        self.track(io.fd, token, interest, opts);
+        Ok(())
+    }
+
+    // Records that `fd` is registered with `token`, replacing whatever the
+    // descriptor or the token was registered with before. The kernel only
+    // accepts a descriptor that is not registered already, or updates its
+    // registration, so an older entry for the same number belongs to a
+    // descriptor that was closed without being deregistered.
+    fn track(&mut self, fd: Fd, token: Token, interest: event::Interest, opts: event::PollOpt) {
+        if let Some(prev) = self.tokens.insert(fd, token) {
+            if prev != token {
+                self.forget(prev);
+            }
+        }
+
+        if let Some((prev, _, _)) = self.registrations.insert(token, (fd, interest, opts)) {
+            if prev != fd {
+                self.tokens.remove(&prev);
+            }
+        }
+
+        self.changed = true;
+    }
+
+    // Drops the registration of `token` along with its descriptor's entry
+    fn forget(&mut self, token: Token) {
+        if let Some((fd, _, _)) = self.registrations.remove(&token) {
+            if self.tokens.get(&fd) == Some(&token) {
+                self.tokens.remove(&fd);
+            }
+        }
+
+        self.stale.insert(token);
+        self.changed = true;
+    }
+
+    fn current(&self, evt: event::IoEvent) -> Option<event::IoEvent> {
+        // Nothing changed since the poll, the event is as current as it gets
+        if !self.changed {
+            return Some(evt);
+        }
+
+        let token = evt.token();
+
+        // Registrations that are not tracked, such as ones made directly on
+        // the selector, are passed through as is
+        if self.stale.contains(&token) {
+            return None;
+        }
+
+        match self.registrations.get(&token) {
+            Some(&(_, interest, _)) => Some(evt.restrict(interest)),
+            None => Some(evt)
+        }
+    }
+}
+
+// A descriptor known by its number only, borrowed from whoever owns it:
+// dropping it leaves the descriptor open
+struct FdRef {
+    desc: Option<IoDesc>
+}
+
+impl FdRef {
+    fn new(fd: Fd) -> FdRef {
+        FdRef { desc: Some(IoDesc { fd: fd }) }
+    }
+}
+
+impl Deref for FdRef {
+    type Target = IoDesc;
+
+    fn deref(&self) -> &IoDesc {
+        self.desc.as_ref().unwrap()
+    }
+}
+
+impl Drop for FdRef {
+    fn drop(&mut self) {
+        if let Some(desc) = self.desc.take() {
+            mem::forget(desc);
+        }
+    }
+}
+
 pub struct EventsIterator<'a> {
     events: &'a os::Events,
     index: usize
@@ -0,0 +1,98 @@
+use std::cell::RefCell;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::fmt;
+use io::IoHandle;
+use poll::Registry;
+use os::token::Token;
+
+/// An IO handle that is registered with an event loop for as long as the
+/// guard is alive.
+///
+/// Returned by
+/// [EventLoop::register_owned](struct.EventLoop.html#method.register_owned).
+/// The guard owns the handle, and dropping the guard deregisters the handle
+/// before closing it. This makes it impossible to close a handle that is
+/// still registered, which would otherwise leave a stale registration behind
+/// that keeps firing for the closed, or worse recycled, file descriptor.
+///
+/// The handle is reachable through `Deref`, so a `Registration<TcpSocket>`
+/// can be read from and written to directly. Use
+/// [#into_inner](#method.into_inner) to deregister the handle and take it
+/// back, for example to hand it over to another event loop.
+pub struct Registration<H: IoHandle> {
+    io: Option<H>,
+    token: Token,
+    registry: Rc<RefCell<Registry>>,
+}
+
+impl<H: IoHandle> Registration<H> {
+    #[doc(hidden)]
+    pub fn new(io: H, token: Token, registry: Rc<RefCell<Registry>>) -> Registration<H> {
+        Registration {
+            io: Some(io),
+            token: token,
+            registry: registry,
+        }
+    }
+
+    /// The token the handle is registered with.
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    /// Returns a reference to the registered handle.
+    pub fn get_ref(&self) -> &H {
+        self.io.as_ref().unwrap()
+    }
+
+    /// Returns a mutable reference to the registered handle.
+    pub fn get_mut(&mut self) -> &mut H {
+        self.io.as_mut().unwrap()
+    }
+
+    /// Deregisters the handle from the event loop and returns it without
+    /// closing it.
+    pub fn into_inner(mut self) -> H {
+        let io = self.io.take().unwrap();
+        self.deregister(&io);
+        io
+    }
+
+    fn deregister(&self, io: &H) {
+        debug!("deregistering guarded IO; token={:?}", self.token);
+
+        // Nothing useful can be done with an error at this point, the handle
+        // is going away regardless.
+        let _ = self.registry.borrow_mut().deregister(io.desc());
+    }
+}
+
+impl<H: IoHandle> Deref for Registration<H> {
+    type Target = H;
+
+    fn deref(&self) -> &H {
+        self.get_ref()
+    }
+}
+
+impl<H: IoHandle> DerefMut for Registration<H> {
+    fn deref_mut(&mut self) -> &mut H {
+        self.get_mut()
+    }
+}
+
+#[unsafe_destructor]
+impl<H: IoHandle> Drop for Registration<H> {
+    fn drop(&mut self) {
+        if let Some(io) = self.io.take() {
+            self.deregister(&io);
+        }
+    }
+}
+
+impl<H: IoHandle> fmt::Debug for Registration<H> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Registration {{ token: {:?} }}", self.token)
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use error::MioResult;
+use event_fd::EventFd;
+use io::IoHandle;
+use os;
+
+/// A queue that several event loops can consume from, for spreading work
+/// over loops running on different threads.
+///
+/// Any thread may `send` into the queue. Each consuming loop gets its own
+/// `QueueReceiver` and registers it for readable interest; every message
+/// wakes up all the receivers, and whichever loop gets to the queue first
+/// dequeues it, so each message is delivered to exactly one of them.
+///
+/// ```
+/// use mio::{EventLoop, SharedQueue};
+///
+/// let queue = SharedQueue::new();
+/// let rx = queue.receiver().unwrap();
+///
+/// let mut event_loop = EventLoop::<(), ()>::new().unwrap();
+/// event_loop.register(&rx, mio::Token(0)).unwrap();
+///
+/// queue.send("job").unwrap();
+/// assert_eq!(Some("job"), rx.recv());
+/// ```
+pub struct SharedQueue<M> {
+    inner: Arc<Inner<M>>
+}
+
+struct Inner<M> {
+    queue: Mutex<VecDeque<M>>,
+    // The wakeup of every live receiver
+    wakeups: Mutex<Vec<Arc<EventFd>>>,
+}
+
+impl<M: Send> SharedQueue<M> {
+    pub fn new() -> SharedQueue<M> {
+        SharedQueue {
+            inner: Arc::new(Inner {
+                queue: Mutex::new(VecDeque::new()),
+                wakeups: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    /// Creates a receiver, to be registered with one of the consuming event
+    /// loops. It is woken up right away if messages are already queued.
+    pub fn receiver(&self) -> MioResult<QueueReceiver<M>> {
+        let wakeup = Arc::new(try!(EventFd::new()));
+
+        self.inner.wakeups.lock().unwrap().push(wakeup.clone());
+
+        if !self.inner.queue.lock().unwrap().is_empty() {
+            try!(wakeup.wake(1));
+        }
+
+        Ok(QueueReceiver {
+            inner: self.inner.clone(),
+            wakeup: wakeup,
+        })
+    }
+
+    /// Queues `msg` and wakes up the receivers.
+    ///
+    /// The message is queued even if waking a receiver fails, in which case
+    /// the other receivers are still woken up and the first error is
+    /// returned. A wakeup that would block means the receiver has plenty
+    /// pending already, so it is not an error.
+    pub fn send(&self, msg: M) -> MioResult<()> {
+        self.inner.queue.lock().unwrap().push_back(msg);
+
+        let mut res = Ok(());
+
+        for wakeup in self.inner.wakeups.lock().unwrap().iter() {
+            match wakeup.wake(1) {
+                Err(ref e) if e.is_would_block() => {}
+                Err(e) => {
+                    debug!("failed to wake up receiver; err={:?}", e);
+
+                    if res.is_ok() {
+                        res = Err(e);
+                    }
+                }
+                Ok(()) => {}
+            }
+        }
+
+        res
+    }
+
+    /// Returns the number of messages waiting to be received.
+    pub fn len(&self) -> usize {
+        self.inner.queue.lock().unwrap().len()
+    }
+}
+
+impl<M> Clone for SharedQueue<M> {
+    fn clone(&self) -> SharedQueue<M> {
+        SharedQueue { inner: self.inner.clone() }
+    }
+}
+
+/// One consumer of a `SharedQueue`. It is readable while messages may be
+/// waiting; the readable handler calls `recv` until it returns `None`.
+pub struct QueueReceiver<M> {
+    inner: Arc<Inner<M>>,
+    wakeup: Arc<EventFd>,
+}
+
+impl<M: Send> QueueReceiver<M> {
+    /// Dequeues the next message, or returns `None` once the queue is empty,
+    /// which also clears the wakeup of this receiver.
+    pub fn recv(&self) -> Option<M> {
+        if let Some(msg) = self.pop() {
+            return Some(msg);
+        }
+
+        // Clear the wakeup before looking again, so that a message sent in
+        // between wakes the receiver up again instead of being missed
+        let _ = self.wakeup.reset();
+        self.pop()
+    }
+
+    fn pop(&self) -> Option<M> {
+        self.inner.queue.lock().unwrap().pop_front()
+    }
+}
+
+impl<M> IoHandle for QueueReceiver<M> {
+    fn desc(&self) -> &os::IoDesc {
+        self.wakeup.desc()
+    }
+}
+
+#[unsafe_destructor]
+impl<M> Drop for QueueReceiver<M> {
+    fn drop(&mut self) {
+        let ptr = &*self.wakeup as *const EventFd;
+
+        self.inner.wakeups.lock().unwrap()
+            .retain(|wakeup| &**wakeup as *const EventFd != ptr);
+    }
+}
@@ -0,0 +1,223 @@
+//! Helpers for testing code built on mio without real sockets
+//!
+//! Only built with the `testing` feature, which is meant to be enabled
+//! for the tests of a crate rather than for its release builds.
+
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::VecDeque;
+use buf::{Buf, MutBuf};
+use error::{MioResult, MioError};
+use io::{IoReader, IoWriter, NonBlock};
+use io::NonBlock::{Ready, WouldBlock};
+
+/// An in-memory stream implementing `IoReader` and `IoWriter`, the same
+/// interface `TcpSocket` offers, so that protocol logic written against
+/// those traits can be driven deterministically in tests.
+///
+/// What reads return is scripted up front: every `push_read` chunk is
+/// returned by a separate read, which simulates data arriving in pieces,
+/// `push_read_would_block` makes one read fail with `WouldBlock` and
+/// `push_eof` ends the stream. Once the script runs out, reads would block,
+/// like they do on an idle socket.
+///
+/// Writes are accepted in full unless limited with `push_write_limit` or
+/// `push_write_would_block`, each of which applies to a single write. The
+/// accepted bytes are collected and returned by `take_written`.
+pub struct MemStream {
+    inner: RefCell<Inner>
+}
+
+struct Inner {
+    reads: VecDeque<ReadStep>,
+    writes: VecDeque<WriteStep>,
+    written: Vec<u8>,
+}
+
+enum ReadStep {
+    Data(Vec<u8>),
+    WouldBlock,
+    Eof,
+}
+
+enum WriteStep {
+    Limit(usize),
+    WouldBlock,
+}
+
+impl MemStream {
+    pub fn new() -> MemStream {
+        MemStream {
+            inner: RefCell::new(Inner {
+                reads: VecDeque::new(),
+                writes: VecDeque::new(),
+                written: Vec::new(),
+            })
+        }
+    }
+
+    /// Queues `data` to be returned by a single read. A read with a smaller
+    /// buffer only consumes part of it, and the rest is returned next.
+    pub fn push_read(&self, data: &[u8]) {
+        self.inner.borrow_mut().reads.push_back(ReadStep::Data(data.to_vec()));
+    }
+
+    /// Makes the next read, once the data queued before is consumed, return
+    /// `WouldBlock`.
+    pub fn push_read_would_block(&self) {
+        self.inner.borrow_mut().reads.push_back(ReadStep::WouldBlock);
+    }
+
+    /// Ends the stream: once the data queued before is consumed, reads fail
+    /// with an error for which `is_eof()` is true.
+    pub fn push_eof(&self) {
+        self.inner.borrow_mut().reads.push_back(ReadStep::Eof);
+    }
+
+    /// Makes the next write accept at most `max` bytes.
+    pub fn push_write_limit(&self, max: usize) {
+        self.inner.borrow_mut().writes.push_back(WriteStep::Limit(max));
+    }
+
+    /// Makes the next write return `WouldBlock`.
+    pub fn push_write_would_block(&self) {
+        self.inner.borrow_mut().writes.push_back(WriteStep::WouldBlock);
+    }
+
+    /// Returns the scripted reads that have not been consumed yet, counting
+    /// each chunk, `WouldBlock` and EOF as one.
+    pub fn pending_reads(&self) -> usize {
+        self.inner.borrow().reads.len()
+    }
+
+    /// Returns and clears everything written so far.
+    pub fn take_written(&self) -> Vec<u8> {
+        let mut inner = self.inner.borrow_mut();
+        let written = inner.written.clone();
+        inner.written.clear();
+        written
+    }
+}
+
+impl IoReader for MemStream {
+    fn read<B: MutBuf>(&self, buf: &mut B) -> MioResult<NonBlock<usize>> {
+        let res = self.read_slice(buf.mut_bytes());
+
+        if let Ok(Ready(cnt)) = res {
+            buf.advance(cnt);
+        }
+
+        res
+    }
+
+    fn read_slice(&self, dst: &mut [u8]) -> MioResult<NonBlock<usize>> {
+        let mut inner = self.inner.borrow_mut();
+
+        let step = match inner.reads.pop_front() {
+            Some(step) => step,
+            None => return Ok(WouldBlock)
+        };
+
+        match step {
+            ReadStep::Data(data) => {
+                let cnt = cmp::min(dst.len(), data.len());
+
+                for (d, s) in dst.iter_mut().zip(data.iter()) {
+                    *d = *s;
+                }
+
+                // Put back whatever did not fit
+                if cnt < data.len() {
+                    inner.reads.push_front(ReadStep::Data(data[cnt..].to_vec()));
+                }
+
+                Ok(Ready(cnt))
+            }
+            ReadStep::WouldBlock => Ok(WouldBlock),
+            ReadStep::Eof => {
+                // Like a socket, the stream stays at EOF
+                inner.reads.push_front(ReadStep::Eof);
+                Err(MioError::eof())
+            }
+        }
+    }
+}
+
+impl IoWriter for MemStream {
+    fn write<B: Buf>(&self, buf: &mut B) -> MioResult<NonBlock<usize>> {
+        let res = self.write_slice(buf.bytes());
+
+        if let Ok(Ready(cnt)) = res {
+            buf.advance(cnt);
+        }
+
+        res
+    }
+
+    fn write_slice(&self, src: &[u8]) -> MioResult<NonBlock<usize>> {
+        let mut inner = self.inner.borrow_mut();
+
+        let cnt = match inner.writes.pop_front() {
+            Some(WriteStep::Limit(max)) => cmp::min(max, src.len()),
+            Some(WriteStep::WouldBlock) => return Ok(WouldBlock),
+            None => src.len()
+        };
+
+        inner.written.push_all(&src[..cnt]);
+        Ok(Ready(cnt))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buf::{ByteBuf, SliceBuf, Buf};
+    use io::{IoReader, IoWriter};
+    use super::MemStream;
+
+    #[test]
+    fn test_scripted_reads() {
+        let stream = MemStream::new();
+
+        stream.push_read(b"hello");
+        stream.push_read_would_block();
+        stream.push_read(b" world");
+        stream.push_eof();
+
+        let mut buf = ByteBuf::mut_with_capacity(3);
+
+        // A short buffer leaves the rest of the chunk for the next read
+        assert_eq!(3, stream.read(&mut buf).unwrap().unwrap());
+        assert_eq!(b"hel", buf.flip().bytes());
+
+        let mut dst = [0u8; 64];
+        assert_eq!(2, stream.read_slice(&mut dst).unwrap().unwrap());
+        assert_eq!(b"lo", &dst[..2]);
+
+        assert!(stream.read_slice(&mut dst).unwrap().would_block());
+        assert_eq!(6, stream.read_slice(&mut dst).unwrap().unwrap());
+
+        assert!(stream.read_slice(&mut dst).unwrap_err().is_eof());
+        assert!(stream.read_slice(&mut dst).unwrap_err().is_eof());
+    }
+
+    #[test]
+    fn test_scripted_writes() {
+        let stream = MemStream::new();
+
+        stream.push_write_limit(2);
+        stream.push_write_would_block();
+
+        let mut buf = SliceBuf::wrap(b"hello");
+
+        assert_eq!(2, stream.write(&mut buf).unwrap().unwrap());
+        assert!(stream.write(&mut buf).unwrap().would_block());
+        assert_eq!(3, stream.write(&mut buf).unwrap().unwrap());
+        assert!(!buf.has_remaining());
+
+        assert_eq!(b"hello", stream.take_written().as_slice());
+        assert!(stream.take_written().is_empty());
+
+        // Nothing scripted: reads would block
+        assert!(stream.read_slice(&mut [0u8; 8]).unwrap().would_block());
+    }
+}
@@ -6,7 +6,7 @@ use time::precise_time_ns;
 use os::token::Token;
 use util::Slab;
 
-use self::TimerErrorKind::TimerOverflow;
+use self::TimerErrorKind::{TimerOverflow, TimerDisabled};
 
 const EMPTY: Token = Token(usize::MAX);
 const NS_PER_MS: u64 = 1_000_000;
@@ -33,11 +33,19 @@ pub struct Timer<T> {
     tick: u64,
     // The next entry to possibly timeout
     next: Token,
+    // Entries of the current tick found expired by `tick_to_at`, along with
+    // the time they were scheduled for. Sorted with the next one to fire
+    // last.
+    due: Vec<(u64, Token)>,
     // Masks the target tick to get the slot
     mask: u64,
+    // Highest number of pending timeouts so far
+    peak: usize,
+    // Number of timeouts rejected because the timer was full
+    overflows: u64,
 }
 
-#[derive(Copy)]
+#[derive(Copy, Debug)]
 pub struct Timeout {
     // Reference into the timer entry slab
     token: Token,
@@ -57,7 +65,10 @@ impl<T> Timer<T> {
             start: 0,
             tick: 0,
             next: EMPTY,
-            mask: (slots as u64) - 1
+            due: Vec::new(),
+            mask: (slots as u64) - 1,
+            peak: 0,
+            overflows: 0,
         }
     }
 
@@ -65,6 +76,35 @@ impl<T> Timer<T> {
         self.entries.count()
     }
 
+    // Walks the whole wheel to count the timeouts that are more than a turn
+    // away, so it costs O(pending timeouts)
+    pub fn stats(&self) -> TimerStats {
+        let slots = self.wheel.len() as u64;
+        let mut beyond_wheel = 0;
+
+        for &head in self.wheel.iter() {
+            let mut curr = head;
+
+            while curr != EMPTY {
+                let links = self.entries[curr].links;
+
+                if links.tick >= self.tick + slots {
+                    beyond_wheel += 1;
+                }
+
+                curr = links.next;
+            }
+        }
+
+        TimerStats {
+            scheduled: self.entries.count(),
+            peak: self.peak,
+            capacity: self.entries.count() + self.entries.remaining(),
+            overflows: self.overflows,
+            beyond_wheel: beyond_wheel,
+        }
+    }
+
     // Number of ms remaining until the next tick
     pub fn next_tick_in_ms(&self) -> u64 {
         let now = self.now_ms();
@@ -77,6 +117,56 @@ impl<T> Timer<T> {
         nxt - now
     }
 
+    // Number of ms remaining until the next pending timeout is due, or None
+    // if there are no pending timeouts. Timeouts more than a full turn of the
+    // wheel away are not searched for; the time until the end of the turn is
+    // returned instead, after which the search starts over.
+    pub fn next_timeout_in_ms(&self) -> Option<u64> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let tick = self.next_timeout_tick()
+            .unwrap_or(self.tick + self.wheel.len() as u64);
+
+        let at = self.start + tick * self.tick_ms;
+        let now = self.now_ms();
+
+        if at <= now {
+            return Some(0);
+        }
+
+        Some(at - now)
+    }
+
+    // The tick at which the next pending timeout is due, looking at most one
+    // turn of the wheel ahead
+    fn next_timeout_tick(&self) -> Option<u64> {
+        if self.entries.is_empty() {
+            return None;
+        }
+
+        let slots = self.wheel.len() as u64;
+
+        for tick in range(self.tick, self.tick + slots) {
+            let mut curr = self.wheel[self.slot_for(tick)];
+
+            // Slots are shared by ticks that are a full turn apart, so only
+            // entries for this turn count
+            while curr != EMPTY {
+                let links = self.entries[curr].links;
+
+                if links.tick <= tick {
+                    return Some(tick);
+                }
+
+                curr = links.next;
+            }
+        }
+
+        None
+    }
+
     /*
      *
      * ===== Initialization =====
@@ -105,18 +195,18 @@ impl<T> Timer<T> {
         self.timeout_at_ms(token, at)
     }
 
-    pub fn timeout_at_ms(&mut self, token: T, mut at: u64) -> TimerResult<Timeout> {
+    pub fn timeout_at_ms(&mut self, token: T, at: u64) -> TimerResult<Timeout> {
         // Make relative to start
-        at -= self.start;
+        let rel = at - self.start;
         // Calculate tick
-        let mut tick = (at + self.tick_ms - 1) / self.tick_ms;
+        let mut tick = (rel + self.tick_ms - 1) / self.tick_ms;
 
         // Always target at least 1 tick in the future
         if tick <= self.tick {
             tick = self.tick + 1;
         }
 
-        self.insert(token, tick)
+        self.insert(token, tick, at)
     }
 
     pub fn clear(&mut self, timeout: Timeout) -> bool {
@@ -135,15 +225,21 @@ impl<T> Timer<T> {
         true
     }
 
-    fn insert(&mut self, token: T, tick: u64) -> TimerResult<Timeout> {
+    fn insert(&mut self, token: T, tick: u64, at: u64) -> TimerResult<Timeout> {
         // Get the slot for the requested tick
         let slot = (tick & self.mask) as usize;
         let curr = self.wheel[slot];
 
         // Insert the new entry
-        let token = try!(
-            self.entries.insert(Entry::new(token, tick, curr))
-            .map_err(|_| TimerError::overflow()));
+        let token = match self.entries.insert(Entry::new(token, tick, at, curr)) {
+            Ok(token) => token,
+            Err(_) => {
+                self.overflows += 1;
+                return Err(TimerError::overflow());
+            }
+        };
+
+        self.peak = max(self.peak, self.entries.count());
 
         if curr != EMPTY {
             // If there was a previous entry, set its prev pointer to the new
@@ -227,6 +323,66 @@ impl<T> Timer<T> {
         None
     }
 
+    // Like `tick_to`, also returning the time, in milliseconds, the timeout
+    // was scheduled for. Timeouts expiring in the same tick are returned in
+    // the order of their scheduled times, and, for equal times, in the order
+    // they were set. Not to be mixed with `tick_to` on the same timer.
+    pub fn tick_to_at(&mut self, now: u64) -> Option<(T, u64)> {
+        debug!("tick_to_at; now={}; tick={}", now, self.tick);
+
+        loop {
+            // Expired entries of the current tick, in order, once the whole
+            // slot has been walked
+            if self.next == EMPTY {
+                while let Some((at, curr)) = self.due.pop() {
+                    let links = match self.entries.get(curr) {
+                        Some(e) => e.links,
+                        // Cleared in the meantime
+                        None => continue
+                    };
+
+                    // Cleared, and the entry reused by a timeout of a later tick
+                    if links.tick > self.tick {
+                        continue;
+                    }
+
+                    debug!("triggering; token={:?}", curr);
+
+                    self.unlink(&links, curr);
+
+                    return self.entries.remove(curr)
+                        .map(|e| (e.token, at));
+                }
+            }
+
+            if self.tick > now {
+                return None;
+            }
+
+            let curr = self.next;
+
+            if curr == EMPTY {
+                self.tick += 1;
+                self.next = self.wheel[self.slot_for(self.tick)];
+                continue;
+            }
+
+            let links = self.entries[curr].links;
+
+            if links.tick <= self.tick {
+                self.due.push((links.at, curr));
+            }
+
+            self.next = links.next;
+
+            if self.next == EMPTY && !self.due.is_empty() {
+                // The slot lists the newest entries first, so the stable
+                // sort keeps the ones set earlier last
+                self.due.sort_by(|a, b| b.0.cmp(&a.0));
+            }
+        }
+    }
+
     /*
      *
      * ===== Misc =====
@@ -252,10 +408,16 @@ impl<T> Timer<T> {
 
     #[inline]
     fn now_ms(&self) -> u64 {
-        precise_time_ns() / NS_PER_MS
+        now_ms()
     }
 }
 
+// The monotonic clock timeouts are measured against, in milliseconds
+#[inline]
+pub fn now_ms() -> u64 {
+    precise_time_ns() / NS_PER_MS
+}
+
 // Doubly linked list of timer entries. Allows for efficient insertion /
 // removal of timeouts.
 struct Entry<T> {
@@ -264,11 +426,12 @@ struct Entry<T> {
 }
 
 impl<T> Entry<T> {
-    fn new(token: T, tick: u64, next: Token) -> Entry<T> {
+    fn new(token: T, tick: u64, at: u64, next: Token) -> Entry<T> {
         Entry {
             token: token,
             links: EntryLinks {
                 tick: tick,
+                at: at,
                 prev: EMPTY,
                 next: next,
             },
@@ -279,10 +442,34 @@ impl<T> Entry<T> {
 #[derive(Copy)]
 struct EntryLinks {
     tick: u64,
+    // Time the timeout was scheduled for, in milliseconds
+    at: u64,
     prev: Token,
     next: Token
 }
 
+/// A snapshot of the timer's occupancy, see
+/// [EventLoop::timer_stats](struct.EventLoop.html#method.timer_stats).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct TimerStats {
+    /// Number of pending timeouts.
+    pub scheduled: usize,
+    /// Highest number of pending timeouts since the event loop was created.
+    pub peak: usize,
+    /// Maximum number of pending timeouts, `timer_capacity` rounded up to a
+    /// power of two.
+    pub capacity: usize,
+    /// Number of timeouts that could not be scheduled because `capacity`
+    /// timeouts were already pending. Each of them also failed with an
+    /// overflow error.
+    pub overflows: u64,
+    /// Number of pending timeouts due more than a full turn of the wheel
+    /// away. They share slots with nearer timeouts and are skipped over on
+    /// every turn until they are due, so a high count suggests a larger
+    /// `timer_wheel_size`.
+    pub beyond_wheel: usize,
+}
+
 pub type TimerResult<T> = Result<T, TimerError>;
 
 #[derive(Debug)]
@@ -298,11 +485,28 @@ impl TimerError {
             desc: "too many timer entries"
         }
     }
+
+    #[doc(hidden)]
+    pub fn disabled() -> TimerError {
+        TimerError {
+            kind: TimerDisabled,
+            desc: "timers are disabled"
+        }
+    }
+
+    /// Returns true if the event loop was configured without timers.
+    pub fn is_disabled(&self) -> bool {
+        match self.kind {
+            TimerDisabled => true,
+            _ => false
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum TimerErrorKind {
     TimerOverflow,
+    TimerDisabled,
 }
 
 #[cfg(test)]
@@ -469,6 +673,90 @@ mod test {
         assert_eq!(0, t.count());
     }
 
+    #[test]
+    pub fn test_same_tick_in_scheduled_order() {
+        let mut t = timer();
+
+        // All three are due in the second tick
+        t.timeout_at_ms("c", 190).unwrap();
+        t.timeout_at_ms("a", 110).unwrap();
+        t.timeout_at_ms("b", 150).unwrap();
+        t.timeout_at_ms("b2", 150).unwrap();
+
+        let tick = t.ms_to_tick(200);
+        assert_eq!(Some(("a", 110)), t.tick_to_at(tick));
+        assert_eq!(Some(("b", 150)), t.tick_to_at(tick));
+        assert_eq!(Some(("b2", 150)), t.tick_to_at(tick));
+        assert_eq!(Some(("c", 190)), t.tick_to_at(tick));
+        assert_eq!(None, t.tick_to_at(tick));
+    }
+
+    #[test]
+    pub fn test_clearing_timeout_of_current_tick() {
+        let mut t = timer();
+
+        t.timeout_at_ms("a", 110).unwrap();
+        let b = t.timeout_at_ms("b", 150).unwrap();
+
+        // "b" is already known to be due, but is cleared before it fires
+        let tick = t.ms_to_tick(200);
+        assert_eq!(Some(("a", 110)), t.tick_to_at(tick));
+        assert!(t.clear(b));
+
+        assert_eq!(None, t.tick_to_at(tick));
+        assert_eq!(0, t.count());
+    }
+
+    #[test]
+    pub fn test_next_timeout_tick() {
+        let mut t = timer();
+
+        assert_eq!(None, t.next_timeout_tick());
+
+        t.timeout_at_ms("a", 350).unwrap();
+        t.timeout_at_ms("b", 100 + 2 * TICK * SLOTS as u64).unwrap();
+
+        // Rounded up to the tick
+        assert_eq!(Some(4), t.next_timeout_tick());
+
+        let tick = t.ms_to_tick(400);
+        assert_eq!(Some("a"), t.tick_to(tick));
+
+        // "b" is more than a turn of the wheel away
+        assert_eq!(None, t.next_timeout_tick());
+        assert_eq!(1, t.count());
+    }
+
+    #[test]
+    pub fn test_stats() {
+        let mut t = timer();
+        let mut timeouts = vec![];
+
+        // Fill the timer, ticks 1 to 32
+        for i in range(0, 32u64) {
+            timeouts.push(t.timeout_at_ms("a", (i + 1) * TICK).unwrap());
+        }
+
+        assert!(t.timeout_at_ms("b", 100).is_err());
+
+        let stats = t.stats();
+        assert_eq!(32, stats.scheduled);
+        assert_eq!(32, stats.peak);
+        assert_eq!(32, stats.capacity);
+        assert_eq!(1, stats.overflows);
+        // Ticks 16 and up are past the first turn of the 16 slot wheel
+        assert_eq!(17, stats.beyond_wheel);
+
+        for to in timeouts.into_iter().take(8) {
+            assert!(t.clear(to));
+        }
+
+        let stats = t.stats();
+        assert_eq!(24, stats.scheduled);
+        assert_eq!(32, stats.peak);
+        assert_eq!(17, stats.beyond_wheel);
+    }
+
     const TICK: u64 = 100;
     const SLOTS: usize = 16;
 
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+use event_loop::EventLoopSender;
+use net::tcp::TcpSocket;
+use notify::NotifyError;
+
+/// Spreads accepted connections over a set of event loops, typically one
+/// per core, for servers that accept on one thread and handle connections
+/// on others.
+///
+/// Each connection goes to the loop currently handling the fewest of them.
+/// A loop is added with `add_loop`, which returns the `LoadHandle` that loop
+/// uses to report the connections it is done with.
+///
+/// # Handing off the connection
+///
+/// `dispatch` moves the socket, and with it ownership of its file
+/// descriptor, into a message sent over the chosen loop's channel. The
+/// receiving loop is then responsible for registering the socket in its
+/// `notify` callback, and closes it by dropping it. The accepting thread
+/// must not register the socket itself. If the message cannot be sent, it is
+/// handed back in the error, so the socket is never leaked or closed behind
+/// the caller's back.
+pub struct AcceptCoordinator<M: Send> {
+    loops: Vec<(EventLoopSender<M>, Arc<AtomicUsize>)>,
+    wrap: fn(TcpSocket) -> M,
+}
+
+impl<M: Send> AcceptCoordinator<M> {
+    /// Creates a coordinator without any loops. `wrap` turns an accepted
+    /// socket into the message type of the loops' channels.
+    pub fn new(wrap: fn(TcpSocket) -> M) -> AcceptCoordinator<M> {
+        AcceptCoordinator {
+            loops: Vec::new(),
+            wrap: wrap,
+        }
+    }
+
+    /// Adds a loop that connections can be routed to.
+    pub fn add_loop(&mut self, sender: EventLoopSender<M>) -> LoadHandle {
+        let load = Arc::new(AtomicUsize::new(0));
+        self.loops.push((sender, load.clone()));
+
+        LoadHandle { load: load }
+    }
+
+    /// Sends `sock` to the least loaded loop, returning the index of that
+    /// loop in the order they were added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no loop has been added.
+    pub fn dispatch(&self, sock: TcpSocket) -> Result<usize, NotifyError<M>> {
+        assert!(!self.loops.is_empty(), "no event loop to dispatch to");
+
+        let mut idx = 0;
+
+        for i in range(1, self.loops.len()) {
+            if self.loops[i].1.load(SeqCst) < self.loops[idx].1.load(SeqCst) {
+                idx = i;
+            }
+        }
+
+        let (ref sender, ref load) = self.loops[idx];
+
+        // Count the connection first, so that a loop handling it right away
+        // never brings the count below zero
+        load.fetch_add(1, SeqCst);
+
+        match sender.send((self.wrap)(sock)) {
+            Ok(()) => Ok(idx),
+            Err(e) => {
+                load.fetch_sub(1, SeqCst);
+                Err(e)
+            }
+        }
+    }
+
+    /// The number of connections each loop is handling.
+    pub fn loads(&self) -> Vec<usize> {
+        self.loops.iter().map(|&(_, ref load)| load.load(SeqCst)).collect()
+    }
+}
+
+/// Held by an event loop that receives connections from an
+/// `AcceptCoordinator`, to report when it is done with one.
+#[derive(Clone)]
+pub struct LoadHandle {
+    load: Arc<AtomicUsize>,
+}
+
+impl LoadHandle {
+    /// Reports that a dispatched connection has been closed.
+    pub fn done(&self) {
+        self.load.fetch_sub(1, SeqCst);
+    }
+
+    /// The number of dispatched connections that have not been reported
+    /// done yet.
+    pub fn load(&self) -> usize {
+        self.load.load(SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use event_loop::EventLoop;
+    use net::tcp::TcpSocket;
+    use super::AcceptCoordinator;
+
+    fn wrap(sock: TcpSocket) -> TcpSocket {
+        sock
+    }
+
+    #[test]
+    fn test_dispatch_to_least_loaded() {
+        let mut a = EventLoop::<usize, TcpSocket>::new().unwrap();
+        let mut b = EventLoop::<usize, TcpSocket>::new().unwrap();
+
+        let mut coordinator = AcceptCoordinator::new(wrap);
+        let load_a = coordinator.add_loop(a.channel());
+        let _load_b = coordinator.add_loop(b.channel());
+
+        for _ in range(0, 4us) {
+            coordinator.dispatch(TcpSocket::v4().unwrap()).unwrap();
+        }
+
+        assert_eq!(vec![2, 2], coordinator.loads());
+
+        // Once the first loop is done with a connection, it gets the next
+        load_a.done();
+        assert_eq!(0, coordinator.dispatch(TcpSocket::v4().unwrap()).unwrap());
+
+        assert_eq!(3, a.drain_notifications().len());
+        assert_eq!(2, b.drain_notifications().len());
+    }
+}
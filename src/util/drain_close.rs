@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use error::MioResult;
+use event_loop::EventLoop;
+use io::IoReader;
+use io::NonBlock::{Ready, WouldBlock};
+use net::tcp::TcpSocket;
+use os::event::{Interest, PollOpt};
+use os::token::Token;
+
+/// Finishes closing connections on which
+/// [TcpSocket::begin_close](../net/tcp/struct.TcpSocket.html#method.begin_close)
+/// was called: whatever the peer still sends is read and discarded, and
+/// each socket is closed once the peer closes its end, or the connection
+/// fails.
+///
+/// It is driven by the handler:
+///
+/// * `start` takes a socket over, registered with its token in place of any
+///   previous registration.
+/// * `readable` must be called from `Handler::readable` for the tokens that
+///   `contains` reports, and returns true once the connection is closed.
+///   The token is free to be reused at that point.
+/// * `close` closes a connection right away, for example from
+///   `Handler::deadline_expired` when a deadline set with
+///   `EventLoop::set_deadline` bounds how long a peer that never closes is
+///   waited for. Clear that deadline once `readable` returns true.
+pub struct DrainClose {
+    socks: HashMap<Token, TcpSocket>,
+}
+
+impl DrainClose {
+    pub fn new() -> DrainClose {
+        DrainClose {
+            socks: HashMap::new(),
+        }
+    }
+
+    /// Takes `sock` over and registers it with `token`.
+    pub fn start<T, M: Send>(&mut self, event_loop: &mut EventLoop<T, M>, sock: TcpSocket, token: Token) -> MioResult<()> {
+        // The handle was most likely registered by the handler
+        let _ = event_loop.deregister(&sock);
+
+        // Edge triggered: registering reports data that is already waiting,
+        // and each new event is drained down to WouldBlock
+        try!(event_loop.register_opt(&sock, token, Interest::readable() | Interest::hup(), PollOpt::edge()));
+
+        self.socks.insert(token, sock);
+        Ok(())
+    }
+
+    /// Returns true if the connection of `token` is being drained.
+    pub fn contains(&self, token: Token) -> bool {
+        self.socks.contains_key(&token)
+    }
+
+    /// Number of connections being drained.
+    pub fn len(&self) -> usize {
+        self.socks.len()
+    }
+
+    /// Reads and discards the data waiting on the connection of `token`, and
+    /// closes it if the peer closed its end. Returns true if the connection
+    /// is closed, including if `token` was not being drained.
+    pub fn readable<T, M: Send>(&mut self, event_loop: &mut EventLoop<T, M>, token: Token) -> bool {
+        let mut done = false;
+
+        match self.socks.get(&token) {
+            Some(sock) => {
+                let mut buf = [0u8; 4096];
+
+                loop {
+                    match sock.read_slice(&mut buf) {
+                        Ok(Ready(_)) => {}
+                        Ok(WouldBlock) => break,
+                        // EOF, or the connection failed
+                        Err(_) => {
+                            done = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            None => return true
+        }
+
+        if done {
+            self.close(event_loop, token);
+        }
+
+        done
+    }
+
+    /// Closes the connection of `token` without waiting for the peer.
+    /// Returns false if it was not being drained.
+    pub fn close<T, M: Send>(&mut self, event_loop: &mut EventLoop<T, M>, token: Token) -> bool {
+        match self.socks.remove(&token) {
+            Some(sock) => {
+                debug!("connection closed; token={:?}", token);
+
+                let _ = event_loop.deregister(&sock);
+                true
+            }
+            None => false
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use os::event::Interest;
+
+/// Computes the interest to register for a handle, given whether the code
+/// driving it wants to read, write, or both.
+///
+/// This is meant for protocols layered on top of a socket, TLS being the
+/// common case, where the direction of the next socket operation does not
+/// follow the direction of the application's operation: a write may first
+/// need to read a handshake or renegotiation message, and a read may need to
+/// flush one. After every operation, ask the protocol layer which directions
+/// it is blocked on and reregister with the result:
+///
+/// ```ignore
+/// let interest = util::interest_for(tls.wants_read(), tls.wants_write());
+/// event_loop.reregister(&sock, token, interest, PollOpt::edge() | PollOpt::oneshot()).unwrap();
+/// ```
+///
+/// Reregistering with `edge | oneshot` this way does not lose wakeups:
+/// oneshot disarms the handle after each event, and rearming it makes the
+/// kernel check the current readiness, so data that arrived between the
+/// event and the reregistration is still reported.
+///
+/// Returns `Interest::none()` when neither direction is wanted.
+pub fn interest_for(want_read: bool, want_write: bool) -> Interest {
+    let mut interest = Interest::none();
+
+    if want_read {
+        interest.insert(Interest::readable());
+    }
+
+    if want_write {
+        interest.insert(Interest::writable());
+    }
+
+    interest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::interest_for;
+    use os::event::Interest;
+
+    #[test]
+    fn test_interest_for() {
+        assert_eq!(Interest::none(), interest_for(false, false));
+        assert_eq!(Interest::readable(), interest_for(true, false));
+        assert_eq!(Interest::writable(), interest_for(false, true));
+        assert_eq!(Interest::readable() | Interest::writable(), interest_for(true, true));
+    }
+}
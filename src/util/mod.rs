@@ -1,7 +1,19 @@
 //! Utilities for non-blocking IO programs
 
+pub use self::accept_coordinator::{AcceptCoordinator, LoadHandle};
+pub use self::drain_close::DrainClose;
+pub use self::interest::interest_for;
 pub use self::mpmc_bounded_queue::Queue as BoundedQueue;
+pub use self::reconnector::{Reconnector, Backoff, Reconnect};
 pub use self::slab::Slab;
+pub use self::token_pool::TokenPool;
+pub use self::write_backlog::{WriteBacklog, OverflowPolicy, Pushed};
 
+mod accept_coordinator;
+mod drain_close;
+mod interest;
 mod mpmc_bounded_queue;
+mod reconnector;
 mod slab;
+mod token_pool;
+mod write_backlog;
@@ -0,0 +1,193 @@
+use std::cmp;
+use std::time::Duration;
+use std::usize;
+use error::{MioResult, MioError};
+use event_loop::EventLoop;
+use net::SockAddr;
+use net::AddressFamily::{Inet, Inet6, Unix};
+use net::tcp::TcpSocket;
+use os::event::{Interest, PollOpt, WriteHint};
+use os::token::Token;
+
+/// How long a `Reconnector` waits before each retry.
+#[derive(Copy, Clone, Debug)]
+pub struct Backoff {
+    initial_ms: u64,
+    max_ms: u64,
+    max_retries: usize,
+}
+
+impl Backoff {
+    /// Waits `initial` before the first retry and doubles the wait after
+    /// every failed attempt, up to `max`. Retries forever unless limited
+    /// with `max_retries`.
+    pub fn exponential(initial: Duration, max: Duration) -> Backoff {
+        Backoff {
+            initial_ms: cmp::max(0, initial.num_milliseconds()) as u64,
+            max_ms: cmp::max(0, max.num_milliseconds()) as u64,
+            max_retries: usize::MAX,
+        }
+    }
+
+    /// Sets the number of retries after which the reconnector gives up.
+    pub fn max_retries(mut self, max: usize) -> Backoff {
+        self.max_retries = max;
+        self
+    }
+
+    // The wait before the given retry, counting from 1
+    fn delay(&self, retry: usize) -> Duration {
+        let mut ms = self.initial_ms;
+
+        for _ in range(1, retry) {
+            if ms >= self.max_ms {
+                break;
+            }
+
+            ms *= 2;
+        }
+
+        Duration::milliseconds(cmp::min(ms, self.max_ms) as i64)
+    }
+}
+
+/// The state of a `Reconnector` after one of its methods was called.
+#[derive(Debug)]
+pub enum Reconnect {
+    /// An attempt is in progress. Its outcome is reported by a writable
+    /// event for the reconnector's token.
+    Connecting,
+    /// The connection is established. The socket is still registered with
+    /// the reconnector's token, as oneshot and disarmed: reregister it with
+    /// the interest the connection needs.
+    Connected(TcpSocket),
+    /// The attempt failed and a retry is scheduled in this long.
+    Retrying(Duration),
+    /// The attempt failed and no retries are left. Holds the error of the
+    /// last attempt.
+    GaveUp(MioError),
+}
+
+/// Connects to an address, retrying with a backoff while the attempts fail,
+/// for clients that have to keep a connection to a server that may not be
+/// up yet.
+///
+/// The reconnector is driven by the handler, using a token for the socket
+/// and a value of the loop's timeout type for the retries:
+///
+/// * `connect` starts the first attempt, and must be called again from
+///   `Handler::timeout` when the retry timeout fires.
+/// * `writable` must be called from `Handler::writable_hint` for the
+///   socket's token, and reports whether the attempt succeeded.
+///
+/// Each method returns a `Reconnect` telling the handler what happened. A
+/// failed attempt closes its socket, so every retry uses a new one. If the
+/// retry cannot be scheduled, the error is returned instead: `Unsupported`
+/// when the event loop has no timers, `TimerOverflow` when its timer is
+/// full.
+pub struct Reconnector<T> {
+    addr: SockAddr,
+    token: Token,
+    timeout: T,
+    backoff: Backoff,
+    // Failed attempts since the last successful one
+    failures: usize,
+    sock: Option<TcpSocket>,
+}
+
+impl<T: Clone> Reconnector<T> {
+    /// Creates a reconnector for `addr`. Sockets are registered with
+    /// `token`, and retries are scheduled by setting a timeout for
+    /// `timeout`.
+    pub fn new(addr: SockAddr, token: Token, timeout: T, backoff: Backoff) -> Reconnector<T> {
+        Reconnector {
+            addr: addr,
+            token: token,
+            timeout: timeout,
+            backoff: backoff,
+            failures: 0,
+            sock: None,
+        }
+    }
+
+    /// Number of attempts that failed since the last successful one.
+    pub fn failures(&self) -> usize {
+        self.failures
+    }
+
+    /// Starts an attempt, with a new socket.
+    pub fn connect<M: Send>(&mut self, event_loop: &mut EventLoop<T, M>) -> MioResult<Reconnect> {
+        let sock = try!(match self.addr.family() {
+            Inet => TcpSocket::v4(),
+            Inet6 => TcpSocket::v6(),
+            Unix => Err(MioError::unsupported())
+        });
+
+        // Some failures are reported right away
+        if let Err(e) = sock.connect(&self.addr) {
+            return self.failed(event_loop, e);
+        }
+
+        try!(event_loop.register_opt(&sock, self.token, Interest::writable(), PollOpt::edge() | PollOpt::oneshot()));
+        self.sock = Some(sock);
+
+        Ok(Reconnect::Connecting)
+    }
+
+    /// Checks the outcome of the attempt in progress, once its socket is
+    /// writable.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no attempt is in progress.
+    pub fn writable<M: Send>(&mut self, event_loop: &mut EventLoop<T, M>, hint: WriteHint) -> MioResult<Reconnect> {
+        let sock = self.sock.take().expect("no connection attempt in progress");
+
+        debug!("reconnector writable; addr={:?}; hint={:?}", self.addr, hint);
+
+        match sock.take_socket_error() {
+            Ok(()) => {
+                self.failures = 0;
+                Ok(Reconnect::Connected(sock))
+            }
+            Err(e) => {
+                let _ = event_loop.deregister(&sock);
+                self.failed(event_loop, e)
+            }
+        }
+    }
+
+    fn failed<M: Send>(&mut self, event_loop: &mut EventLoop<T, M>, err: MioError) -> MioResult<Reconnect> {
+        self.failures += 1;
+
+        if self.failures > self.backoff.max_retries {
+            return Ok(Reconnect::GaveUp(err));
+        }
+
+        let delay = self.backoff.delay(self.failures);
+
+        match event_loop.timeout(self.timeout.clone(), delay) {
+            Ok(_) => Ok(Reconnect::Retrying(delay)),
+            Err(ref e) if e.is_disabled() => Err(MioError::unsupported()),
+            // The timer holds as many timeouts as it has room for
+            Err(_) => Err(MioError::timer_overflow())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use super::Backoff;
+
+    #[test]
+    fn test_exponential_backoff() {
+        let backoff = Backoff::exponential(Duration::milliseconds(10), Duration::milliseconds(50));
+
+        assert_eq!(Duration::milliseconds(10), backoff.delay(1));
+        assert_eq!(Duration::milliseconds(20), backoff.delay(2));
+        assert_eq!(Duration::milliseconds(40), backoff.delay(3));
+        assert_eq!(Duration::milliseconds(50), backoff.delay(4));
+        assert_eq!(Duration::milliseconds(50), backoff.delay(100));
+    }
+}
@@ -1,4 +1,4 @@
-use std::{fmt, mem, ptr, isize};
+use std::{cmp, fmt, mem, ptr, isize};
 use std::num::Int;
 use std::ops::{Index, IndexMut};
 use alloc::heap;
@@ -126,6 +126,39 @@ impl<T> Slab<T> {
         None
     }
 
+    /// Returns the generation of the value stored at the given token, or
+    /// `None` if the slot is not in use. Each time a slot is reused for a
+    /// new value, its generation changes, so a `(Token, generation)` pair
+    /// identifies a value even after its token has been handed out again.
+    pub fn generation(&self, idx: Token) -> Option<u64> {
+        let idx = self.token_to_idx(idx);
+
+        if idx <= MAX {
+            let idx = idx as isize;
+
+            if idx < self.init {
+                let entry = self.entry(idx);
+
+                if entry.in_use() {
+                    return Some(entry.gen);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Like `get`, but returns `None` if the slot has been reused since
+    /// `gen` was obtained from `generation`. Use this to look up the value a
+    /// timeout or other deferred event was meant for, which may have been
+    /// removed and replaced in the meantime.
+    pub fn get_gen(&self, idx: Token, gen: u64) -> Option<&T> {
+        match self.generation(idx) {
+            Some(g) if g == gen => self.get(idx),
+            _ => None
+        }
+    }
+
     pub fn insert(&mut self, val: T) -> Result<Token, T> {
         let idx = self.nxt;
 
@@ -155,6 +188,16 @@ impl<T> Slab<T> {
         Ok(self.idx_to_token(idx))
     }
 
+    /// Replaces the value stored at the given token, returning the previous
+    /// value. The token stays valid and keeps referring to the slot. Returns
+    /// `None`, leaving the slab untouched, if the slot is not in use.
+    pub fn replace(&mut self, idx: Token, val: T) -> Option<T> {
+        match self.get_mut(idx) {
+            Some(v) => Some(mem::replace(v, val)),
+            None => None
+        }
+    }
+
     /// Releases the given slot
     pub fn remove(&mut self, idx: Token) -> Option<T> {
         debug!("removing value; idx={:?}", idx);
@@ -185,6 +228,64 @@ impl<T> Slab<T> {
         }
     }
 
+    /// Moves values out of the end of the slab into the free slots before
+    /// them, so that all values end up in the lowest slots with no holes in
+    /// between. `relocate` is called with the old and the new token of every
+    /// value that moved.
+    ///
+    /// This invalidates the old tokens of the moved values, which are free
+    /// afterwards and will be handed out again by `insert`, so anything
+    /// keyed by them, such as registrations with an event loop, has to be
+    /// updated from `relocate`. A moved value gets a new generation, higher
+    /// than any its new slot or its old one had, so no `(Token, generation)`
+    /// pair obtained before compacting matches it, nor whatever reuses its
+    /// old slot.
+    pub fn compact<F>(&mut self, mut relocate: F) where F: FnMut(Token, Token) {
+        let mut lo = 0;
+        let mut hi = self.init - 1;
+
+        loop {
+            // Lowest free slot
+            while lo < self.len && self.entry(lo).in_use() {
+                lo += 1;
+            }
+
+            // Highest slot in use
+            while hi > lo && !self.entry(hi).in_use() {
+                hi -= 1;
+            }
+
+            if lo >= hi {
+                break;
+            }
+
+            unsafe {
+                let src = self.mem.offset(hi);
+                let dst = self.mem.offset(lo);
+
+                ptr::write(&mut (*dst).val as *mut T, ptr::read(&(*src).val as *const T));
+                (*dst).gen = cmp::max((*dst).gen, (*src).gen) + 1;
+                (*dst).nxt = IN_USE;
+                (*src).nxt = self.init;
+            }
+
+            debug!("relocating value; from={}; to={}", hi, lo);
+            relocate(self.idx_to_token(hi), self.idx_to_token(lo));
+        }
+
+        // The free slots are now the ones past the values. They keep their
+        // generation, so they are threaded into the free list rather than
+        // treated as never used.
+        let mut i = self.len;
+
+        while i < self.init {
+            self.mut_entry(i).nxt = i + 1;
+            i += 1;
+        }
+
+        self.nxt = self.len;
+    }
+
     #[inline]
     fn entry(&self, idx: isize) -> &Entry<T> {
         unsafe { &*self.mem.offset(idx) }
@@ -275,6 +376,8 @@ impl<T> Drop for Slab<T> {
 // Holds the values in the slab.
 struct Entry<T> {
     nxt: isize,
+    // Bumped every time the slot is reused
+    gen: u64,
     val: T
 }
 
@@ -288,6 +391,12 @@ impl<T> Entry<T> {
         unsafe { ptr::write(&mut self.val as *mut T, val); }
         self.nxt = IN_USE;
 
+        if init {
+            self.gen = 0;
+        } else {
+            self.gen += 1;
+        }
+
         // Could be uninitialized memory, but the caller (Slab) should guard
         // not use the return value in those cases.
         ret
@@ -437,6 +546,23 @@ mod tests {
         slab[Token(0)];
     }
 
+    #[test]
+    fn test_replace() {
+        let mut slab = Slab::new(16);
+
+        let t0 = slab.insert("plain".to_string()).unwrap();
+        let t1 = slab.insert("other".to_string()).unwrap();
+
+        assert_eq!(slab.replace(t0, "upgraded".to_string()), Some("plain".to_string()));
+        assert_eq!(slab[t0].as_slice(), "upgraded");
+        assert_eq!(slab[t1].as_slice(), "other");
+        assert_eq!(slab.count(), 2);
+
+        slab.remove(t1);
+        assert_eq!(slab.replace(t1, "gone".to_string()), None);
+        assert!(!slab.contains(t1));
+    }
+
     #[test]
     fn test_contains() {
         let mut slab = Slab::new_starting_at(Token(5),16);
@@ -445,4 +571,88 @@ mod tests {
         let tok = slab.insert(111).unwrap();
         assert!(slab.contains(tok));
     }
+
+    #[test]
+    fn test_generation() {
+        let mut slab = Slab::new(1);
+
+        let t0 = slab.insert(123).unwrap();
+        let gen = slab.generation(t0).unwrap();
+        assert_eq!(slab.get_gen(t0, gen), Some(&123));
+
+        slab.remove(t0);
+        assert_eq!(slab.generation(t0), None);
+        assert_eq!(slab.get_gen(t0, gen), None);
+
+        // The slot is reused, but the old generation no longer matches
+        let t1 = slab.insert(456).unwrap();
+        assert_eq!(t0, t1);
+        assert_eq!(slab.get_gen(t1, gen), None);
+
+        let gen = slab.generation(t1).unwrap();
+        assert_eq!(slab.get_gen(t1, gen), Some(&456));
+    }
+
+    #[test]
+    fn test_compact() {
+        let mut slab = Slab::new_starting_at(Token(5), 16);
+        let mut tokens = vec![];
+
+        for i in range(0, 8us) {
+            tokens.push(slab.insert(i).unwrap());
+        }
+
+        // Leave holes at 0, 2, 3 and 5
+        for &i in [0us, 2, 3, 5].iter() {
+            slab.remove(tokens[i]);
+        }
+
+        let mut moves = vec![];
+        slab.compact(|from, to| moves.push((from, to)));
+
+        assert_eq!(vec![(Token(12), Token(5)), (Token(11), Token(7)), (Token(9), Token(8))], moves);
+
+        // Every value sits in one of the lowest slots
+        assert_eq!(4, slab.count());
+        assert_eq!(slab[Token(5)], 7);
+        assert_eq!(slab[Token(6)], 1);
+        assert_eq!(slab[Token(7)], 6);
+        assert_eq!(slab[Token(8)], 4);
+
+        for i in range(9, 13) {
+            assert!(!slab.contains(Token(i)));
+        }
+
+        // New values go right after the compacted ones
+        assert_eq!(Token(9), slab.insert(8).unwrap());
+        assert_eq!(Token(10), slab.insert(9).unwrap());
+    }
+
+    #[test]
+    fn test_compact_changes_generations() {
+        let mut slab = Slab::new(16);
+        let mut tokens = vec![];
+
+        for i in range(0, 4us) {
+            tokens.push(slab.insert(i).unwrap());
+        }
+
+        let gen_lo = slab.generation(tokens[0]).unwrap();
+        let gen_hi = slab.generation(tokens[3]).unwrap();
+
+        slab.remove(tokens[0]);
+        slab.compact(|_, _| {});
+
+        // The last value moved into the first slot
+        assert_eq!(slab[tokens[0]], 3);
+        assert_eq!(slab.get_gen(tokens[0], gen_lo), None);
+        assert_eq!(slab.get_gen(tokens[0], gen_hi), None);
+
+        // Neither is the slot it left matched once reused
+        assert_eq!(tokens[3], slab.insert(4).unwrap());
+        assert_eq!(slab.get_gen(tokens[3], gen_hi), None);
+
+        let gen = slab.generation(tokens[0]).unwrap();
+        assert_eq!(slab.get_gen(tokens[0], gen), Some(&3));
+    }
 }
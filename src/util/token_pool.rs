@@ -0,0 +1,134 @@
+use std::iter;
+use os::token::Token;
+
+/// Hands out unique tokens for connections whose state is stored outside
+/// of a `Slab`.
+///
+/// Tokens are taken from the range `base..base + cap`, so reserved tokens
+/// below `base`, such as a listener's, are never handed out. Released
+/// tokens are reused before new ones are taken from the range, and a token
+/// is never handed out twice while it is live.
+pub struct TokenPool {
+    base: usize,
+    // Offsets of released tokens, reused last in first out like a slab
+    free: Vec<usize>,
+    // Offset of the next token that was never handed out
+    next: usize,
+    live: Vec<bool>,
+}
+
+impl TokenPool {
+    pub fn new(base: Token, cap: usize) -> TokenPool {
+        assert!(base.as_usize().checked_add(cap).is_some(), "capacity too large");
+
+        TokenPool {
+            base: base.as_usize(),
+            free: Vec::new(),
+            next: 0,
+            live: iter::repeat(false).take(cap).collect(),
+        }
+    }
+
+    /// Returns a token that is not live, or `None` if all of them are.
+    pub fn acquire(&mut self) -> Option<Token> {
+        let off = match self.free.pop() {
+            Some(off) => off,
+            None => {
+                if self.next == self.live.len() {
+                    return None;
+                }
+
+                self.next += 1;
+                self.next - 1
+            }
+        };
+
+        self.live[off] = true;
+        Some(Token(self.base + off))
+    }
+
+    /// Returns `token` to the pool. Returns false, and does nothing, if the
+    /// token was not handed out by this pool or was already released.
+    pub fn release(&mut self, token: Token) -> bool {
+        if !self.contains(token) {
+            return false;
+        }
+
+        let off = token.as_usize() - self.base;
+
+        self.live[off] = false;
+        self.free.push(off);
+        true
+    }
+
+    /// Returns true if `token` was handed out and not released yet.
+    pub fn contains(&self, token: Token) -> bool {
+        let idx = token.as_usize();
+
+        if idx < self.base || idx - self.base >= self.live.len() {
+            return false;
+        }
+
+        self.live[idx - self.base]
+    }
+
+    /// Number of live tokens.
+    pub fn count(&self) -> usize {
+        self.next - self.free.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.live.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    pub fn has_remaining(&self) -> bool {
+        self.count() < self.capacity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use os::token::Token;
+    use super::TokenPool;
+
+    #[test]
+    fn test_exhaust_and_recycle() {
+        let mut pool = TokenPool::new(Token(2), 3);
+
+        assert_eq!(Some(Token(2)), pool.acquire());
+        assert_eq!(Some(Token(3)), pool.acquire());
+        assert_eq!(Some(Token(4)), pool.acquire());
+        assert_eq!(None, pool.acquire());
+        assert!(!pool.has_remaining());
+
+        assert!(pool.release(Token(3)));
+        assert!(!pool.contains(Token(3)));
+        assert_eq!(2, pool.count());
+
+        assert_eq!(Some(Token(3)), pool.acquire());
+        assert_eq!(None, pool.acquire());
+    }
+
+    #[test]
+    fn test_release_unknown() {
+        let mut pool = TokenPool::new(Token(2), 3);
+        let token = pool.acquire().unwrap();
+
+        // Reserved, out of range and not yet handed out
+        assert!(!pool.release(Token(0)));
+        assert!(!pool.release(Token(5)));
+        assert!(!pool.release(Token(3)));
+
+        assert!(pool.release(token));
+
+        // Releasing twice must not let the token be handed out twice
+        assert!(!pool.release(token));
+        assert_eq!(Some(token), pool.acquire());
+        assert_eq!(Some(Token(3)), pool.acquire());
+        assert!(!pool.is_empty());
+    }
+}
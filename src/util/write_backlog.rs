@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use error::MioResult;
+use event_loop::EventLoop;
+use io::{IoHandle, IoWriter};
+use io::NonBlock::{Ready, WouldBlock};
+use os::event::{Interest, PollOpt};
+use os::token::Token;
+
+/// What `WriteBacklog::push` does when a message does not fit under the
+/// byte cap.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop queued messages, oldest first, until the new one fits. A message
+    /// that is partially written is never dropped, as the peer would receive
+    /// a truncated message. A new message that cannot fit even then is
+    /// dropped without touching the queue.
+    DropOldest,
+    /// Drop the new message.
+    DropNewest,
+    /// Keep the queue as it is and ask for the connection to be closed.
+    CloseConnection,
+}
+
+/// The outcome of `WriteBacklog::push`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Pushed {
+    /// The message was queued without dropping anything.
+    Queued,
+    /// The cap was hit and this many messages were dropped to enforce it,
+    /// which may include the new one.
+    Dropped(usize),
+    /// The cap was hit under `CloseConnection`. The message was not queued
+    /// and the connection should be closed.
+    Close,
+}
+
+/// A per-connection queue of outgoing messages with a cap on the number of
+/// bytes it holds, which protects a server from peers that stop reading.
+///
+/// Messages are written in order by `flush`, which also keeps the
+/// connection's writable interest in sync: while data is pending, the handle
+/// is registered for writable events along with `interest`, and once the
+/// queue is drained it goes back to `interest` alone. Call `flush` after
+/// queueing messages and again from the handler's `writable` callback.
+#[derive(Debug)]
+pub struct WriteBacklog {
+    queue: VecDeque<Vec<u8>>,
+    // Bytes of the front message that were already written
+    pos: usize,
+    // Bytes queued and not written yet
+    len: usize,
+    max_bytes: usize,
+    policy: OverflowPolicy,
+    token: Token,
+    interest: Interest,
+    opts: PollOpt,
+    // Whether the handle is currently registered for writable events
+    writable: bool,
+}
+
+impl WriteBacklog {
+    /// Creates an empty backlog for the connection registered with `token`
+    /// and `opts`, holding at most `max_bytes` unwritten bytes.
+    pub fn new(token: Token, opts: PollOpt, max_bytes: usize, policy: OverflowPolicy) -> WriteBacklog {
+        WriteBacklog {
+            queue: VecDeque::new(),
+            pos: 0,
+            len: 0,
+            max_bytes: max_bytes,
+            policy: policy,
+            token: token,
+            interest: Interest::readable(),
+            opts: opts,
+            writable: false,
+        }
+    }
+
+    /// Sets the interest the connection is registered with besides
+    /// writable, readable by default.
+    pub fn interest(mut self, interest: Interest) -> WriteBacklog {
+        self.interest = interest;
+        self
+    }
+
+    /// Number of bytes queued and not written yet.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Queues `msg`, applying the overflow policy if it does not fit.
+    pub fn push(&mut self, msg: Vec<u8>) -> Pushed {
+        if self.len + msg.len() <= self.max_bytes {
+            self.enqueue(msg);
+            return Pushed::Queued;
+        }
+
+        match self.policy {
+            OverflowPolicy::DropNewest => Pushed::Dropped(1),
+            OverflowPolicy::CloseConnection => Pushed::Close,
+            OverflowPolicy::DropOldest => {
+                // The front message is kept if it has been partially written
+                let (keep, kept) = match self.queue.front() {
+                    Some(front) if self.pos > 0 => (1, front.len() - self.pos),
+                    _ => (0, 0)
+                };
+
+                if msg.len() > self.max_bytes - kept {
+                    // Larger than what can be made room for
+                    return Pushed::Dropped(1);
+                }
+
+                let mut dropped = 0;
+
+                while self.len + msg.len() > self.max_bytes {
+                    let old = self.queue.remove(keep).unwrap();
+                    self.len -= old.len();
+                    dropped += 1;
+                }
+
+                self.enqueue(msg);
+                Pushed::Dropped(dropped)
+            }
+        }
+    }
+
+    /// Writes as much of the queue as `io` accepts. Returns true if the
+    /// queue was drained.
+    pub fn write_to<W: IoWriter>(&mut self, io: &W) -> MioResult<bool> {
+        loop {
+            let cnt = match self.queue.front() {
+                Some(msg) => {
+                    match try!(io.write_slice(&msg[self.pos..])) {
+                        Ready(cnt) => cnt,
+                        WouldBlock => return Ok(false)
+                    }
+                }
+                None => return Ok(true)
+            };
+
+            if cnt == 0 {
+                return Ok(false);
+            }
+
+            self.pos += cnt;
+            self.len -= cnt;
+
+            if self.pos == self.queue.front().unwrap().len() {
+                self.queue.pop_front();
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// Writes as much of the queue as `io` accepts, then registers the
+    /// handle for writable events if data is left, or stops listening for
+    /// them once the queue is drained. Registration only changes on those
+    /// transitions, with the options passed to `new`.
+    pub fn flush<T, M: Send, W: IoWriter + IoHandle>(&mut self, event_loop: &mut EventLoop<T, M>, io: &W) -> MioResult<()> {
+        let drained = try!(self.write_to(io));
+
+        if drained == self.writable {
+            let interest = if drained {
+                self.interest
+            } else {
+                self.interest | Interest::writable()
+            };
+
+            try!(event_loop.reregister(io, self.token, interest, self.opts));
+            self.writable = !drained;
+        }
+
+        Ok(())
+    }
+
+    fn enqueue(&mut self, msg: Vec<u8>) {
+        if msg.is_empty() {
+            return;
+        }
+
+        self.len += msg.len();
+        self.queue.push_back(msg);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use os::event::PollOpt;
+    use os::token::Token;
+    use test::MemStream;
+    use super::{WriteBacklog, OverflowPolicy, Pushed};
+
+    fn stalled(policy: OverflowPolicy) -> (WriteBacklog, MemStream) {
+        let mut backlog = WriteBacklog::new(Token(0), PollOpt::edge(), 10, policy);
+        let stream = MemStream::new();
+
+        // The peer takes two bytes, then stops reading
+        stream.push_write_limit(2);
+        stream.push_write_would_block();
+
+        assert_eq!(Pushed::Queued, backlog.push(b"aaaa".to_vec()));
+        assert!(!backlog.write_to(&stream).unwrap());
+
+        assert_eq!(Pushed::Queued, backlog.push(b"bbbb".to_vec()));
+        assert_eq!(Pushed::Queued, backlog.push(b"cc".to_vec()));
+        assert_eq!(8, backlog.len());
+
+        (backlog, stream)
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let (mut backlog, stream) = stalled(OverflowPolicy::DropOldest);
+
+        // "bbbb" goes, the partially written "aaaa" stays
+        assert_eq!(Pushed::Dropped(1), backlog.push(b"ddd".to_vec()));
+        assert_eq!(7, backlog.len());
+
+        // Too large to fit next to the rest of "aaaa", only the new one goes
+        assert_eq!(Pushed::Dropped(1), backlog.push(b"eeeeeeeeee".to_vec()));
+        assert_eq!(7, backlog.len());
+
+        assert!(backlog.write_to(&stream).unwrap());
+        assert_eq!(b"aaaaccddd", stream.take_written().as_slice());
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        let (mut backlog, stream) = stalled(OverflowPolicy::DropNewest);
+
+        assert_eq!(Pushed::Dropped(1), backlog.push(b"ddd".to_vec()));
+        assert_eq!(8, backlog.len());
+
+        // Still room for a smaller one
+        assert_eq!(Pushed::Queued, backlog.push(b"ee".to_vec()));
+
+        assert!(backlog.write_to(&stream).unwrap());
+        assert!(backlog.is_empty());
+        assert_eq!(b"aaaabbbbccee", stream.take_written().as_slice());
+    }
+
+    #[test]
+    fn test_close_connection() {
+        let (mut backlog, stream) = stalled(OverflowPolicy::CloseConnection);
+
+        assert_eq!(Pushed::Close, backlog.push(b"ddd".to_vec()));
+        assert_eq!(8, backlog.len());
+
+        assert!(backlog.write_to(&stream).unwrap());
+        assert_eq!(b"aaaabbbbcc", stream.take_written().as_slice());
+    }
+}
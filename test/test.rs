@@ -7,15 +7,47 @@ extern crate log;
 
 pub use ports::localhost;
 
+mod test_accept;
+mod test_adopt_listener;
+mod test_backlog_len;
 mod test_battery;
+mod test_bytes_available;
+mod test_close;
 mod test_close_on_drop;
+mod test_connect;
+mod test_custom_buf;
+mod test_defer_accept;
+mod test_dns;
 mod test_echo_server;
+mod test_event_fd;
+mod test_exclusive;
+mod test_fastopen;
+mod test_is_writable;
+mod test_migrate;
 mod test_notify;
+mod test_rdhup;
+mod test_read_into_vec;
+mod test_read_split;
+mod test_reconnector;
+mod test_reentrant_registration;
+mod test_sctp;
+mod test_shared_queue;
+mod test_tcp_info;
 mod test_timer;
+mod test_udp_mmsg;
+mod test_udp_pktinfo;
 mod test_udp_socket;
 mod test_udp_socket_connectionless;
 mod test_register_deregister;
+mod test_registration;
+mod test_sock_addr;
+mod test_socket_options;
+mod test_spurious_wouldblock;
+mod test_unified_readiness;
+mod test_unix_bind_clean;
 mod test_unix_echo_server;
+mod test_unix_peer_cred;
+mod test_unix_seqpacket;
 
 mod ports {
     use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT};
@@ -0,0 +1,224 @@
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use mio::buf::ByteBuf;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+
+struct TestHandler {
+    srv: TcpAcceptor,
+    accepted: bool
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert!(token == SERVER, "unexpected token {:?}", token);
+
+        let sock = self.srv.accept().unwrap().unwrap();
+        let mut buf = ByteBuf::mut_with_capacity(1024);
+
+        // Nothing has been written by the client, the accepted socket must be
+        // non-blocking or this call will hang the test.
+        match sock.read(&mut buf) {
+            Ok(NonBlock::WouldBlock) => {}
+            res => panic!("expected the read to block; actual={:?}", res)
+        }
+
+        self.accepted = true;
+        event_loop.shutdown();
+    }
+}
+
+#[test]
+pub fn test_accept_non_blocking() {
+    debug!("Starting TEST_ACCEPT_NON_BLOCKING");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let sock = TcpSocket::v4().unwrap();
+    sock.connect(&addr).unwrap();
+
+    let handler = event_loop.run(TestHandler { srv: srv, accepted: false })
+        .ok().expect("failed to execute event loop");
+
+    assert!(handler.accepted);
+}
+
+struct BatchHandler {
+    srv: TcpAcceptor,
+    accepted: Vec<TcpSocket>
+}
+
+impl Handler for BatchHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert!(token == SERVER, "unexpected token {:?}", token);
+
+        let conns = self.srv.accept_many(2).unwrap();
+        assert!(conns.len() <= 2, "accepted more than requested; actual={}", conns.len());
+
+        for (sock, addr) in conns.into_iter() {
+            assert_eq!(addr.family(), AddressFamily::Inet);
+            self.accepted.push(sock);
+        }
+
+        if self.accepted.len() == 3 {
+            event_loop.shutdown();
+        }
+    }
+}
+
+#[test]
+pub fn test_accept_many() {
+    debug!("Starting TEST_ACCEPT_MANY");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    // Nothing is pending yet
+    assert!(srv.accept_many(2).unwrap().is_empty());
+
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::level()).unwrap();
+
+    let clients: Vec<TcpSocket> = range(0, 3us).map(|_| {
+        let sock = TcpSocket::v4().unwrap();
+        sock.connect(&addr).unwrap();
+        sock
+    }).collect();
+
+    let handler = event_loop.run(BatchHandler { srv: srv, accepted: vec![] })
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(clients.len(), handler.accepted.len());
+}
+
+struct DrainHandler {
+    srv: TcpAcceptor,
+    accepted: Vec<TcpSocket>
+}
+
+impl Handler for DrainHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert!(token == SERVER, "unexpected token {:?}", token);
+
+        let conns = self.srv.accept_loop().unwrap();
+        self.accepted.extend(conns.into_iter());
+
+        // Drained, nothing else is pending
+        assert!(self.srv.accept().unwrap().would_block());
+        assert!(self.srv.accept_loop().unwrap().is_empty());
+
+        event_loop.shutdown();
+    }
+}
+
+#[test]
+pub fn test_accept_loop_skips_closed() {
+    debug!("Starting TEST_ACCEPT_LOOP_SKIPS_CLOSED");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    let mut clients: Vec<TcpSocket> = range(0, 3us).map(|_| {
+        let sock = TcpSocket::v4().unwrap();
+        sock.connect(&addr).unwrap();
+        sock
+    }).collect();
+
+    sleep(Duration::milliseconds(100));
+
+    // The middle connection is reset before it is accepted. Depending on
+    // the platform, accept skips it or returns it already closed, in which
+    // case it is dropped; either way the drain has to go on to the last one.
+    let reset = clients.remove(1);
+    reset.set_reset_on_close().unwrap();
+    drop(reset);
+
+    for sock in clients.iter() {
+        sock.write_slice(b"x").unwrap();
+    }
+
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let handler = event_loop.run(DrainHandler { srv: srv, accepted: vec![] })
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(2, handler.accepted.len());
+
+    // Both live clients made it through
+    let mut buf = [0u8; 16];
+    let live = handler.accepted.iter().filter(|sock| {
+        match sock.read_slice(&mut buf) {
+            Ok(NonBlock::Ready(1)) => true,
+            _ => false
+        }
+    }).count();
+
+    assert_eq!(2, live);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_allowed_sources() {
+    debug!("Starting TEST_ALLOWED_SOURCES");
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    // Covers 127.0.0.2 and 127.0.0.3, but not 127.0.0.1
+    srv.set_allowed_sources(vec![(IPv4Addr(127, 0, 0, 2), 31)]);
+
+    let denied = TcpSocket::v4().unwrap();
+    denied.connect(&addr).unwrap();
+
+    // The whole of 127.0.0.0/8 is loopback on Linux
+    let allowed = TcpSocket::v4().unwrap();
+    allowed.bind_local(&SockAddr::parse("127.0.0.2:0").unwrap()).unwrap();
+    allowed.connect(&addr).unwrap();
+
+    sleep(Duration::milliseconds(100));
+
+    // The first connection is skipped
+    let conn = srv.accept().unwrap().unwrap();
+    assert_eq!(Some(IPv4Addr(127, 0, 0, 2)), conn.getpeername().unwrap().ip());
+    assert!(srv.accept().unwrap().would_block());
+
+    sleep(Duration::milliseconds(100));
+
+    // and was closed on the server side
+    let mut buf = [0u8; 16];
+    assert!(denied.read_slice(&mut buf).unwrap_err().is_eof());
+}
@@ -0,0 +1,86 @@
+use std::mem;
+use std::time::Duration;
+use std::old_io::TempDir;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use mio::net::pipe::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+
+struct TestHandler {
+    readable: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(SERVER, token);
+        self.readable += 1;
+    }
+}
+
+#[test]
+pub fn test_tcp_acceptor_from_raw_fd() {
+    debug!("Starting TEST_TCP_ACCEPTOR_FROM_RAW_FD");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    // Stands in for the socket a service manager binds and passes on
+    let sock = TcpSocket::v4().unwrap();
+    sock.set_reuseaddr(true).unwrap();
+
+    let orig = sock.bind(&addr).unwrap().listen(256).unwrap();
+    orig.set_nonblocking(false).unwrap();
+
+    let fd = orig.desc().fd;
+
+    // The adopted acceptor owns the descriptor from now on
+    mem::forget(orig);
+
+    let mut srv = TcpAcceptor::from_raw_fd(fd).unwrap();
+    assert!(srv.is_nonblocking().unwrap());
+    assert!(srv.accept().unwrap().would_block());
+
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let mut handler = TestHandler { readable: 0 };
+    handler = event_loop.run_once(handler, Some(Duration::seconds(1)))
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(1, handler.readable);
+    assert!(!srv.accept().unwrap().would_block());
+}
+
+#[test]
+pub fn test_unix_acceptor_from_raw_fd() {
+    debug!("Starting TEST_UNIX_ACCEPTOR_FROM_RAW_FD");
+
+    let tmp_dir = TempDir::new("test_unix_acceptor_from_raw_fd").unwrap();
+    let addr = SockAddr::from_path(tmp_dir.path().join(Path::new("sock")));
+
+    let orig = UnixSocket::stream().unwrap()
+        .bind(&addr).unwrap()
+        .listen(256).unwrap();
+    orig.set_nonblocking(false).unwrap();
+
+    let fd = orig.desc().fd;
+    mem::forget(orig);
+
+    let mut srv = UnixAcceptor::from_raw_fd(fd).unwrap();
+    assert!(srv.accept().unwrap().would_block());
+
+    let cli = UnixSocket::stream().unwrap();
+    cli.connect(&addr).unwrap();
+
+    assert!(!srv.accept().unwrap().would_block());
+}
@@ -4,6 +4,7 @@ use mio::net::tcp::*;
 use mio::util::Slab;
 use super::localhost;
 use std::collections::DList;
+use std::default::Default;
 use std::thread::Thread;
 use std::old_io::timer::Timer;
 use std::time::duration::Duration;
@@ -22,15 +23,12 @@ struct EchoConn {
 
 impl EchoConn {
     fn new(sock: TcpSocket) -> EchoConn {
-        let mut ec =
         EchoConn {
             sock: sock,
             token: Token(-1),
             buf: Vec::with_capacity(22),
             count: 0
-        };
-        unsafe { ec.buf.set_len(22) };
-        ec
+        }
     }
 
     fn writable(&mut self, event_loop: &mut TestEventLoop) -> MioResult<()> {
@@ -39,7 +37,9 @@ impl EchoConn {
 
     fn readable(&mut self, event_loop: &mut TestEventLoop) -> MioResult<()> {
         loop {
-            match self.sock.read_slice(self.buf.as_mut_slice()) {
+            self.buf.clear();
+
+            match self.sock.read_into_vec(&mut self.buf, 22) {
                 Ok(NonBlock::WouldBlock) => {
                     break;
                 }
@@ -167,7 +167,10 @@ impl EchoHandler {
     }
 }
 
-impl Handler<usize, String> for EchoHandler {
+impl Handler for EchoHandler {
+    type Timeout = usize;
+    type Message = String;
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, hint: ReadHint) {
         assert_eq!(hint, ReadHint::data());
 
@@ -212,12 +215,8 @@ pub fn test_echo_server() {
     debug!("Starting TEST_ECHO_SERVER");
     let config =
         EventLoopConfig {
-            io_poll_timeout_ms: 1_000,
             notify_capacity: 1_048_576,
-            messages_per_tick: 64,
-            timer_tick_ms: 100,
-            timer_wheel_size: 1_024,
-            timer_capacity: 65_536,
+            .. Default::default()
         };
     let mut event_loop = EventLoop::configured(config).unwrap();
 
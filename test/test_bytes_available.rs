@@ -0,0 +1,70 @@
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CONN: Token = Token(1);
+
+const LEN: usize = 1024;
+
+struct TestHandler {
+    srv: TcpAcceptor,
+    cli: TcpSocket,
+    conn: Option<TcpSocket>,
+    available: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        match token {
+            SERVER => {
+                let conn = self.srv.accept().unwrap().unwrap();
+                event_loop.register_opt(&conn, CONN, Interest::readable(), PollOpt::edge()).unwrap();
+                self.conn = Some(conn);
+
+                let data = [0u8; LEN];
+                assert_eq!(LEN, self.cli.write_slice(&data).unwrap().unwrap());
+            }
+            CONN => {
+                self.available = self.conn.as_ref().unwrap().bytes_available().unwrap();
+                event_loop.shutdown();
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+#[test]
+pub fn test_bytes_available() {
+    debug!("Starting TEST_BYTES_AVAILABLE");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let handler = TestHandler {
+        srv: srv,
+        cli: cli,
+        conn: None,
+        available: 0
+    };
+
+    let handler = event_loop.run(handler)
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(LEN, handler.available);
+}
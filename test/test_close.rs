@@ -0,0 +1,102 @@
+use mio::*;
+use mio::buf::ByteBuf;
+use mio::net::*;
+use mio::net::tcp::*;
+use mio::util::DrainClose;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CLIENT: Token = Token(1);
+const CONN: Token = Token(2);
+
+struct TestHandler {
+    srv: TcpAcceptor,
+    cli: Option<TcpSocket>,
+    draining: DrainClose,
+    received: Vec<u8>,
+    closed: bool
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        match token {
+            SERVER => {
+                let conn = self.srv.accept().unwrap().unwrap();
+
+                // Reply, then close without waiting on the client
+                conn.write_slice(b"goodbye").unwrap().unwrap();
+                conn.begin_close().unwrap();
+
+                self.draining.start(event_loop, conn, CONN).unwrap();
+            }
+            CONN => {
+                assert!(self.draining.contains(CONN));
+
+                if self.draining.readable(event_loop, CONN) {
+                    assert!(self.cli.is_none(), "closed before the client hung up");
+
+                    self.closed = true;
+                    event_loop.shutdown();
+                }
+            }
+            CLIENT => {
+                let mut buf = ByteBuf::mut_with_capacity(64);
+
+                match self.cli.as_ref().unwrap().read(&mut buf) {
+                    Ok(NonBlock::Ready(_)) => {
+                        self.received.push_all(buf.flip().bytes());
+                    }
+                    Ok(NonBlock::WouldBlock) => {}
+                    Err(e) => {
+                        // The server shut down its writing half. Closing
+                        // our end completes the handshake.
+                        assert!(e.is_eof());
+
+                        let cli = self.cli.take().unwrap();
+                        event_loop.deregister(&cli).unwrap();
+                    }
+                }
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+#[test]
+pub fn test_close() {
+    debug!("Starting TEST_CLOSE");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Level triggered, so that reads pick up where the last one left off
+    event_loop.register_opt(&cli, CLIENT, Interest::readable(), PollOpt::level()).unwrap();
+
+    let handler = TestHandler {
+        srv: srv,
+        cli: Some(cli),
+        draining: DrainClose::new(),
+        received: vec![],
+        closed: false
+    };
+
+    let handler = event_loop.run(handler)
+        .ok().expect("failed to execute event loop");
+
+    assert!(handler.closed);
+    assert_eq!(b"goodbye", handler.received.as_slice());
+}
@@ -32,7 +32,10 @@ impl TestHandler {
     }
 }
 
-impl Handler<usize, ()> for TestHandler {
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, tok: Token, hint: ReadHint) {
         debug!("readable; tok={:?}; hint={:?}", tok, hint);
 
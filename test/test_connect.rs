@@ -0,0 +1,99 @@
+use std::old_io::timer::sleep;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+use std::time::Duration;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const CONNECTIONS: usize = 64;
+
+struct TestHandler {
+    socks: Vec<TcpSocket>,
+    connected: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn writable(&mut self, event_loop: &mut TestEventLoop, token: Token) {
+        assert!(token.as_usize() < self.socks.len(), "unexpected token {:?}", token);
+
+        self.connected += 1;
+
+        if self.connected == self.socks.len() {
+            event_loop.shutdown();
+        }
+    }
+
+    fn timeout(&mut self, _: &mut TestEventLoop, _: usize) {
+        panic!("only {} of {} connections were reported writable", self.connected, self.socks.len());
+    }
+}
+
+#[test]
+pub fn test_connect_before_register() {
+    debug!("Starting TEST_CONNECT_BEFORE_REGISTER");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    // The connections are never accepted, the backlog just has to hold them
+    let _srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    let mut socks = vec![];
+
+    for i in range(0, CONNECTIONS) {
+        let sock = TcpSocket::v4().unwrap();
+
+        // Connect first, so that the connection has a chance to complete
+        // before the socket is registered. The edge must not be lost.
+        sock.connect(&addr).unwrap();
+
+        event_loop.register_opt(&sock, Token(i), Interest::writable(), PollOpt::edge() | PollOpt::oneshot()).unwrap();
+        socks.push(sock);
+    }
+
+    event_loop.timeout(0, Duration::seconds(5)).unwrap();
+
+    let handler = event_loop.run(TestHandler { socks: socks, connected: 0 })
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(handler.connected, CONNECTIONS);
+}
+
+#[test]
+pub fn test_connect_from_bound_socket() {
+    debug!("Starting TEST_CONNECT_FROM_BOUND_SOCKET");
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    // Let the kernel pick the port, on the loopback address
+    let sock = TcpSocket::v4().unwrap();
+    sock.bind_local(&SockAddr::parse("127.0.0.1:0").unwrap()).unwrap();
+
+    let local = sock.getsockname().unwrap();
+    let port = local.port().unwrap();
+    assert!(port != 0);
+
+    sock.connect(&addr).unwrap();
+
+    // The connection comes from the address the socket was bound to
+    sleep(Duration::milliseconds(100));
+
+    let conn = srv.accept().unwrap().unwrap();
+    let peer = conn.getpeername().unwrap();
+
+    assert_eq!(local.ip(), peer.ip());
+    assert_eq!(Some(port), peer.port());
+    assert_eq!(Some(port), sock.getsockname().unwrap().port());
+}
@@ -0,0 +1,95 @@
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use mio::buf::{Buf, MutBuf};
+use super::localhost;
+
+// A fixed size buffer, standing in for an application's own buffer type
+struct ArenaBuf {
+    mem: [u8; 16],
+    pos: usize,
+    len: usize
+}
+
+impl ArenaBuf {
+    fn new() -> ArenaBuf {
+        ArenaBuf { mem: [0u8; 16], pos: 0, len: 0 }
+    }
+
+    fn filled(data: &[u8]) -> ArenaBuf {
+        let mut buf = ArenaBuf::new();
+
+        for (d, s) in buf.mem.iter_mut().zip(data.iter()) {
+            *d = *s;
+        }
+
+        buf.len = data.len();
+        buf
+    }
+}
+
+impl Buf for ArenaBuf {
+    fn remaining(&self) -> usize {
+        self.len - self.pos
+    }
+
+    fn bytes<'a>(&'a self) -> &'a [u8] {
+        &self.mem[self.pos..self.len]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.pos += cnt;
+    }
+}
+
+impl MutBuf for ArenaBuf {
+    fn remaining(&self) -> usize {
+        self.mem.len() - self.len
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.len += cnt;
+    }
+
+    fn mut_bytes<'a>(&'a mut self) -> &'a mut [u8] {
+        &mut self.mem[self.len..]
+    }
+}
+
+#[test]
+pub fn test_custom_buf() {
+    debug!("Starting TEST_CUSTOM_BUF");
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    sleep(Duration::milliseconds(100));
+
+    let conn = srv.accept().unwrap().unwrap();
+
+    let mut src = ArenaBuf::filled(b"hello world");
+    assert_eq!(11, cli.write(&mut src).unwrap().unwrap());
+    assert_eq!(0, Buf::remaining(&src));
+
+    sleep(Duration::milliseconds(100));
+
+    // Fills the buffer up to its capacity, and advances it
+    let mut dst = ArenaBuf::new();
+    dst.len = 10;
+
+    assert_eq!(6, conn.read(&mut dst).unwrap().unwrap());
+    assert_eq!(0, MutBuf::remaining(&dst));
+    assert_eq!(b"hello", &dst.mem[10..15]);
+
+    let mut rest = ArenaBuf::new();
+    assert_eq!(5, conn.read(&mut rest).unwrap().unwrap());
+    assert_eq!(b"world", Buf::bytes(&rest));
+}
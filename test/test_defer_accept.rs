@@ -0,0 +1,63 @@
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+
+struct TestHandler {
+    readable: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(SERVER, token);
+        self.readable += 1;
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_defer_accept() {
+    debug!("Starting TEST_DEFER_ACCEPT");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    srv.set_defer_accept(5).unwrap();
+
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let mut handler = TestHandler { readable: 0 };
+
+    // The handshake completes, but the client has not sent anything
+    handler = event_loop.run_once(handler, Some(Duration::milliseconds(300)))
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(0, handler.readable);
+    assert!(srv.accept().unwrap().would_block());
+
+    cli.write_slice(b"GET").unwrap();
+
+    handler = event_loop.run_once(handler, Some(Duration::seconds(1)))
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(1, handler.readable);
+
+    let conn = srv.accept().unwrap().unwrap();
+    let mut buf = [0u8; 8];
+    assert_eq!(3, conn.read_slice(&mut buf).unwrap().unwrap());
+}
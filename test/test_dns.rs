@@ -0,0 +1,76 @@
+use std::time::Duration;
+use mio::*;
+use mio::dns::{Resolver, Resolved};
+use mio::net::*;
+
+type TestEventLoop = EventLoop<usize, Resolved>;
+
+struct TestHandler {
+    resolved: Vec<Resolved>,
+    ticks: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = Resolved;
+
+    fn notify(&mut self, event_loop: &mut TestEventLoop, msg: Resolved) {
+        self.resolved.push(msg);
+
+        if self.resolved.len() == 2 && self.ticks > 0 {
+            event_loop.shutdown();
+        }
+    }
+
+    fn timeout(&mut self, event_loop: &mut TestEventLoop, _: usize) {
+        // The loop keeps running while the lookups are in progress
+        self.ticks += 1;
+
+        if self.resolved.len() == 2 {
+            event_loop.shutdown();
+        } else {
+            event_loop.timeout(0, Duration::milliseconds(10)).unwrap();
+        }
+    }
+}
+
+fn identity(msg: Resolved) -> Resolved {
+    msg
+}
+
+#[test]
+pub fn test_resolve_localhost() {
+    debug!("Starting TEST_RESOLVE_LOCALHOST");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let resolver = Resolver::new(&event_loop, 2, identity).unwrap();
+    resolver.resolve("localhost", 8080, Token(1));
+    resolver.resolve("127.0.0.1", 53, Token(2));
+
+    event_loop.timeout(0, Duration::milliseconds(10)).unwrap();
+
+    let mut handler = TestHandler { resolved: vec![], ticks: 0 };
+    handler = event_loop.run(handler).ok().expect("failed to execute event loop");
+
+    assert_eq!(2, handler.resolved.len());
+    assert!(handler.ticks > 0);
+
+    for msg in handler.resolved.iter() {
+        let addrs = msg.result.as_ref().unwrap();
+        assert!(!addrs.is_empty());
+
+        match msg.token {
+            Token(1) => assert!(addrs.iter().all(|a| a.port() == Some(8080))),
+            Token(2) => assert_eq!(Some(IPv4Addr(127, 0, 0, 1)), addrs[0].ip()),
+            _ => panic!("unexpected token {:?}", msg.token)
+        }
+    }
+}
+
+#[test]
+pub fn test_resolver_threads() {
+    debug!("Starting TEST_RESOLVER_THREADS");
+    let event_loop: TestEventLoop = EventLoop::new().unwrap();
+
+    assert!(Resolver::new(&event_loop, 0, identity).unwrap_err().is_invalid_config());
+}
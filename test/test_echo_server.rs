@@ -87,7 +87,12 @@ impl EchoServer {
     fn accept(&mut self, event_loop: &mut TestEventLoop) -> MioResult<()> {
         debug!("server accepting socket");
 
-        let sock = self.sock.accept().unwrap().unwrap();
+        // The connection may have gone away since the readable event
+        let sock = match try!(self.sock.accept()) {
+            NonBlock::Ready(sock) => sock,
+            NonBlock::WouldBlock => return Ok(())
+        };
+
         let conn = EchoConn::new(sock,);
         let tok = self.conns.insert(conn)
             .ok().expect("could not add connectiont o slab");
@@ -233,7 +238,10 @@ impl EchoHandler {
     }
 }
 
-impl Handler<usize, ()> for EchoHandler {
+impl Handler for EchoHandler {
+    type Timeout = usize;
+    type Message = ();
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, hint: ReadHint) {
         assert!(hint.is_data());
 
@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use std::thread::Thread;
+use mio::*;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const EVENT_FD: Token = Token(0);
+
+struct TestHandler {
+    event_fd: Arc<EventFd>,
+    total: u64
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(token, EVENT_FD);
+
+        self.total += self.event_fd.reset().unwrap();
+
+        if self.total == 6 {
+            event_loop.shutdown();
+        }
+    }
+}
+
+#[test]
+pub fn test_event_fd() {
+    debug!("Starting TEST_EVENT_FD");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let event_fd = Arc::new(EventFd::new().unwrap());
+    event_loop.register(&*event_fd, EVENT_FD).unwrap();
+
+    let waker = event_fd.clone();
+
+    Thread::spawn(move || {
+        waker.wake(1).unwrap();
+        waker.wake(2).unwrap();
+        waker.wake(3).unwrap();
+    });
+
+    let handler = event_loop.run(TestHandler { event_fd: event_fd, total: 0 })
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(6, handler.total);
+
+    // Nothing left after the counter was reset
+    assert_eq!(0, handler.event_fd.reset().unwrap());
+}
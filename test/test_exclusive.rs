@@ -0,0 +1,77 @@
+use std::mem;
+use std::old_io::timer::sleep;
+use std::thread::Thread;
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const NUM_LOOPS: usize = 4;
+
+struct TestHandler {
+    wakeups: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(SERVER, token);
+        self.wakeups += 1;
+    }
+}
+
+// Runs an event loop of its own with the shared acceptor registered, and
+// returns how many times it was woken up for it
+fn wait_for_connection(fd: i32) -> usize {
+    let mut event_loop = EventLoop::new().unwrap();
+
+    // The acceptor is owned by the test, the loop only borrows the fd
+    let desc = IoDesc { fd: fd };
+    event_loop.register_opt(&desc, SERVER, Interest::readable(), PollOpt::edge() | PollOpt::exclusive()).unwrap();
+
+    let handler = event_loop.run_for(TestHandler { wakeups: 0 }, Duration::milliseconds(500))
+        .ok().expect("failed to execute event loop");
+
+    drop(event_loop);
+    mem::forget(desc);
+
+    handler.wakeups
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_exclusive() {
+    debug!("Starting TEST_EXCLUSIVE");
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    let fd = srv.desc().fd;
+
+    let loops: Vec<_> = range(0, NUM_LOOPS).map(|_| {
+        Thread::scoped(move || wait_for_connection(fd))
+    }).collect();
+
+    // All the loops are blocked waiting for events by now
+    sleep(Duration::milliseconds(100));
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let wakeups = loops.into_iter()
+        .fold(0, |sum, guard| sum + guard.join().ok().unwrap());
+
+    // The kernel only promises to wake one or more of the loops, but not
+    // all of them
+    assert!(wakeups >= 1 && wakeups < NUM_LOOPS, "wakeups={}", wakeups);
+    assert!(!srv.accept().unwrap().would_block());
+}
@@ -0,0 +1,93 @@
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CLIENT: Token = Token(1);
+
+struct TestHandler {
+    cli: TcpSocket,
+    pending: Option<&'static [u8]>,
+    accepted: bool
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(SERVER, token);
+        self.accepted = true;
+    }
+
+    fn writable(&mut self, _event_loop: &mut TestEventLoop, token: Token) {
+        assert_eq!(CLIENT, token);
+
+        // The data did not ride the SYN, send it now
+        if let Some(data) = self.pending.take() {
+            assert_eq!(data.len(), self.cli.write_slice(data).unwrap().unwrap());
+        }
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_fastopen() {
+    debug!("Starting TEST_FASTOPEN");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    srv.set_fastopen(16).unwrap();
+
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+
+    let pending = match cli.connect_with_data(&addr, b"GET").unwrap() {
+        NonBlock::Ready(cnt) => {
+            assert_eq!(3, cnt);
+            None
+        }
+        NonBlock::WouldBlock => Some(&b"GET"[..])
+    };
+
+    event_loop.register_opt(&cli, CLIENT, Interest::writable(), PollOpt::edge() | PollOpt::oneshot()).unwrap();
+
+    let mut handler = TestHandler { cli: cli, pending: pending, accepted: false };
+
+    for _ in range(0, 10) {
+        if handler.accepted && handler.pending.is_none() {
+            break;
+        }
+
+        handler = event_loop.run_once(handler, Some(Duration::milliseconds(200)))
+            .ok().expect("failed to execute event loop");
+    }
+
+    assert!(handler.accepted);
+    assert!(handler.pending.is_none());
+
+    let conn = srv.accept().unwrap().unwrap();
+    let mut buf = [0u8; 8];
+
+    for _ in range(0, 10) {
+        if let NonBlock::Ready(cnt) = conn.read_slice(&mut buf).unwrap() {
+            assert_eq!(b"GET", &buf[..cnt]);
+            return;
+        }
+
+        handler = event_loop.run_once(handler, Some(Duration::milliseconds(100)))
+            .ok().expect("failed to execute event loop");
+    }
+
+    panic!("data never received");
+}
@@ -0,0 +1,50 @@
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use super::localhost;
+
+#[test]
+pub fn test_is_writable() {
+    debug!("Starting TEST_IS_WRITABLE");
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    // Connect in blocking mode so that the connection is established
+    let cli = TcpSocket::v4().unwrap();
+    cli.set_nonblocking(false).unwrap();
+    cli.connect(&addr).unwrap();
+    cli.set_nonblocking(true).unwrap();
+
+    assert!(cli.is_writable());
+
+    // The server does not read, so the buffers eventually fill up
+    let chunk = [0u8; 65536];
+
+    while !cli.write_slice(&chunk).unwrap().would_block() {
+    }
+
+    assert!(!cli.is_writable());
+
+    let conn = srv.accept().unwrap().unwrap();
+    let mut buf = [0u8; 65536];
+
+    for _ in range(0, 100us) {
+        while !conn.read_slice(&mut buf).unwrap().would_block() {
+        }
+
+        if cli.is_writable() {
+            return;
+        }
+
+        sleep(Duration::milliseconds(10));
+    }
+
+    panic!("socket never became writable again");
+}
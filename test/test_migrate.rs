@@ -0,0 +1,132 @@
+use mio::*;
+use mio::buf::ByteBuf;
+use mio::net::*;
+use mio::net::tcp::*;
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CONN: Token = Token(1);
+
+// Accepts the connection, echoes the first message and hands it over
+struct FirstLoop {
+    srv: TcpAcceptor,
+    conn: Option<TcpSocket>,
+    migrated: Option<TcpSocket>,
+    stale_events: usize
+}
+
+impl Handler for FirstLoop {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        match token {
+            SERVER => {
+                let conn = self.srv.accept().unwrap().unwrap();
+                event_loop.register_opt(&conn, CONN, Interest::readable(), PollOpt::edge()).unwrap();
+                self.conn = Some(conn);
+            }
+            CONN => {
+                let conn = match self.conn.take() {
+                    Some(conn) => conn,
+                    None => {
+                        self.stale_events += 1;
+                        return;
+                    }
+                };
+
+                // Only read the first message, the second stays queued in
+                // the kernel while the connection moves
+                let mut buf = ByteBuf::mut_with_capacity(3);
+                conn.read(&mut buf).unwrap().unwrap();
+                conn.write(&mut buf.flip()).unwrap().unwrap();
+
+                event_loop.deregister(&conn).unwrap();
+                self.migrated = Some(conn);
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+// Keeps echoing on the second loop
+struct SecondLoop {
+    conn: TcpSocket
+}
+
+impl Handler for SecondLoop {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(CONN, token);
+
+        let mut buf = ByteBuf::mut_with_capacity(64);
+        self.conn.read(&mut buf).unwrap().unwrap();
+        self.conn.write(&mut buf.flip()).unwrap().unwrap();
+
+        event_loop.shutdown();
+    }
+}
+
+#[test]
+pub fn test_migrate() {
+    debug!("Starting TEST_MIGRATE");
+
+    let mut first = EventLoop::new().unwrap();
+    let mut second = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    first.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Wait for the connection before sending both messages
+    let mut handler = FirstLoop { srv: srv, conn: None, migrated: None, stale_events: 0 };
+
+    while handler.conn.is_none() {
+        handler = first.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+    }
+
+    cli.write_slice(b"onetwo").unwrap().unwrap();
+
+    while handler.migrated.is_none() {
+        handler = first.run_once(handler, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+    }
+
+    // The first loop does not hear about the connection anymore
+    handler = first.run_once(handler, Some(Duration::milliseconds(100))).ok().expect("failed to run event loop");
+    assert_eq!(0, handler.stale_events);
+
+    let conn = handler.migrated.take().unwrap();
+    second.register_opt(&conn, CONN, Interest::readable(), PollOpt::edge()).unwrap();
+    second.run(SecondLoop { conn: conn }).ok().expect("failed to run event loop");
+
+    // Both messages came back exactly once, in order
+    let mut received = vec![];
+    let mut buf = [0u8; 64];
+
+    for _ in range(0, 100us) {
+        if let Ok(NonBlock::Ready(cnt)) = cli.read_slice(&mut buf) {
+            received.push_all(&buf[..cnt]);
+        }
+
+        if received.len() >= 6 {
+            break;
+        }
+
+        sleep(Duration::milliseconds(10));
+    }
+
+    assert_eq!(b"onetwo", received.as_slice());
+}
@@ -22,7 +22,10 @@ impl TestHandler {
     }
 }
 
-impl Handler<usize, String> for TestHandler {
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = String;
+
     fn notify(&mut self, event_loop: &mut TestEventLoop, msg: String) {
         match self.notify {
             0 => {
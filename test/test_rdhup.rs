@@ -0,0 +1,60 @@
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CLIENT: Token = Token(1);
+
+struct TestHandler {
+    srv: TcpAcceptor,
+    hint: Option<ReadHint>
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, hint: ReadHint) {
+        match token {
+            SERVER => {
+                // Closing the accepted connection shuts down the client's
+                // reading half, while it can still write
+                let _ = self.srv.accept().unwrap().unwrap();
+            }
+            CLIENT => {
+                self.hint = Some(hint);
+                event_loop.shutdown();
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_rdhup() {
+    debug!("Starting TEST_RDHUP");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+    event_loop.register_opt(&cli, CLIENT, Interest::readable() | Interest::hup(), PollOpt::edge()).unwrap();
+
+    let handler = event_loop.run(TestHandler { srv: srv, hint: None })
+        .ok().expect("failed to execute event loop");
+
+    let hint = handler.hint.expect("expected the client to be readable");
+    assert!(hint.is_rdhup(), "unexpected hint {:?}", hint);
+    assert!(hint.is_hup(), "unexpected hint {:?}", hint);
+}
@@ -0,0 +1,50 @@
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use super::localhost;
+
+// Retries a non-blocking operation until it is ready
+fn wait<T, F: FnMut() -> NonBlock<T>>(mut f: F) -> T {
+    for _ in range(0, 100us) {
+        if let NonBlock::Ready(v) = f() {
+            return v;
+        }
+
+        sleep(Duration::milliseconds(10));
+    }
+
+    panic!("operation never completed");
+}
+
+#[test]
+pub fn test_read_into_vec() {
+    debug!("Starting TEST_READ_INTO_VEC");
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let conn = wait(|| srv.accept().unwrap());
+    let mut vec = b"ab".to_vec();
+
+    assert!(conn.read_into_vec(&mut vec, 64).unwrap().would_block());
+    assert_eq!(b"ab", vec.as_slice());
+
+    wait(|| cli.write_slice(b"hello").unwrap());
+
+    // A short read only grows the vector by what was received
+    let cnt = wait(|| conn.read_into_vec(&mut vec, 64).unwrap());
+
+    assert_eq!(5, cnt);
+    assert_eq!(7, vec.len());
+    assert_eq!(b"abhello", vec.as_slice());
+    assert!(vec.capacity() >= 66);
+}
@@ -0,0 +1,78 @@
+use mio::*;
+use mio::buf::ByteBuf;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CONN: Token = Token(1);
+
+struct TestHandler {
+    srv: TcpAcceptor,
+    cli: TcpSocket,
+    conn: Option<TcpSocket>,
+    read: Option<(usize, Vec<u8>, Vec<u8>)>
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        match token {
+            SERVER => {
+                let conn = self.srv.accept().unwrap().unwrap();
+                event_loop.register_opt(&conn, CONN, Interest::readable(), PollOpt::edge()).unwrap();
+                self.conn = Some(conn);
+
+                self.cli.write_slice(b"HEADbody").unwrap().unwrap();
+            }
+            CONN => {
+                let mut header = ByteBuf::mut_with_capacity(4);
+                let mut body = ByteBuf::mut_with_capacity(64);
+
+                let cnt = self.conn.as_ref().unwrap()
+                    .read_split(&mut header, &mut body).unwrap().unwrap();
+
+                self.read = Some((cnt, header.flip().bytes().to_vec(), body.flip().bytes().to_vec()));
+                event_loop.shutdown();
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+#[test]
+pub fn test_read_split() {
+    debug!("Starting TEST_READ_SPLIT");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let handler = TestHandler {
+        srv: srv,
+        cli: cli,
+        conn: None,
+        read: None
+    };
+
+    let handler = event_loop.run(handler)
+        .ok().expect("failed to execute event loop");
+
+    // The header is filled exactly, the rest spills into the body
+    let (cnt, header, body) = handler.read.unwrap();
+    assert_eq!(8, cnt);
+    assert_eq!(b"HEAD", header.as_slice());
+    assert_eq!(b"body", body.as_slice());
+}
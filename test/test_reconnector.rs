@@ -0,0 +1,114 @@
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use mio::util::{Reconnector, Backoff, Reconnect};
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const CLIENT: Token = Token(1);
+
+struct TestHandler {
+    reconnector: Reconnector<usize>,
+    addr: SockAddr,
+    // Attempts refused before the server starts listening
+    refuse: usize,
+    srv: Option<TcpAcceptor>,
+    retries: Vec<Duration>,
+    connected: bool,
+    gave_up: bool,
+}
+
+impl TestHandler {
+    fn new(addr: &str, refuse: usize, max_retries: usize) -> TestHandler {
+        let backoff = Backoff::exponential(Duration::milliseconds(10), Duration::milliseconds(40))
+            .max_retries(max_retries);
+
+        TestHandler {
+            reconnector: Reconnector::new(SockAddr::parse(addr).unwrap(), CLIENT, 0, backoff),
+            addr: SockAddr::parse(addr).unwrap(),
+            refuse: refuse,
+            srv: None,
+            retries: vec![],
+            connected: false,
+            gave_up: false,
+        }
+    }
+
+    fn handle(&mut self, event_loop: &mut TestEventLoop, res: Reconnect) {
+        match res {
+            Reconnect::Connecting => {}
+            Reconnect::Connected(sock) => {
+                assert!(sock.getpeername().is_ok());
+                self.connected = true;
+                event_loop.shutdown();
+            }
+            Reconnect::Retrying(delay) => {
+                self.retries.push(delay);
+
+                if self.retries.len() == self.refuse {
+                    let srv = TcpSocket::v4().unwrap();
+                    srv.set_reuseaddr(true).unwrap();
+                    self.srv = Some(srv.bind(&self.addr).unwrap().listen(256).unwrap());
+                }
+            }
+            Reconnect::GaveUp(_) => {
+                self.gave_up = true;
+                event_loop.shutdown();
+            }
+        }
+    }
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn writable_hint(&mut self, event_loop: &mut TestEventLoop, token: Token, hint: WriteHint) {
+        assert_eq!(CLIENT, token);
+
+        let res = self.reconnector.writable(event_loop, hint).unwrap();
+        self.handle(event_loop, res);
+    }
+
+    fn timeout(&mut self, event_loop: &mut TestEventLoop, _: usize) {
+        let res = self.reconnector.connect(event_loop).unwrap();
+        self.handle(event_loop, res);
+    }
+}
+
+#[test]
+pub fn test_reconnector() {
+    debug!("Starting TEST_RECONNECTOR");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let mut handler = TestHandler::new(localhost().as_slice(), 3, 5);
+
+    let res = handler.reconnector.connect(&mut event_loop).unwrap();
+    handler.handle(&mut event_loop, res);
+
+    let handler = event_loop.run(handler).ok().expect("failed to execute event loop");
+
+    assert!(handler.connected);
+    assert_eq!(0, handler.reconnector.failures());
+    assert_eq!(vec![Duration::milliseconds(10), Duration::milliseconds(20), Duration::milliseconds(40)], handler.retries);
+}
+
+#[test]
+pub fn test_reconnector_gives_up() {
+    debug!("Starting TEST_RECONNECTOR_GIVES_UP");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    // Nothing ever listens
+    let mut handler = TestHandler::new(localhost().as_slice(), 0, 2);
+
+    let res = handler.reconnector.connect(&mut event_loop).unwrap();
+    handler.handle(&mut event_loop, res);
+
+    let handler = event_loop.run(handler).ok().expect("failed to execute event loop");
+
+    assert!(handler.gave_up);
+    assert_eq!(3, handler.reconnector.failures());
+    assert_eq!(2, handler.retries.len());
+}
@@ -0,0 +1,74 @@
+use std::time::Duration;
+use mio::*;
+use mio::buf::SliceBuf;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+fn readable_pipe() -> (PipeReader, PipeWriter) {
+    let (reader, writer) = pipe().unwrap();
+    writer.write(&mut SliceBuf::wrap("hello".as_bytes())).unwrap();
+    (reader, writer)
+}
+
+struct TestHandler {
+    readers: Vec<PipeReader>,
+    replacement: Option<PipeReader>,
+    // Tokens in the order they were dispatched
+    dispatched: Vec<usize>
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        self.dispatched.push(token.as_usize());
+
+        if let Some(replacement) = self.replacement.take() {
+            // Swap out the other handle while its event may still be pending
+            // in this tick, reusing its token for the new handle
+            let other = 1 - token.as_usize();
+
+            event_loop.deregister(&self.readers[other]).unwrap();
+            event_loop.register(&replacement, Token(other)).unwrap();
+
+            self.readers[other] = replacement;
+        }
+    }
+}
+
+#[test]
+pub fn test_reentrant_registration() {
+    debug!("Starting TEST_REENTRANT_REGISTRATION");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let (r0, _w0) = readable_pipe();
+    let (r1, _w1) = readable_pipe();
+    let (r2, _w2) = readable_pipe();
+
+    event_loop.register(&r0, Token(0)).unwrap();
+    event_loop.register(&r1, Token(1)).unwrap();
+
+    let handler = TestHandler {
+        readers: vec![r0, r1],
+        replacement: Some(r2),
+        dispatched: vec![]
+    };
+
+    // Both pipes are readable, but the second event belongs to a handle that
+    // was deregistered by the time it is dispatched
+    let handler = event_loop.run_once(handler, Some(Duration::seconds(1)))
+        .ok().expect("failed to run event loop");
+
+    assert_eq!(1, handler.dispatched.len());
+
+    // The next poll reports both the remaining handle and the new one, under
+    // the reused token
+    let handler = event_loop.run_once(handler, Some(Duration::seconds(1)))
+        .ok().expect("failed to run event loop");
+
+    let mut next_tick = handler.dispatched[1..].to_vec();
+    next_tick.sort();
+
+    assert_eq!(vec![0, 1], next_tick);
+}
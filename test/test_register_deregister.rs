@@ -25,7 +25,10 @@ impl TestHandler {
     }
 }
 
-impl Handler<usize, ()> for TestHandler {
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
         match token {
             SERVER => {
@@ -86,3 +89,24 @@ pub fn test_register_deregister() {
 
     assert!(handler.state == 2, "unexpected final state {}", handler.state);
 }
+
+#[test]
+pub fn test_is_registered() {
+    debug!("Starting TEST_IS_REGISTERED");
+    let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+
+    let sock = TcpSocket::v4().unwrap();
+    assert!(!event_loop.is_registered(CLIENT));
+
+    event_loop.register_opt(&sock, CLIENT, Interest::readable(), PollOpt::edge()).unwrap();
+    assert!(event_loop.is_registered(CLIENT));
+    assert!(!event_loop.is_registered(SERVER));
+
+    // Moving the handle to another token moves the registration along
+    event_loop.reregister(&sock, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+    assert!(!event_loop.is_registered(CLIENT));
+    assert!(event_loop.is_registered(SERVER));
+
+    event_loop.deregister(&sock).unwrap();
+    assert!(!event_loop.is_registered(SERVER));
+}
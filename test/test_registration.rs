@@ -0,0 +1,59 @@
+use std::time::Duration;
+use mio::*;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const WRITER: Token = Token(0);
+
+struct TestHandler {
+    writable: usize
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn writable(&mut self, _: &mut TestEventLoop, token: Token) {
+        assert_eq!(token, WRITER);
+        self.writable += 1;
+    }
+}
+
+#[test]
+pub fn test_registration_drop_deregisters() {
+    debug!("Starting TEST_REGISTRATION_DROP_DEREGISTERS");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let (_reader, writer) = pipe().unwrap();
+
+    let reg = event_loop.register_owned(writer, WRITER, Interest::writable(), PollOpt::level()).unwrap();
+    assert_eq!(reg.token(), WRITER);
+    assert!(event_loop.is_registered(WRITER));
+
+    // The pipe is writable, so the handler hears about it
+    let handler = event_loop.run_once(TestHandler { writable: 0 }, Some(Duration::seconds(1))).ok().expect("failed to run event loop");
+    assert_eq!(1, handler.writable);
+
+    drop(reg);
+
+    // Closing the pipe alone would silence it, but leave the token behind
+    assert!(!event_loop.is_registered(WRITER));
+
+    // Once the guard is gone, no more events are delivered
+    let handler = event_loop.run_once(handler, Some(Duration::milliseconds(100))).ok().expect("failed to run event loop");
+    assert_eq!(1, handler.writable);
+}
+
+#[test]
+pub fn test_registration_into_inner() {
+    debug!("Starting TEST_REGISTRATION_INTO_INNER");
+    let mut event_loop: TestEventLoop = EventLoop::new().unwrap();
+
+    let (_reader, writer) = pipe().unwrap();
+
+    let reg = event_loop.register_owned(writer, WRITER, Interest::writable(), PollOpt::level()).unwrap();
+    let writer = reg.into_inner();
+
+    // The handle was deregistered, so it can be registered again
+    event_loop.register_opt(&writer, WRITER, Interest::writable(), PollOpt::level()).unwrap();
+}
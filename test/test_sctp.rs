@@ -0,0 +1,132 @@
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::sctp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+
+struct TestHandler {
+    srv: SctpSocket,
+    received: Vec<(Vec<u8>, u16)>
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(SERVER, token);
+
+        let mut buf = [0u8; 64];
+
+        loop {
+            let msg = match self.srv.recv(&mut buf).unwrap() {
+                NonBlock::Ready(msg) => msg,
+                NonBlock::WouldBlock => break
+            };
+
+            assert!(msg.complete);
+
+            // Echo the message back to its sender, on the same stream
+            self.srv.send_to(&buf[..msg.len], msg.stream, &msg.addr).unwrap();
+            self.received.push((buf[..msg.len].to_vec(), msg.stream));
+        }
+
+        if self.received.len() == 2 {
+            event_loop.shutdown();
+        }
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_sctp_streams() {
+    debug!("Starting TEST_SCTP_STREAMS");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    // One-to-many on the server side, one-to-one on the client side
+    let srv = match SctpSocket::one_to_many() {
+        Ok(srv) => srv,
+        // The sctp kernel module is not available
+        Err(ref e) if e.is_unsupported() => return,
+        Err(e) => panic!("failed to create socket; err={:?}", e)
+    };
+
+    srv.set_reuseaddr(true).unwrap();
+    srv.bind(&addr).unwrap();
+    srv.listen(16).unwrap();
+
+    event_loop.register(&srv, SERVER).unwrap();
+
+    let cli = SctpSocket::one_to_one().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Let the association come up
+    sleep(Duration::milliseconds(100));
+
+    assert_eq!(3, cli.send(b"one", 0).unwrap().unwrap());
+    assert_eq!(5, cli.send(b"three", 1).unwrap().unwrap());
+
+    let handler = event_loop.run(TestHandler { srv: srv, received: vec![] })
+        .ok().expect("failed to execute event loop");
+
+    assert_eq!(vec![(b"one".to_vec(), 0), (b"three".to_vec(), 1)], handler.received);
+
+    sleep(Duration::milliseconds(100));
+
+    // The boundaries hold: each message is read on its own, even though the
+    // buffer could hold both
+    let mut buf = [0u8; 64];
+
+    let msg = cli.recv(&mut buf).unwrap().unwrap();
+    assert_eq!(b"one", &buf[..msg.len]);
+    assert_eq!(0, msg.stream);
+
+    let msg = cli.recv(&mut buf).unwrap().unwrap();
+    assert_eq!(b"three", &buf[..msg.len]);
+    assert_eq!(1, msg.stream);
+    assert!(msg.complete);
+
+    assert!(cli.recv(&mut buf).unwrap().would_block());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_sctp_accept() {
+    debug!("Starting TEST_SCTP_ACCEPT");
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let mut srv = match SctpAcceptor::new(&addr, 16) {
+        Ok(srv) => srv,
+        // The sctp kernel module is not available
+        Err(ref e) if e.is_unsupported() => return,
+        Err(e) => panic!("failed to create acceptor; err={:?}", e)
+    };
+
+    // Nothing is pending yet
+    assert!(srv.accept().unwrap().would_block());
+
+    let cli = SctpSocket::one_to_one().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Let the association come up
+    sleep(Duration::milliseconds(100));
+
+    let conn = srv.accept().unwrap().unwrap();
+
+    assert_eq!(3, cli.send(b"one", 2).unwrap().unwrap());
+
+    sleep(Duration::milliseconds(100));
+
+    let mut buf = [0u8; 64];
+
+    let msg = conn.recv(&mut buf).unwrap().unwrap();
+    assert_eq!(b"one", &buf[..msg.len]);
+    assert_eq!(2, msg.stream);
+}
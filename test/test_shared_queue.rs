@@ -0,0 +1,62 @@
+use std::thread::Thread;
+use std::time::Duration;
+use mio::*;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const QUEUE: Token = Token(0);
+
+struct TestHandler {
+    rx: QueueReceiver<usize>,
+    received: Vec<usize>
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        assert_eq!(QUEUE, token);
+
+        while let Some(msg) = self.rx.recv() {
+            self.received.push(msg);
+        }
+    }
+}
+
+fn consume(rx: QueueReceiver<usize>) -> Vec<usize> {
+    let mut event_loop = EventLoop::new().unwrap();
+    event_loop.register(&rx, QUEUE).unwrap();
+
+    let handler = event_loop.run_for(TestHandler { rx: rx, received: vec![] }, Duration::milliseconds(500))
+        .ok().expect("failed to execute event loop");
+
+    handler.received
+}
+
+#[test]
+pub fn test_shared_queue() {
+    debug!("Starting TEST_SHARED_QUEUE");
+    let queue = SharedQueue::new();
+
+    let first = queue.receiver().unwrap();
+    let second = queue.receiver().unwrap();
+
+    let first = Thread::scoped(move || consume(first));
+    let second = Thread::scoped(move || consume(second));
+
+    for i in range(0, 1000us) {
+        queue.send(i).unwrap();
+    }
+
+    let first = first.join().ok().unwrap();
+    let second = second.join().ok().unwrap();
+
+    // Every message was received by exactly one of the loops
+    let mut all = first.clone();
+    all.push_all(second.as_slice());
+    all.sort();
+
+    assert_eq!(range(0, 1000us).collect::<Vec<usize>>(), all);
+    assert_eq!(0, queue.len());
+}
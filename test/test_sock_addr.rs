@@ -0,0 +1,45 @@
+use mio::net::*;
+use mio::net::AddressFamily::{Inet, Inet6, Unix};
+
+#[test]
+pub fn test_sock_addr_family() {
+    debug!("Starting TEST_SOCK_ADDR_FAMILY");
+
+    let v4 = SockAddr::parse("127.0.0.1:8080").unwrap();
+    assert_eq!(Inet, v4.family());
+    assert_eq!(Some((IPv4Addr(127, 0, 0, 1), 8080)), v4.as_inet());
+    assert!(v4.as_unix().is_none());
+
+    let v6 = SockAddr::parse("[::1]:8080").unwrap();
+    assert_eq!(Inet6, v6.family());
+    assert_eq!(Some((IPv6Addr(0, 0, 0, 0, 0, 0, 0, 1), 8080)), v6.as_inet());
+
+    let unix = SockAddr::from_path(Path::new("/tmp/mio.sock"));
+    assert_eq!(Unix, unix.family());
+    assert_eq!(Some(&Path::new("/tmp/mio.sock")), unix.as_unix());
+    assert!(unix.as_inet().is_none());
+}
+
+#[test]
+pub fn test_sock_addr_ip_port() {
+    debug!("Starting TEST_SOCK_ADDR_IP_PORT");
+
+    let v4 = SockAddr::inet(IPv4Addr(10, 0, 0, 1), 8080);
+    assert_eq!(Some(IPv4Addr(10, 0, 0, 1)), v4.ip());
+    assert_eq!(Some(8080), v4.port());
+    assert_eq!(Inet, v4.family());
+
+    let v6 = SockAddr::inet(IPv6Addr(0, 0, 0, 0, 0, 0, 0, 1), 443);
+    assert_eq!(Some(IPv6Addr(0, 0, 0, 0, 0, 0, 0, 1)), v6.ip());
+    assert_eq!(Some(443), v6.port());
+    assert_eq!(Inet6, v6.family());
+
+    // Round trips through the accessors
+    let parsed = SockAddr::parse("192.168.1.20:53").unwrap();
+    let rebuilt = SockAddr::inet(parsed.ip().unwrap(), parsed.port().unwrap());
+    assert_eq!(parsed.as_inet(), rebuilt.as_inet());
+
+    let unix = SockAddr::from_path(Path::new("/tmp/mio.sock"));
+    assert!(unix.ip().is_none());
+    assert!(unix.port().is_none());
+}
@@ -0,0 +1,123 @@
+use std::old_io::PermissionDenied;
+use std::old_io::timer::sleep;
+use std::thread::Thread;
+use std::time::Duration;
+use mio::{IoReader, IoWriter};
+use mio::net::{Socket, SockAddr};
+use mio::net::tcp::TcpSocket;
+use mio::net::udp::UdpSocket;
+use super::localhost;
+
+// DSCP expedited forwarding
+const EF: u8 = 46 << 2;
+
+#[test]
+pub fn test_tcp_tos() {
+    debug!("Starting TEST_TCP_TOS");
+    let sock = TcpSocket::v4().unwrap();
+
+    sock.set_tos(EF).unwrap();
+    assert_eq!(EF, sock.tos().unwrap());
+}
+
+#[test]
+pub fn test_udp_tos() {
+    debug!("Starting TEST_UDP_TOS");
+    let sock = UdpSocket::v4().unwrap();
+
+    sock.set_tos(EF).unwrap();
+    assert_eq!(EF, sock.tos().unwrap());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_incoming_cpu() {
+    debug!("Starting TEST_INCOMING_CPU");
+    let sock = TcpSocket::v4().unwrap();
+
+    assert_eq!(-1, sock.incoming_cpu().unwrap());
+
+    sock.set_incoming_cpu(0).unwrap();
+    assert_eq!(0, sock.incoming_cpu().unwrap());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_bind_device() {
+    debug!("Starting TEST_BIND_DEVICE");
+    let sock = TcpSocket::v4().unwrap();
+
+    assert_eq!(None, sock.device().unwrap());
+
+    match sock.bind_device("lo") {
+        Ok(()) => {}
+        // Without CAP_NET_RAW there is nothing more to check
+        Err(ref e) if e.as_io_error().kind == PermissionDenied => return,
+        Err(e) => panic!("bind_device failed; err={:?}", e)
+    }
+
+    assert_eq!(Some("lo".to_string()), sock.device().unwrap());
+
+    // Names that do not fit in IFNAMSIZ are rejected
+    assert!(sock.bind_device("an-interface-name-too-long").is_err());
+}
+
+#[test]
+#[cfg(not(target_os = "linux"))]
+pub fn test_bind_device_unsupported() {
+    debug!("Starting TEST_BIND_DEVICE_UNSUPPORTED");
+    let sock = TcpSocket::v4().unwrap();
+
+    assert!(sock.bind_device("lo0").unwrap_err().is_unsupported());
+    assert!(sock.device().unwrap_err().is_unsupported());
+}
+
+#[test]
+pub fn test_tcp_timeouts() {
+    debug!("Starting TEST_TCP_TIMEOUTS");
+    let sock = TcpSocket::v4().unwrap();
+
+    assert_eq!(None, sock.read_timeout().unwrap());
+    assert_eq!(None, sock.write_timeout().unwrap());
+
+    sock.set_read_timeout(Some(Duration::milliseconds(1500))).unwrap();
+    sock.set_write_timeout(Some(Duration::seconds(3))).unwrap();
+
+    assert_eq!(Some(Duration::milliseconds(1500)), sock.read_timeout().unwrap());
+    assert_eq!(Some(Duration::seconds(3)), sock.write_timeout().unwrap());
+
+    sock.set_read_timeout(None).unwrap();
+
+    assert_eq!(None, sock.read_timeout().unwrap());
+    assert_eq!(Some(Duration::seconds(3)), sock.write_timeout().unwrap());
+}
+
+#[test]
+pub fn test_nonblocking() {
+    debug!("Starting TEST_NONBLOCKING");
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let recv = UdpSocket::v4().unwrap();
+    recv.bind(&addr).unwrap();
+
+    let mut buf = [0u8; 16];
+
+    // Sockets start out non-blocking
+    assert!(recv.is_nonblocking().unwrap());
+    assert!(recv.read_slice(&mut buf).unwrap().would_block());
+
+    recv.set_nonblocking(false).unwrap();
+    assert!(!recv.is_nonblocking().unwrap());
+
+    let _t = Thread::scoped(move || {
+        sleep(Duration::milliseconds(100));
+
+        let send = UdpSocket::v4().unwrap();
+        send.connect(&addr).unwrap();
+        send.write_slice(b"hello").unwrap();
+    });
+
+    // Blocks until the datagram arrives instead of returning WouldBlock
+    assert_eq!(5, recv.read_slice(&mut buf).unwrap().unwrap());
+    assert_eq!(b"hello", &buf[..5]);
+}
@@ -0,0 +1,105 @@
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const FIRST: Token = Token(1);
+const SECOND: Token = Token(2);
+
+struct TestHandler {
+    conns: Vec<TcpSocket>,
+    clients: Vec<TcpSocket>,
+    // Bytes read from the second connection, whichever handler read them
+    received: Vec<u8>,
+    spurious: usize
+}
+
+impl TestHandler {
+    fn read_second(&mut self) -> bool {
+        let mut buf = [0u8; 16];
+
+        match self.conns[1].read_slice(&mut buf).unwrap() {
+            NonBlock::Ready(cnt) => {
+                self.received.push_all(&buf[..cnt]);
+                true
+            }
+            NonBlock::WouldBlock => false
+        }
+    }
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        match token {
+            FIRST => {
+                // Also drains the second connection, whose event is
+                // already queued for this tick
+                let mut buf = [0u8; 16];
+                assert_eq!(3, self.conns[0].read_slice(&mut buf).unwrap().unwrap());
+                assert!(self.read_second());
+            }
+            SECOND => {
+                if self.read_second() {
+                    event_loop.shutdown();
+                    return;
+                }
+
+                // Readable, but nothing to read: not an error, the next
+                // event comes with the next data
+                self.spurious += 1;
+                self.clients[1].write_slice(b"two").unwrap();
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+#[test]
+pub fn test_spurious_wouldblock() {
+    debug!("Starting TEST_SPURIOUS_WOULDBLOCK");
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    let clients: Vec<TcpSocket> = range(0, 2us).map(|_| {
+        let sock = TcpSocket::v4().unwrap();
+        sock.connect(&addr).unwrap();
+        sock
+    }).collect();
+
+    sleep(Duration::milliseconds(100));
+
+    let conns: Vec<TcpSocket> = range(0, 2us).map(|_| {
+        srv.accept().unwrap().unwrap()
+    }).collect();
+
+    for sock in clients.iter() {
+        sock.write_slice(b"one").unwrap();
+    }
+
+    sleep(Duration::milliseconds(100));
+
+    // The first connection is dispatched first, so that both events are
+    // delivered in the same tick in a known order
+    event_loop.set_priority(FIRST, 1);
+    event_loop.register_opt(&conns[0], FIRST, Interest::readable(), PollOpt::edge()).unwrap();
+    event_loop.register_opt(&conns[1], SECOND, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let handler = TestHandler { conns: conns, clients: clients, received: vec![], spurious: 0 };
+    let handler = event_loop.run(handler).ok().expect("failed to execute event loop");
+
+    assert_eq!(1, handler.spurious);
+    assert_eq!(b"onetwo", handler.received.as_slice());
+}
@@ -0,0 +1,38 @@
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use mio::net::*;
+use mio::net::tcp::*;
+use super::localhost;
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_tcp_info() {
+    debug!("Starting TEST_TCP_INFO");
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let mut srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Give the handshake time to complete
+    sleep(Duration::milliseconds(100));
+
+    let conn = srv.accept().unwrap().unwrap();
+
+    for sock in [&cli, &conn].iter() {
+        let info = sock.tcp_info().unwrap();
+
+        // Measured from the handshake, and well under a second on loopback
+        assert!(info.rtt > Duration::zero(), "rtt={:?}", info.rtt);
+        assert!(info.rtt < Duration::seconds(1), "rtt={:?}", info.rtt);
+        assert!(info.rto >= info.rtt);
+
+        assert_eq!(0, info.total_retrans);
+        assert!(info.snd_cwnd > 0);
+        assert!(info.snd_mss > 0);
+    }
+}
@@ -0,0 +1,58 @@
+use mio::net::*;
+use mio::net::udp::*;
+use super::localhost;
+
+fn bound() -> (UdpSocket, String) {
+    let addr = localhost();
+    let sock = UdpSocket::bound(&SockAddr::parse(addr.as_slice()).unwrap()).unwrap();
+    (sock, addr)
+}
+
+#[test]
+pub fn test_udp_send_recv_mmsg() {
+    debug!("Starting TEST_UDP_SEND_RECV_MMSG");
+
+    let (tx, tx_addr) = bound();
+    let (rx1, addr1) = bound();
+    let (rx2, addr2) = bound();
+
+    let packets = [
+        (&b"one"[..], SockAddr::parse(addr1.as_slice()).unwrap()),
+        (&b"two"[..], SockAddr::parse(addr2.as_slice()).unwrap()),
+        (&b"three"[..], SockAddr::parse(addr1.as_slice()).unwrap()),
+        (&b""[..], SockAddr::parse(addr2.as_slice()).unwrap()),
+    ];
+
+    assert_eq!(4, tx.send_mmsg(&packets).unwrap().unwrap());
+
+    let mut a = [0u8; 16];
+    let mut b = [0u8; 16];
+    let mut c = [0u8; 16];
+
+    {
+        let mut bufs = [&mut a[..], &mut b[..], &mut c[..]];
+        let received = rx1.recv_mmsg(&mut bufs).unwrap().unwrap();
+
+        assert_eq!(2, received.len());
+        assert_eq!(3, received[0].0);
+        assert_eq!(5, received[1].0);
+        assert_eq!(SockAddr::parse(tx_addr.as_slice()).unwrap().as_inet(), received[0].1.as_inet());
+    }
+
+    assert_eq!(b"one", &a[..3]);
+    assert_eq!(b"three", &b[..5]);
+
+    {
+        let mut bufs = [&mut a[..], &mut b[..], &mut c[..]];
+        let received = rx2.recv_mmsg(&mut bufs).unwrap().unwrap();
+
+        // The empty datagram is received as well
+        assert_eq!(2, received.len());
+        assert_eq!(3, received[0].0);
+        assert_eq!(0, received[1].0);
+
+        assert!(rx2.recv_mmsg(&mut bufs).unwrap().would_block());
+    }
+
+    assert_eq!(b"two", &a[..3]);
+}
@@ -0,0 +1,60 @@
+use mio::*;
+use mio::net::*;
+use mio::net::udp::*;
+use mio::buf::{ByteBuf, SliceBuf};
+use std::old_io::net::ip::Ipv4Addr;
+use std::old_io::timer::sleep;
+use std::time::Duration;
+use super::localhost;
+
+// Retries a non-blocking receive until the datagram shows up
+fn wait<T, F: FnMut() -> NonBlock<T>>(mut f: F) -> T {
+    for _ in range(0, 100us) {
+        if let NonBlock::Ready(v) = f() {
+            return v;
+        }
+
+        sleep(Duration::milliseconds(10));
+    }
+
+    panic!("no datagram received");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+pub fn test_udp_pktinfo() {
+    debug!("Starting TEST_UDP_PKTINFO");
+
+    let port = match SockAddr::parse(localhost().as_slice()).unwrap() {
+        SockAddr::InetAddr(_, port) => port,
+        _ => panic!("unexpected address")
+    };
+
+    // Bound to the wildcard address, the server only learns which address
+    // was used from the control message
+    let srv = UdpSocket::bound(&SockAddr::InetAddr(Ipv4Addr(0, 0, 0, 0), port)).unwrap();
+    srv.set_recv_pktinfo(true).unwrap();
+
+    let cli_addr = SockAddr::parse(localhost().as_slice()).unwrap();
+    let mut cli = UdpSocket::bound(&cli_addr).unwrap();
+    let srv_addr = SockAddr::InetAddr(Ipv4Addr(127, 0, 0, 1), port);
+
+    cli.send_to(&mut SliceBuf::wrap(b"ping"), &srv_addr).unwrap();
+
+    let mut buf = ByteBuf::mut_with_capacity(64);
+    let (cnt, src, dst) = wait(|| srv.recv_from_pktinfo(&mut buf).unwrap());
+
+    assert_eq!(4, cnt);
+    assert_eq!(b"ping", buf.flip().bytes());
+    assert_eq!(srv_addr.as_inet(), dst.as_inet());
+    assert_eq!(cli_addr.as_inet(), src.as_inet());
+
+    // Reply from the address the request was sent to
+    srv.send_from(&mut SliceBuf::wrap(b"pong"), &src, &dst).unwrap();
+
+    let mut buf = ByteBuf::mut_with_capacity(64);
+    let from = wait(|| cli.recv_from(&mut buf).unwrap());
+
+    assert_eq!(srv_addr.as_inet(), from.as_inet());
+    assert_eq!(b"pong", buf.flip().bytes());
+}
@@ -31,7 +31,10 @@ impl UdpHandler {
     }
 }
 
-impl Handler<usize, ()> for UdpHandler {
+impl Handler for UdpHandler {
+    type Timeout = usize;
+    type Message = ();
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
         match token {
             LISTENER => {
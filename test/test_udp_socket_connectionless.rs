@@ -1,9 +1,10 @@
 use mio::*;
 use mio::net::*;
 use mio::net::udp::*;
-use mio::buf::{RingBuf, SliceBuf};
+use mio::buf::{ByteBuf, RingBuf, SliceBuf};
 use std::str;
 use std::old_io::net::ip::{Ipv4Addr};
+use super::localhost;
 
 type TestEventLoop = EventLoop<usize, ()>;
 
@@ -32,7 +33,10 @@ impl UdpHandler {
     }
 }
 
-impl Handler<usize, ()> for UdpHandler {
+impl Handler for UdpHandler {
+    type Timeout = usize;
+    type Message = ();
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
         match token {
             LISTENER => {
@@ -99,3 +103,34 @@ pub fn test_udp_socket_connectionless() {
     event_loop.run(UdpHandler::new(send_sock, recv_sock, "hello world")).ok().expect("Failed to run the actual event listener loop");
 }
 
+
+#[test]
+pub fn test_udp_empty_datagram() {
+    debug!("Starting TEST_UDP_EMPTY_DATAGRAM");
+    let recv_addr = SockAddr::parse(localhost().as_slice()).unwrap();
+    let send_addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let mut recv_sock = UdpSocket::bound(&recv_addr).unwrap();
+    let mut send_sock = UdpSocket::bound(&send_addr).unwrap();
+
+    let mut rx_buf = ByteBuf::mut_with_capacity(16);
+
+    // Nothing was sent yet, which is not the same as an empty datagram
+    assert!(recv_sock.recv_from(&mut rx_buf).unwrap().would_block());
+
+    send_sock.send_to(&mut SliceBuf::wrap(b""), &recv_addr).unwrap().unwrap();
+    send_sock.send_to(&mut SliceBuf::wrap(b""), &recv_addr).unwrap().unwrap();
+
+    recv_sock.set_nonblocking(false).unwrap();
+
+    let src = recv_sock.recv_from(&mut rx_buf).unwrap().unwrap();
+    assert!(src.as_inet() == send_addr.as_inet());
+    assert_eq!(0, rx_buf.flip().remaining());
+
+    // Plain reads return zero instead of EOF
+    let mut buf = [0u8; 16];
+    assert_eq!(0, recv_sock.read_slice(&mut buf).unwrap().unwrap());
+
+    recv_sock.set_nonblocking(true).unwrap();
+    assert!(recv_sock.read_slice(&mut buf).unwrap().would_block());
+}
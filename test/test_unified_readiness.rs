@@ -0,0 +1,90 @@
+use mio::*;
+use mio::net::*;
+use mio::net::tcp::*;
+use std::default::Default;
+use super::localhost;
+
+type TestEventLoop = EventLoop<usize, ()>;
+
+const SERVER: Token = Token(0);
+const CONN: Token = Token(1);
+
+struct TestHandler {
+    srv: TcpAcceptor,
+    cli: TcpSocket,
+    conn: Option<TcpSocket>,
+    events: Vec<Ready>
+}
+
+impl Handler for TestHandler {
+    type Timeout = usize;
+    type Message = ();
+
+    fn readable(&mut self, _event_loop: &mut TestEventLoop, token: Token, _: ReadHint) {
+        panic!("unexpected readable callback; token={:?}", token);
+    }
+
+    fn writable(&mut self, _event_loop: &mut TestEventLoop, token: Token) {
+        panic!("unexpected writable callback; token={:?}", token);
+    }
+
+    fn ready(&mut self, event_loop: &mut TestEventLoop, token: Token, readiness: Ready) {
+        match token {
+            SERVER => {
+                assert!(readiness.is_readable());
+
+                let conn = self.srv.accept().unwrap().unwrap();
+
+                // Data is waiting before the connection is registered, so it
+                // is both readable and writable on the first poll
+                self.cli.write_slice(b"hello").unwrap().unwrap();
+
+                event_loop.register_opt(&conn, CONN, Interest::readable() | Interest::writable(), PollOpt::edge()).unwrap();
+                self.conn = Some(conn);
+            }
+            CONN => {
+                self.events.push(readiness);
+                event_loop.shutdown();
+            }
+            _ => panic!("unexpected token {:?}", token)
+        }
+    }
+}
+
+#[test]
+pub fn test_unified_readiness() {
+    debug!("Starting TEST_UNIFIED_READINESS");
+
+    let config = EventLoopConfig {
+        unified_readiness: true,
+        .. Default::default()
+    };
+
+    let mut event_loop = EventLoop::configured(config).unwrap();
+
+    let addr = SockAddr::parse(localhost().as_slice()).unwrap();
+
+    let srv = TcpSocket::v4().unwrap();
+    srv.set_reuseaddr(true).unwrap();
+
+    let srv = srv.bind(&addr).unwrap().listen(256).unwrap();
+    event_loop.register_opt(&srv, SERVER, Interest::readable(), PollOpt::edge()).unwrap();
+
+    let cli = TcpSocket::v4().unwrap();
+    cli.connect(&addr).unwrap();
+
+    let handler = TestHandler {
+        srv: srv,
+        cli: cli,
+        conn: None,
+        events: vec![]
+    };
+
+    let handler = event_loop.run(handler)
+        .ok().expect("failed to execute event loop");
+
+    // A single callback carries both kinds of readiness
+    assert_eq!(1, handler.events.len());
+    assert!(handler.events[0].is_readable());
+    assert!(handler.events[0].is_writable());
+}
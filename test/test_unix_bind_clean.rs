@@ -0,0 +1,81 @@
+use mio::net::*;
+use mio::net::pipe::*;
+use std::old_io::{fs, TempDir};
+use std::old_io::fs::PathExtensions;
+
+#[test]
+pub fn test_unix_bind_clean() {
+    debug!("Starting TEST_UNIX_BIND_CLEAN");
+
+    let tmp_dir = TempDir::new("test_unix_bind_clean").unwrap();
+    let path = tmp_dir.path().join(Path::new("sock"));
+    let addr = SockAddr::from_path(path.clone());
+
+    // Dropping the acceptor closes the socket but leaves the file behind
+    drop(UnixSocket::stream().unwrap()
+        .bind(&addr).unwrap()
+        .listen(256).unwrap());
+
+    assert!(path.exists());
+    assert!(UnixSocket::stream().unwrap().bind(&addr).unwrap_err().is_addr_in_use());
+
+    // The stale file is replaced
+    let _srv = UnixSocket::stream().unwrap()
+        .bind_clean(&addr).unwrap()
+        .listen(256).unwrap();
+
+    // A live server keeps its path
+    let err = UnixSocket::stream().unwrap().bind_clean(&addr).unwrap_err();
+    assert!(err.is_addr_in_use());
+    assert!(path.exists());
+
+    let cli = UnixSocket::stream().unwrap();
+    cli.connect(&addr).unwrap();
+}
+
+#[test]
+pub fn test_unix_bind_clean_regular_file() {
+    debug!("Starting TEST_UNIX_BIND_CLEAN_REGULAR_FILE");
+
+    let tmp_dir = TempDir::new("test_unix_bind_clean_regular_file").unwrap();
+    let path = tmp_dir.path().join(Path::new("sock"));
+    fs::File::create(&path).unwrap();
+
+    // Anything that is not a socket is never removed
+    let err = UnixSocket::stream().unwrap()
+        .bind_clean(&SockAddr::from_path(path.clone())).unwrap_err();
+
+    assert!(err.is_addr_in_use());
+    assert!(path.exists());
+}
+
+#[test]
+pub fn test_unix_bind_with_cleanup() {
+    debug!("Starting TEST_UNIX_BIND_WITH_CLEANUP");
+
+    let tmp_dir = TempDir::new("test_unix_bind_with_cleanup").unwrap();
+    let path = tmp_dir.path().join(Path::new("sock"));
+    let addr = SockAddr::from_path(path.clone());
+
+    let srv = UnixSocket::stream().unwrap()
+        .bind_with_cleanup(&addr).unwrap()
+        .listen(256).unwrap();
+
+    assert!(path.exists());
+    drop(srv);
+    assert!(!path.exists());
+
+    // A path that was replaced after binding belongs to someone else
+    let srv = UnixSocket::stream().unwrap()
+        .bind_with_cleanup(&addr).unwrap()
+        .listen(256).unwrap();
+
+    fs::unlink(&path).unwrap();
+
+    let _other = UnixSocket::stream().unwrap()
+        .bind(&addr).unwrap()
+        .listen(256).unwrap();
+
+    drop(srv);
+    assert!(path.exists());
+}
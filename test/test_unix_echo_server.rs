@@ -234,7 +234,10 @@ impl EchoHandler {
     }
 }
 
-impl Handler<usize, ()> for EchoHandler {
+impl Handler for EchoHandler {
+    type Timeout = usize;
+    type Message = ();
+
     fn readable(&mut self, event_loop: &mut TestEventLoop, token: Token, hint: ReadHint) {
         assert!(hint.is_data());
 
@@ -0,0 +1,31 @@
+use mio::*;
+use mio::net::*;
+use mio::net::pipe::*;
+use std::old_io::TempDir;
+
+#[test]
+pub fn test_unix_peer_cred() {
+    debug!("Starting TEST_UNIX_PEER_CRED");
+
+    let tmp_dir = TempDir::new("test_unix_peer_cred").unwrap();
+    let addr = SockAddr::from_path(tmp_dir.path().join(Path::new("sock")));
+
+    let mut srv = UnixSocket::stream().unwrap()
+        .bind(&addr).unwrap()
+        .listen(256).unwrap();
+
+    let cli = UnixSocket::stream().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Connecting a unix socket completes immediately, so the connection is
+    // already waiting to be accepted
+    let conn = srv.accept().unwrap().unwrap();
+
+    // Both ends belong to this process
+    let (uid, gid, pid) = conn.peer_cred().unwrap();
+    assert_eq!((uid, gid, pid), cli.peer_cred().unwrap());
+
+    if cfg!(target_os = "linux") {
+        assert!(pid != 0);
+    }
+}
@@ -0,0 +1,37 @@
+use mio::*;
+use mio::net::*;
+use mio::net::pipe::*;
+use std::old_io::TempDir;
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_unix_seqpacket() {
+    debug!("Starting TEST_UNIX_SEQPACKET");
+
+    let tmp_dir = TempDir::new("test_unix_seqpacket").unwrap();
+    let addr = SockAddr::from_path(tmp_dir.path().join(Path::new("sock")));
+
+    let mut srv = UnixSeqpacketAcceptor::new(&addr, 256).unwrap();
+
+    let cli = UnixSocket::seqpacket().unwrap();
+    cli.connect(&addr).unwrap();
+
+    // Connecting a unix socket completes immediately, so the connection is
+    // already waiting to be accepted
+    let conn = srv.accept().unwrap().unwrap();
+
+    cli.write_slice(b"foo").unwrap().unwrap();
+    cli.write_slice(b"barbaz").unwrap().unwrap();
+
+    // Each read returns exactly one message, where a stream would have
+    // returned both at once
+    let mut buf = [0u8; 64];
+
+    let n = conn.read_slice(&mut buf).unwrap().unwrap();
+    assert_eq!(b"foo", &buf[..n]);
+
+    let n = conn.read_slice(&mut buf).unwrap().unwrap();
+    assert_eq!(b"barbaz", &buf[..n]);
+
+    assert!(conn.read_slice(&mut buf).unwrap().would_block());
+}